@@ -0,0 +1,44 @@
+use std::sync::Arc;
+use async_trait::async_trait;
+use anyhow::Result;
+use crate::auth::GetToken;
+
+
+/// An authentication provider that merges the headers of several providers.
+///
+/// Headers are returned in provider order, so when two providers set the same header
+/// the value of the later one takes precedence.
+#[derive(Debug, Clone)]
+pub struct Chain(pub Vec<Arc<dyn GetToken>>);
+
+
+/// Implements the `GetToken` trait for `Chain`.
+#[async_trait]
+impl GetToken for Chain {
+    async fn get_auth_headers(&self) -> Result<Vec<(String, String)>> {
+        let mut headers = Vec::new();
+        for provider in &self.0 {
+            headers.extend(provider.get_auth_headers().await?);
+        }
+        Ok(headers)
+    }
+
+    async fn get_request_auth_headers(&self, method: &str, path: &str, body: &[u8]) -> Result<Vec<(String, String)>> {
+        let mut headers = Vec::new();
+        for provider in &self.0 {
+            headers.extend(provider.get_request_auth_headers(method, path, body).await?);
+        }
+        Ok(headers)
+    }
+
+    fn name(&self) -> &str {
+        "chain"
+    }
+
+    /// Records the rejection for each chained provider, as any of their headers may have been rejected.
+    fn record_rejection(&self, reason: &str) {
+        for provider in &self.0 {
+            provider.record_rejection(reason);
+        }
+    }
+}
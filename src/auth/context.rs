@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use crate::auth::{CredentialsCallbackHandle, CredentialsEvent, GetToken, RotationCallbacks};
+use crate::auth::telemetry::observed_token_provider;
+use crate::config::AuthConfig;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::grpc::interceptor::{HeaderCache, TonicInterceptor};
+
+
+/// Shared authentication state for all telemetry pipelines.
+///
+/// Token providers are created lazily, once per distinct `AuthConfig`, and reused by every
+/// exporter requesting the same configuration, so a single token cache serves all signals. Likewise,
+/// the gRPC exporters of a configuration share the authorization headers cached for their interceptors.
+/// Authentication failures of the providers are recorded, see `auth_failure_count`, and the token
+/// refreshes and failures of the providers are notified to the callbacks of the context only.
+#[derive(Debug, Default)]
+pub struct AuthContext {
+    providers: Mutex<HashMap<AuthConfig, Arc<dyn GetToken>>>,
+    #[cfg(not(feature = "noop"))]
+    header_caches: Mutex<HashMap<AuthConfig, Arc<HeaderCache>>>,
+    callbacks: RotationCallbacks,
+}
+
+
+impl AuthContext {
+    /// Creates a new, empty `AuthContext`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the token provider for the given configuration, creating it on first use.
+    /// # Arguments
+    /// * `config` - The authentication configuration.
+    /// # Returns
+    /// An `Arc<dyn GetToken>` shared with every other caller using the same configuration.
+    pub fn token_provider(&self, config: &AuthConfig) -> Arc<dyn GetToken> {
+        let mut providers = self.providers.lock().unwrap_or_else(|e| e.into_inner());
+        providers
+            .entry(config.clone())
+            .or_insert_with(|| observed_token_provider(config, &self.callbacks))
+            .clone()
+    }
+
+    /// Returns the authorization headers cached for the gRPC interceptors of the given configuration,
+    /// creating the cache on first use.
+    /// # Arguments
+    /// * `config` - The authentication configuration.
+    #[cfg(not(feature = "noop"))]
+    pub(crate) fn header_cache(&self, config: &AuthConfig) -> Arc<HeaderCache> {
+        let mut caches = self.header_caches.lock().unwrap_or_else(|e| e.into_inner());
+        caches.entry(config.clone()).or_default().clone()
+    }
+
+    /// Returns a gRPC interceptor authenticating with the token provider of the given configuration,
+    /// reading the header cache shared by the interceptors of the configuration.
+    /// Must be called from within a Tokio runtime.
+    /// # Arguments
+    /// * `config` - The authentication configuration.
+    #[cfg(not(feature = "noop"))]
+    pub(crate) fn interceptor(&self, config: &AuthConfig) -> TonicInterceptor {
+        TonicInterceptor::with_cache(self.token_provider(config), self.header_cache(config))
+    }
+
+    /// Registers a callback notified whenever a token provider of the context refreshes its token or
+    /// its credentials are invalidated, e.g. when the collector rejects an export with HTTP 401 or 403.
+    ///
+    /// Callbacks are invoked synchronously by the provider, so they should return quickly.
+    /// # Arguments
+    /// * `callback` - The function called with each `CredentialsEvent`.
+    /// # Returns
+    /// The handle unregistering the callback.
+    pub fn on_credentials_rotated<F>(&self, callback: F) -> CredentialsCallbackHandle
+    where
+        F: Fn(&CredentialsEvent) + Send + Sync + 'static,
+    {
+        self.callbacks.register(Arc::new(callback))
+    }
+}
@@ -1,18 +1,22 @@
 use std::sync::Arc;
 use async_trait::async_trait;
-use google_cloud_auth::credentials::{Builder, CacheableResource};
+use google_cloud_auth::credentials::{impersonated, Builder, CacheableResource, Credentials};
 use tokio::sync::RwLock;
 use tonic::codegen::http::header::AUTHORIZATION;
 use tonic::codegen::http::HeaderMap;
 use anyhow::Result;
-use crate::auth::GetToken;
-use crate::config::GCPAuthConfig;
+use crate::auth::{CredentialsEvent, GetToken, RotationCallbacks};
+use crate::config::{GCPAuthConfig, GCPImpersonationConfig};
 
 #[derive(Debug, Clone)]
 pub struct GcpAuthProvider {
     token: Arc<RwLock<String>>,
     last_refresh: Arc<RwLock<std::time::SystemTime>>,
     project_id: String,
+    refresh_after: std::time::Duration,
+    scopes: Vec<String>,
+    impersonation: Option<GCPImpersonationConfig>,
+    callbacks: RotationCallbacks,
 }
 
 
@@ -40,10 +44,13 @@ impl GcpAuthProvider {
     /// * `token` - An `Arc<RwLock<String>>` to hold the access token.
     /// * `last_refresh` - An `Arc<RwLock<SystemTime>>` to track the last refresh time.
     /// * `project_id` - A `String` representing the GCP project ID.
+    /// * `refresh_after` - The age after which the token is refreshed.
+    /// * `scopes` - The OAuth scopes of the token. If empty, the default scopes are used.
+    /// * `impersonation` - The service account to impersonate, if any.
     /// # Returns
     /// A new `GcpAuthProvider` instance.
-    fn new(token: Arc<RwLock<String>>, last_refresh: Arc<RwLock<std::time::SystemTime>>, project_id: String) -> Self {
-        Self { token , last_refresh, project_id}
+    fn new(token: Arc<RwLock<String>>, last_refresh: Arc<RwLock<std::time::SystemTime>>, project_id: String, refresh_after: std::time::Duration, scopes: Vec<String>, impersonation: Option<GCPImpersonationConfig>) -> Self {
+        Self { token , last_refresh, project_id, refresh_after, scopes, impersonation, callbacks: RotationCallbacks::default() }
     }
 
     /// Creates a new instance of `GcpAuthProvider` with default values.
@@ -55,21 +62,56 @@ impl GcpAuthProvider {
     /// A new `GcpAuthProvider` instance with default values.
     pub fn new_with_default(config: &GCPAuthConfig) -> Self {
         let token: Arc<RwLock<String>> = Arc::new(RwLock::new(String::new()));
-        let last_refresh: Arc<RwLock<std::time::SystemTime>> = Arc::new(RwLock::new(std::time::SystemTime::from(std::time::UNIX_EPOCH)));
-        Self::new(token, last_refresh, config.project_id.clone())
+        let last_refresh: Arc<RwLock<std::time::SystemTime>> = Arc::new(RwLock::new(std::time::UNIX_EPOCH));
+        let refresh_after = config.refresh_interval.saturating_sub(config.expiry_skew);
+        Self::new(token, last_refresh, config.project_id.clone(), refresh_after, config.scopes.clone(), config.impersonate_service_account.clone())
 
     }
 
-    /// Retrieves a new access token using GCP credentials.
+    /// Sets the credential rotation callbacks notified of the token refreshes.
+    /// # Arguments
+    /// * `callbacks` - The callbacks of the `AuthContext` of the provider.
     /// # Returns
-    /// A `Result<String>` containing the new access token or an error if retrieval fails.
-    async fn get_new_token() -> Result<String> {
+    /// The `GcpAuthProvider` instance notifying the callbacks.
+    pub(crate) fn with_rotation_callbacks(mut self, callbacks: RotationCallbacks) -> Self {
+        self.callbacks = callbacks;
+        self
+    }
+
+    /// Builds the GCP credentials.
+    /// The application default credentials are used, with the configured scopes, exchanged for a token of the impersonated
+    /// service account if impersonation is configured.
+    /// # Returns
+    /// A `Result<Credentials>` containing the credentials or an error if they cannot be built.
+    fn build_credentials(&self) -> Result<Credentials> {
         // Build the credentials using the default builder
-        let credentials = Builder::default().build();
+        let mut builder = Builder::default();
+        if !self.scopes.is_empty() {
+            builder = builder.with_scopes(self.scopes.clone());
+        }
+        let credentials = builder.build()
+            .map_err(|e| anyhow::anyhow!("Error creating auth credentials: {:?}", e))?;
+
+        match &self.impersonation {
+            Some(impersonation) => {
+                let mut builder = impersonated::Builder::from_source_credentials(credentials)
+                    .with_target_principal(impersonation.target_principal.clone())
+                    .with_delegates(impersonation.delegates.clone());
+                if !impersonation.scopes.is_empty() {
+                    builder = builder.with_scopes(impersonation.scopes.clone());
+                }
+                builder.build().map_err(|e| anyhow::anyhow!("Error creating impersonated credentials: {:?}", e))
+            },
+            None => Ok(credentials),
+        }
+    }
 
+    /// Retrieves a new access token using GCP credentials.
+    /// # Returns
+    /// A `Result<String>` containing the new access token or an error if retrieval fails.
+    async fn get_new_token(&self) -> Result<String> {
         // Get the headers containing the access token
-        let headers = credentials
-            .map_err(|e| anyhow::anyhow!("Error creating auth credentials: {:?}", e))?
+        let headers = self.build_credentials()?
             .headers(tonic::Extensions::new())
             .await
             .map_err(|e| anyhow::anyhow!("Error creating auth headers: {:?}", e))?;
@@ -83,30 +125,31 @@ impl GcpAuthProvider {
         }
     }
 
-    /// Authenticates and updates the access token.
+    /// Authenticates and updates the access token, notifying the credential rotation callbacks.
     /// # Returns
     /// A `Result<()>` indicating success or failure of the authentication process.
     async fn authenticate(&self) -> anyhow::Result<()> {
-        let token = Self::get_new_token().await.map_err(|e| anyhow::anyhow!("Error retrieving new token: {:?}", e))?;
+        let token = self.get_new_token().await.map_err(|e| anyhow::anyhow!("Error retrieving new token: {:?}", e))?;
 
         let mut w = self.token.write().await;
         *w = token;
         let mut lr = self.last_refresh.write().await;
         *lr = std::time::SystemTime::now();
+        self.callbacks.notify(CredentialsEvent::Refreshed { provider: "gcp".to_string() });
         Ok(())
     }
 
     /// Retrieves the current access token and updates it if necessary.
-    /// If more than 10 minutes have passed since the last refresh, the token is refreshed.
+    /// If the configured refresh interval, minus the expiry skew, has passed since the last refresh,
+    /// the token is refreshed.
     /// # Returns
     /// A `Result<String>` containing the current access token or an error if retrieval fails.
     async fn get_and_update_token(&self) -> Result<String> {
         let last_refresh = self.last_refresh.read().await;
-        let elapsed = last_refresh.elapsed().unwrap_or(std::time::Duration::new(601,0));
+        let elapsed = last_refresh.elapsed().unwrap_or(std::time::Duration::MAX);
         drop(last_refresh);
 
-        // If more than 10 minutes have passed since last refresh, refresh the token
-        if elapsed.as_secs() > 600 {
+        if elapsed >= self.refresh_after {
             self.authenticate().await.map_err(|e| anyhow::anyhow!("Error authenticating token: {:?}", e))?;
         }
 
@@ -123,4 +166,8 @@ impl GetToken for GcpAuthProvider {
         Ok(vec![("authorization".to_string(), format!("Bearer {}", token)),
                 ("x-goog-user-project".to_string(), self.project_id.clone())])
     }
+
+    fn name(&self) -> &str {
+        "gcp"
+    }
 }
@@ -0,0 +1,85 @@
+use std::fmt::{Debug, Formatter};
+use std::time::{SystemTime, UNIX_EPOCH};
+use async_trait::async_trait;
+use hmac::{Hmac, KeyInit, Mac};
+use sha2::{Digest, Sha256};
+use anyhow::Result;
+use crate::auth::GetToken;
+use crate::config::HMACAuthConfig;
+
+
+/// An authentication provider that signs each request with a shared secret.
+///
+/// The signature is the hex-encoded HMAC-SHA256 of the string
+/// `"{method}\n{path}\n{sha256(body)}\n{timestamp}"`. Only the HTTP transports sign each request; the
+/// gRPC transports reject HMAC authentication.
+#[derive(Clone)]
+pub struct HmacAuthProvider {
+    secret: String,
+    signature_header: String,
+    timestamp_header: String,
+}
+
+
+impl HmacAuthProvider {
+    /// Creates a new instance of `HmacAuthProvider`.
+    /// # Arguments
+    /// * `config` - A reference to `HMACAuthConfig` containing configuration details.
+    /// # Returns
+    /// A new `HmacAuthProvider` instance.
+    pub fn new(config: &HMACAuthConfig) -> Self {
+        Self {
+            secret: config.secret.clone(),
+            signature_header: config.signature_header.clone(),
+            timestamp_header: config.timestamp_header.clone(),
+        }
+    }
+
+    /// Computes the signature of a request.
+    /// # Arguments
+    /// * `method` - The HTTP method of the request.
+    /// * `path` - The path of the request URI.
+    /// * `body` - The request body.
+    /// * `timestamp` - The signing timestamp, in seconds since the UNIX epoch.
+    /// # Returns
+    /// A `Result<String>` containing the hex-encoded signature.
+    fn sign(&self, method: &str, path: &str, body: &[u8], timestamp: u64) -> Result<String> {
+        let body_hash = hex::encode(Sha256::digest(body));
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.secret.as_bytes())
+            .map_err(|e| anyhow::anyhow!("Invalid HMAC secret: {:?}", e))?;
+        mac.update(format!("{}\n{}\n{}\n{}", method, path, body_hash, timestamp).as_bytes());
+        Ok(hex::encode(mac.finalize().into_bytes()))
+    }
+}
+
+
+/// Implementation of the Debug trait for HmacAuthProvider, which does not print the secret
+impl Debug for HmacAuthProvider {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HmacAuthProvider")
+            .field("secret", &"<redacted>")
+            .field("signature_header", &self.signature_header)
+            .field("timestamp_header", &self.timestamp_header)
+            .finish()
+    }
+}
+
+
+/// Implements the `GetToken` trait for `HmacAuthProvider`.
+#[async_trait]
+impl GetToken for HmacAuthProvider {
+    async fn get_auth_headers(&self) -> Result<Vec<(String, String)>> {
+        self.get_request_auth_headers("", "", &[]).await
+    }
+
+    async fn get_request_auth_headers(&self, method: &str, path: &str, body: &[u8]) -> Result<Vec<(String, String)>> {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let signature = self.sign(method, path, body, timestamp)?;
+        Ok(vec![(self.signature_header.clone(), signature),
+                (self.timestamp_header.clone(), timestamp.to_string())])
+    }
+
+    fn name(&self) -> &str {
+        "hmac"
+    }
+}
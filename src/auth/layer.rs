@@ -1,14 +1,29 @@
 use std::sync::Arc;
-use crate::auth::GetToken;
+use crate::auth::{GetToken, RotationCallbacks};
 use crate::config::AuthConfig;
 use crate::auth::unauthenticated::Unauthenticated;
 use crate::auth::gcp::GcpAuthProvider;
+use crate::auth::hmac::HmacAuthProvider;
+use crate::auth::sts::StsAuthProvider;
+use crate::auth::chain::Chain;
+use crate::auth::static_headers::StaticHeaders;
 
 
 /// Creates a new token provider based on the given authentication configuration.
 pub fn new_gen_token(config: &AuthConfig) -> Arc<dyn GetToken> {
+    new_token_provider(config, &RotationCallbacks::default())
+}
+
+
+/// Creates a new token provider based on the given authentication configuration, notifying the given
+/// credential rotation callbacks of its token refreshes.
+pub(crate) fn new_token_provider(config: &AuthConfig, callbacks: &RotationCallbacks) -> Arc<dyn GetToken> {
     match config {
         AuthConfig::Unauthenticated => Arc::new(Unauthenticated::new()),
-        AuthConfig::GCPAuth(conf) => Arc::new(GcpAuthProvider::new_with_default(conf)),
+        AuthConfig::GCPAuth(conf) => Arc::new(GcpAuthProvider::new_with_default(conf).with_rotation_callbacks(callbacks.clone())),
+        AuthConfig::HMACAuth(conf) => Arc::new(HmacAuthProvider::new(conf)),
+        AuthConfig::STSAuth(conf) => Arc::new(StsAuthProvider::new(conf).with_rotation_callbacks(callbacks.clone())),
+        AuthConfig::StaticHeaders(headers) => Arc::new(StaticHeaders::new(headers.clone())),
+        AuthConfig::Chain(configs) => Arc::new(Chain(configs.iter().map(|config| new_token_provider(config, callbacks)).collect())),
     }
 }
\ No newline at end of file
@@ -1,15 +1,69 @@
+//! # Authentication Module
+//!
+//! This module provides the authentication providers used by the OTLP exporters.
 mod unauthenticated;
 mod gcp;
+mod hmac;
+mod sts;
+mod context;
+mod chain;
+mod static_headers;
+mod telemetry;
+mod rotation;
 pub mod layer;
 
+pub use unauthenticated::Unauthenticated;
+pub use gcp::GcpAuthProvider;
+pub use hmac::HmacAuthProvider;
+pub use sts::StsAuthProvider;
+pub use context::AuthContext;
+pub use chain::Chain;
+pub use static_headers::StaticHeaders;
+pub use telemetry::auth_failure_count;
+pub(crate) use telemetry::record_auth_failure;
+pub use rotation::{CredentialsCallbackHandle, CredentialsEvent};
+pub(crate) use rotation::RotationCallbacks;
+
 use std::fmt::Debug;
 use async_trait::async_trait;
 use anyhow::Result;
 
 
 /// Trait for obtaining authentication tokens.
+///
+/// Implement this trait to plug a custom authentication provider into the exporters.
+/// Only `get_auth_headers` is required; `get_request_auth_headers` may be overridden by
+/// providers whose headers depend on the request being sent.
 #[async_trait]
 pub trait GetToken: Debug + Send + Sync {
     /// Asynchronously retrieves authentication headers.
     async fn get_auth_headers(&self) -> Result<Vec<(String, String)>>;
+
+    /// Asynchronously retrieves authentication headers for a specific request.
+    ///
+    /// Providers whose headers depend on the request contents (for example, request signing)
+    /// override this method. By default it falls back to `get_auth_headers`.
+    /// # Arguments
+    /// * `method` - The HTTP method of the request.
+    /// * `path` - The path of the request URI.
+    /// * `body` - The request body.
+    async fn get_request_auth_headers(&self, _method: &str, _path: &str, _body: &[u8]) -> Result<Vec<(String, String)>> {
+        self.get_auth_headers().await
+    }
+
+    /// Returns the name of the provider, used to label authentication failures.
+    fn name(&self) -> &str {
+        "custom"
+    }
+
+    /// Records that the collector rejected an export authenticated by the provider, e.g. with HTTP 401,
+    /// as an authentication failure of the provider.
+    ///
+    /// The providers of an `AuthContext` also notify its credential rotation callbacks, and a `Chain`
+    /// records the failure for each of its providers.
+    /// # Arguments
+    /// * `reason` - A description of the rejection.
+    fn record_rejection(&self, reason: &str) {
+        record_auth_failure(self.name(), reason);
+    }
 }
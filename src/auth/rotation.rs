@@ -0,0 +1,97 @@
+use std::fmt::{Debug, Formatter};
+use std::sync::{Arc, Mutex, Weak};
+
+
+/// Event describing a change of the credentials used by an authentication provider.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CredentialsEvent {
+    /// A provider obtained a new token.
+    Refreshed {
+        /// The name of the provider, as returned by `GetToken::name`.
+        provider: String,
+    },
+    /// A provider's credentials failed or were rejected by the collector.
+    Invalidated {
+        /// The name of the provider, as returned by `GetToken::name`.
+        provider: String,
+        /// A description of the failure.
+        reason: String,
+    },
+}
+
+
+/// Callback notified of credential changes.
+type RotationCallback = Arc<dyn Fn(&CredentialsEvent) + Send + Sync>;
+
+
+/// The registered callbacks, with the ID of the next one.
+#[derive(Default)]
+struct Callbacks {
+    next_id: u64,
+    callbacks: Vec<(u64, RotationCallback)>,
+}
+
+
+/// The credential rotation callbacks of an `AuthContext`, shared with its token providers.
+#[derive(Clone, Default)]
+pub(crate) struct RotationCallbacks(Arc<Mutex<Callbacks>>);
+
+
+impl RotationCallbacks {
+    /// Registers a callback.
+    /// # Arguments
+    /// * `callback` - The function called with each `CredentialsEvent`.
+    /// # Returns
+    /// The handle unregistering the callback.
+    pub(crate) fn register(&self, callback: RotationCallback) -> CredentialsCallbackHandle {
+        let mut callbacks = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let id = callbacks.next_id;
+        callbacks.next_id += 1;
+        callbacks.callbacks.push((id, callback));
+        CredentialsCallbackHandle { callbacks: Arc::downgrade(&self.0), id }
+    }
+
+    /// Notifies the registered callbacks of a credential change.
+    /// # Arguments
+    /// * `event` - The credential change.
+    pub(crate) fn notify(&self, event: CredentialsEvent) {
+        let callbacks: Vec<RotationCallback> = self.0.lock().unwrap_or_else(|e| e.into_inner())
+            .callbacks
+            .iter()
+            .map(|(_, callback)| callback.clone())
+            .collect();
+        for callback in callbacks {
+            callback(&event);
+        }
+    }
+}
+
+
+/// Implementation of the Debug trait for RotationCallbacks
+impl Debug for RotationCallbacks {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let callbacks = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        f.debug_struct("RotationCallbacks").field("callbacks", &callbacks.callbacks.len()).finish()
+    }
+}
+
+
+/// Handle of a credential rotation callback, returned by `AuthContext::on_credentials_rotated`.
+///
+/// Dropping the handle keeps the callback registered; `unregister` removes it.
+#[derive(Debug)]
+pub struct CredentialsCallbackHandle {
+    callbacks: Weak<Mutex<Callbacks>>,
+    id: u64,
+}
+
+
+impl CredentialsCallbackHandle {
+    /// Unregisters the callback, so it is no longer notified.
+    pub fn unregister(self) {
+        if let Some(callbacks) = self.callbacks.upgrade() {
+            let mut callbacks = callbacks.lock().unwrap_or_else(|e| e.into_inner());
+            callbacks.callbacks.retain(|(id, _)| *id != self.id);
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use async_trait::async_trait;
+use anyhow::Result;
+use crate::auth::GetToken;
+
+
+/// An authentication provider that returns a fixed set of headers.
+#[derive(Debug, Clone)]
+pub struct StaticHeaders {
+    headers: Vec<(String, String)>,
+}
+
+
+impl StaticHeaders {
+    /// Creates a new instance of `StaticHeaders`.
+    /// # Arguments
+    /// * `headers` - The headers to add to every request.
+    /// # Returns
+    /// A new `StaticHeaders` instance.
+    pub fn new(headers: Vec<(String, String)>) -> Self {
+        Self { headers }
+    }
+}
+
+
+/// Implements the `GetToken` trait for `StaticHeaders`.
+#[async_trait]
+impl GetToken for StaticHeaders {
+    async fn get_auth_headers(&self) -> Result<Vec<(String, String)>> {
+        Ok(self.headers.clone())
+    }
+
+    fn name(&self) -> &str {
+        "static"
+    }
+}
@@ -0,0 +1,153 @@
+use std::time::{Duration, Instant};
+use async_trait::async_trait;
+use anyhow::Result;
+use serde::Deserialize;
+use tokio::sync::Mutex;
+use crate::auth::{CredentialsEvent, GetToken, RotationCallbacks};
+use crate::config::{STSAuthConfig, SubjectTokenSource};
+use crate::tracer::run_blocking;
+
+
+/// Grant type of an OAuth 2.0 token exchange request.
+const TOKEN_EXCHANGE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:token-exchange";
+
+/// Lifetime assumed for exchanged tokens when the response does not include `expires_in`.
+const DEFAULT_TOKEN_LIFETIME: Duration = Duration::from_secs(3600);
+
+
+/// Successful response of the token service.
+#[derive(Debug, Deserialize)]
+struct TokenExchangeResponse {
+    access_token: String,
+    expires_in: Option<u64>,
+}
+
+
+/// An access token obtained from the token service, with the instant after which it is refreshed.
+#[derive(Debug, Clone)]
+struct CachedToken {
+    access_token: String,
+    refresh_at: Instant,
+}
+
+
+/// An authentication provider that performs an OAuth 2.0 token exchange (RFC 8693).
+///
+/// A local identity token is exchanged at the configured security token service, and the
+/// resulting access token is cached until shortly before it expires.
+#[derive(Debug)]
+pub struct StsAuthProvider {
+    config: STSAuthConfig,
+    client: reqwest::Client,
+    token: Mutex<Option<CachedToken>>,
+    callbacks: RotationCallbacks,
+}
+
+
+impl StsAuthProvider {
+    /// Creates a new instance of `StsAuthProvider`.
+    /// # Arguments
+    /// * `config` - A reference to `STSAuthConfig` containing configuration details.
+    /// # Returns
+    /// A new `StsAuthProvider` instance.
+    pub fn new(config: &STSAuthConfig) -> Self {
+        Self { config: config.clone(), client: reqwest::Client::new(), token: Mutex::new(None), callbacks: RotationCallbacks::default() }
+    }
+
+    /// Sets the credential rotation callbacks notified of the token exchanges.
+    /// # Arguments
+    /// * `callbacks` - The callbacks of the `AuthContext` of the provider.
+    /// # Returns
+    /// The `StsAuthProvider` instance notifying the callbacks.
+    pub(crate) fn with_rotation_callbacks(mut self, callbacks: RotationCallbacks) -> Self {
+        self.callbacks = callbacks;
+        self
+    }
+
+    /// Reads the local identity token from the configured source, reading the file on the blocking thread pool.
+    /// # Returns
+    /// A `Result<String>` containing the subject token or an error if it cannot be read.
+    async fn read_subject_token(&self) -> Result<String> {
+        let token = match &self.config.subject_token_source {
+            SubjectTokenSource::File(path) => {
+                let file = path.clone();
+                run_blocking(move || std::fs::read_to_string(file)).await
+                    .map_err(|e| anyhow::anyhow!("Error reading subject token file {}: {:?}", path, e))?
+            },
+            SubjectTokenSource::Env(name) => std::env::var(name)
+                .map_err(|_| anyhow::anyhow!("{} environment variable not set", name))?,
+        };
+        Ok(token.trim().to_string())
+    }
+
+    /// Exchanges the local identity token for an access token.
+    /// # Returns
+    /// A `Result<CachedToken>` containing the new access token or an error if the exchange fails.
+    async fn exchange(&self) -> Result<CachedToken> {
+        let subject_token = self.read_subject_token().await?;
+        let scope = self.config.scopes.join(" ");
+        let mut form = vec![
+            ("grant_type", TOKEN_EXCHANGE_GRANT_TYPE),
+            ("subject_token", subject_token.as_str()),
+            ("subject_token_type", self.config.subject_token_type.as_str()),
+        ];
+        if let Some(audience) = &self.config.audience {
+            form.push(("audience", audience.as_str()));
+        }
+        if !scope.is_empty() {
+            form.push(("scope", scope.as_str()));
+        }
+
+        let response = self.client.post(&self.config.endpoint)
+            .form(&form)
+            .send()
+            .await
+            .map_err(|e| anyhow::anyhow!("Error sending token exchange request: {:?}", e))?;
+        let status = response.status();
+        let body = response.bytes().await
+            .map_err(|e| anyhow::anyhow!("Error reading token exchange response: {:?}", e))?;
+        if !status.is_success() {
+            return Err(anyhow::anyhow!("Token exchange failed with status {}: {}", status, String::from_utf8_lossy(&body)));
+        }
+
+        let response: TokenExchangeResponse = serde_json::from_slice(&body)
+            .map_err(|e| anyhow::anyhow!("Invalid token exchange response: {:?}", e))?;
+        let lifetime = response.expires_in.map(Duration::from_secs).unwrap_or(DEFAULT_TOKEN_LIFETIME);
+        Ok(CachedToken {
+            access_token: response.access_token,
+            refresh_at: Instant::now() + lifetime.saturating_sub(self.config.expiry_skew),
+        })
+    }
+
+    /// Retrieves the cached access token, exchanging a new one if it is missing or about to expire.
+    /// The credential rotation callbacks are notified of each exchange.
+    /// # Returns
+    /// A `Result<String>` containing the access token or an error if the exchange fails.
+    async fn get_and_update_token(&self) -> Result<String> {
+        let mut token = self.token.lock().await;
+        match token.as_ref() {
+            Some(cached) if Instant::now() < cached.refresh_at => Ok(cached.access_token.clone()),
+            _ => {
+                let cached = self.exchange().await?;
+                let access_token = cached.access_token.clone();
+                *token = Some(cached);
+                self.callbacks.notify(CredentialsEvent::Refreshed { provider: "sts".to_string() });
+                Ok(access_token)
+            },
+        }
+    }
+}
+
+
+/// Implements the `GetToken` trait for `StsAuthProvider`.
+#[async_trait]
+impl GetToken for StsAuthProvider {
+    async fn get_auth_headers(&self) -> Result<Vec<(String, String)>> {
+        let token = self.get_and_update_token().await?;
+        Ok(vec![("authorization".to_string(), format!("Bearer {}", token))])
+    }
+
+    fn name(&self) -> &str {
+        "sts"
+    }
+}
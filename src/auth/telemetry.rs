@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use async_trait::async_trait;
+use anyhow::Result;
+use crate::auth::GetToken;
+use crate::auth::chain::Chain;
+use crate::auth::layer::new_token_provider;
+use crate::auth::rotation::{CredentialsEvent, RotationCallbacks};
+use crate::config::AuthConfig;
+
+
+/// Returns the per-provider authentication failure counters.
+fn failure_counters() -> &'static Mutex<HashMap<String, u64>> {
+    static COUNTERS: OnceLock<Mutex<HashMap<String, u64>>> = OnceLock::new();
+    COUNTERS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+
+/// Records an authentication failure.
+///
+/// A warning is emitted and the failure counter of the provider is incremented.
+/// # Arguments
+/// * `provider` - The name of the authentication provider.
+/// * `reason` - A description of the failure.
+pub fn record_auth_failure(provider: &str, reason: &str) {
+    tracing::warn!(provider, reason, "OpenTelemetry exporter authentication failed");
+    let mut counters = failure_counters().lock().unwrap_or_else(|e| e.into_inner());
+    *counters.entry(provider.to_string()).or_insert(0) += 1;
+}
+
+
+/// Returns the number of authentication failures recorded for a provider.
+///
+/// Failures include token retrieval errors and exports rejected with HTTP 401 or 403, or with the gRPC
+/// UNAUTHENTICATED or PERMISSION_DENIED status. The rejected exports of a chain are recorded for each of its providers.
+/// # Arguments
+/// * `provider` - The name of the authentication provider, as returned by `GetToken::name`.
+pub fn auth_failure_count(provider: &str) -> u64 {
+    let counters = failure_counters().lock().unwrap_or_else(|e| e.into_inner());
+    counters.get(provider).copied().unwrap_or(0)
+}
+
+
+/// A token provider that records the failures of the wrapped provider, notifying the credential rotation
+/// callbacks of its `AuthContext` that the credentials were invalidated.
+#[derive(Debug)]
+pub struct ObservedTokenProvider {
+    inner: Arc<dyn GetToken>,
+    callbacks: RotationCallbacks,
+}
+
+
+impl ObservedTokenProvider {
+    /// Creates a new instance of `ObservedTokenProvider`.
+    /// # Arguments
+    /// * `inner` - The provider to observe.
+    /// * `callbacks` - The credential rotation callbacks notified of the failures.
+    /// # Returns
+    /// A new `ObservedTokenProvider` instance.
+    pub(crate) fn new(inner: Arc<dyn GetToken>, callbacks: RotationCallbacks) -> Self {
+        Self { inner, callbacks }
+    }
+
+    /// Records a failure of the wrapped provider and notifies the callbacks.
+    fn record_failure(&self, reason: &str) {
+        record_auth_failure(self.inner.name(), reason);
+        self.callbacks.notify(CredentialsEvent::Invalidated { provider: self.inner.name().to_string(), reason: reason.to_string() });
+    }
+}
+
+
+/// Implements the `GetToken` trait for `ObservedTokenProvider`.
+#[async_trait]
+impl GetToken for ObservedTokenProvider {
+    async fn get_auth_headers(&self) -> Result<Vec<(String, String)>> {
+        self.inner.get_auth_headers().await
+            .inspect_err(|e| self.record_failure(&e.to_string()))
+    }
+
+    async fn get_request_auth_headers(&self, method: &str, path: &str, body: &[u8]) -> Result<Vec<(String, String)>> {
+        self.inner.get_request_auth_headers(method, path, body).await
+            .inspect_err(|e| self.record_failure(&e.to_string()))
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn record_rejection(&self, reason: &str) {
+        self.record_failure(reason);
+    }
+}
+
+
+/// Creates the token provider of an authentication configuration, recording its failures.
+///
+/// The providers of a chain are observed one by one, so the failures are recorded under the name of the
+/// failing provider rather than the chain.
+/// # Arguments
+/// * `config` - The authentication configuration.
+/// * `callbacks` - The credential rotation callbacks notified of the refreshes and failures of the provider.
+pub(crate) fn observed_token_provider(config: &AuthConfig, callbacks: &RotationCallbacks) -> Arc<dyn GetToken> {
+    match config {
+        AuthConfig::Chain(configs) => Arc::new(Chain(configs.iter().map(|config| observed_token_provider(config, callbacks)).collect())),
+        _ => Arc::new(ObservedTokenProvider::new(new_token_provider(config, callbacks), callbacks.clone())),
+    }
+}
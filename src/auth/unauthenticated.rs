@@ -4,7 +4,7 @@ use crate::auth::GetToken;
 
 
 /// An authentication provider that does not provide any token.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct Unauthenticated;
 
 
@@ -22,4 +22,8 @@ impl GetToken for Unauthenticated {
     async fn get_auth_headers(&self) -> Result<Vec<(String, String)>> {
         Ok(Vec::new())
     }
+
+    fn name(&self) -> &str {
+        "unauthenticated"
+    }
 }
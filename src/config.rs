@@ -1,4 +1,83 @@
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use anyhow::{anyhow, Result};
+use base64::Engine;
+use opentelemetry_sdk::trace::{IdGenerator, ShouldSample, SpanData};
+use tonic::metadata::{Ascii, MetadataKey, MetadataValue};
+use tracing::Level;
+
+
+/// Reads an environment variable of the configuration layer, like `std::env::var`.
+///
+/// Debug builds assert that the variable is listed in `ENV_VARS`, so the registry returned by `env_vars`
+/// cannot miss a variable read by the crate.
+pub(crate) fn read_env_var<K: AsRef<str>>(name: K) -> Result<String, std::env::VarError> {
+    let name = name.as_ref();
+    debug_assert!(ENV_VARS.iter().any(|var| var.name == name), "{} is missing from ENV_VARS", name);
+    std::env::var(name)
+}
+
+
+/// Reads and parses an optional environment variable.
+///
+/// Returns `Ok(None)` if the variable is not set, and an error if it cannot be parsed.
+fn parse_env_var<T: FromStr>(name: &str) -> Result<Option<T>> {
+    match read_env_var(name) {
+        Ok(value) => value
+            .parse::<T>()
+            .map(Some)
+            .map_err(|_| anyhow!("Invalid value for {} environment variable: {}", name, value)),
+        Err(_) => Ok(None),
+    }
+}
+
+
+/// Parses a comma-separated list of `key=value` pairs.
+fn parse_key_value_list(value: &str) -> Result<Vec<(String, String)>> {
+    value
+        .split(',')
+        .filter(|pair| !pair.trim().is_empty())
+        .map(|pair| {
+            pair.split_once('=')
+                .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+                .ok_or_else(|| anyhow!("Invalid key=value pair: {}", pair))
+        })
+        .collect()
+}
+
+
+/// Checks that each gRPC metadata pair has a valid ASCII key and value, so an invalid pair is reported
+/// once, when the configuration is created or built, instead of failing every export.
+pub(crate) fn validate_grpc_metadata(metadata: &[(String, String)]) -> Result<()> {
+    for (key, value) in metadata {
+        MetadataKey::<Ascii>::from_str(key).map_err(|_| anyhow!("Invalid gRPC metadata key: {}", key))?;
+        value.parse::<MetadataValue<Ascii>>().map_err(|_| anyhow!("Invalid gRPC metadata value for key {}", key))?;
+    }
+    Ok(())
+}
+
+
+/// Parses a comma-separated list of values.
+fn parse_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+
+/// Returns the endpoint of the HTTP log exporter for an OTLP endpoint, replacing the `/v1/traces` path
+/// of a trace endpoint with `/v1/logs`, or else appending it.
+pub(crate) fn http_logs_endpoint(endpoint: &str) -> String {
+    let base = endpoint.trim_end_matches('/');
+    format!("{}/v1/logs", base.strip_suffix("/v1/traces").unwrap_or(base))
+}
+
 
 /// Enum representing the possible logging configurations.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -6,9 +85,294 @@ pub enum LogConfig {
     /// Loki configuration.
     Loki(LokiConfig),
     /// OTLP configuration.
-    OTLP,
+    OTLP(OTLPLogConfig),
     /// Standard output configuration.
-    Stdout,
+    Stdout(StdoutLogConfig),
+    /// Local file configuration.
+    File(FileLogConfig),
+    /// Syslog configuration.
+    Syslog(SyslogLogConfig),
+    /// systemd-journald configuration. Only available with the `journald` feature.
+    #[cfg(feature = "journald")]
+    Journald,
+    /// Several sinks receiving the same events, e.g. pretty standard output and OTLP.
+    Multi(Vec<LogSinkConfig>),
+}
+
+
+/// Struct for one sink of a `LogConfig::Multi` configuration.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LogSinkConfig {
+    /// Configuration of the sink.
+    pub config: LogConfig,
+    /// Level filter of the sink, with the `RUST_LOG` syntax, e.g. `info` or `my_crate=debug`.
+    /// It narrows the filter of the minimum level or, without it, of `RUST_LOG`. If `None`, the sink receives every event passing it.
+    pub level: Option<String>,
+    /// Minimum level of the events sent to the sink, replacing the `RUST_LOG` filter for it.
+    /// If `None`, the sink is filtered by `RUST_LOG`.
+    pub min_level: Option<Level>,
+}
+
+
+/// Struct for syslog configuration.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SyslogLogConfig {
+    /// Endpoint of the syslog server, as `udp://host:port`, `tcp://host:port` or `unix:///path`.
+    /// If `None`, the local syslog socket is used.
+    pub endpoint: Option<String>,
+    /// Facility of the messages, e.g. `user`, `daemon` or `local0`.
+    pub facility: String,
+    /// Format of the messages.
+    pub format: SyslogFormat,
+    /// Buffering of the messages, sent to the syslog server by a background thread.
+    pub buffer: LogBufferConfig,
+}
+
+
+/// Enum representing the possible syslog message formats.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SyslogFormat {
+    /// The BSD syslog format.
+    RFC3164,
+    /// The IETF syslog format.
+    #[default]
+    RFC5424,
+}
+
+
+/// Struct for local file log configuration.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FileLogConfig {
+    /// Path of the written file. Rotated files are written next to it.
+    pub path: PathBuf,
+    /// Rotation policy of the file.
+    pub rotation: LogRotation,
+    /// Output format of the log lines.
+    pub format: StdoutLogFormat,
+    /// Settings of the GCP and CloudWatch formats.
+    pub cloud_format: CloudLogFormatConfig,
+    /// Metadata written in the log lines.
+    pub fields: LogLineFields,
+    /// Buffering of the log lines, written to the file by a background thread.
+    pub buffer: LogBufferConfig,
+}
+
+
+/// Struct for the settings of the GCP and CloudWatch JSON formats of the standard output and file log sinks.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct CloudLogFormatConfig {
+    /// The GCP project qualifying the trace field of the GCP format, which requires it.
+    pub gcp_project_id: Option<String>,
+    /// The CloudWatch namespace of the metrics embedded by the CloudWatch format. `None` means no metric is embedded.
+    pub emf_namespace: Option<String>,
+}
+
+
+/// Struct for the buffering of the log lines written by a background thread, so a slow output cannot
+/// stall the threads recording the events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LogBufferConfig {
+    /// Maximum number of log lines waiting to be written.
+    pub buffered_lines: usize,
+    /// What happens to the log lines recorded while the buffer is full.
+    pub policy: LogBufferPolicy,
+}
+
+
+/// Enum representing the possible policies of a full log buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum LogBufferPolicy {
+    /// The log lines are dropped, so the threads recording the events never wait.
+    #[default]
+    Drop,
+    /// The threads recording the events wait for room in the buffer, so no log line is lost.
+    Block,
+}
+
+
+/// Struct for the metadata written in the log lines of the standard output and file log sinks.
+///
+/// Not used by the ECS, GCP and CloudWatch formats, which have their own fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LogLineFields {
+    /// Whether the file and line of the events are written.
+    pub source_location: bool,
+    /// Whether the target of the events is written.
+    pub target: bool,
+    /// Whether the name of the thread recording the events is written.
+    pub thread_names: bool,
+    /// Whether the ID of the thread recording the events is written.
+    pub thread_ids: bool,
+    /// Whether the spans of the events are written. Only used by the JSON format, as the other formats
+    /// always write them.
+    pub span_list: bool,
+}
+
+
+/// Enum representing the possible rotation policies of the log file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum LogRotation {
+    /// A new file every day, named after the path with the date as suffix.
+    #[default]
+    Daily,
+    /// A new file every hour, named after the path with the date and hour as suffix.
+    Hourly,
+    /// The file is renamed to `<path>.<epoch millis>` once it would exceed the given size, in bytes.
+    Size(u64),
+}
+
+
+/// Struct for standard output log configuration.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StdoutLogConfig {
+    /// Output format of the log lines.
+    pub format: StdoutLogFormat,
+    /// Settings of the GCP and CloudWatch formats.
+    pub cloud_format: CloudLogFormatConfig,
+    /// Whether the log lines are colored with ANSI escape codes. Not used by the JSON formats.
+    /// `None` means they are colored unless the `NO_COLOR` environment variable is set.
+    pub ansi: Option<bool>,
+    /// Whether the log lines have a timestamp.
+    pub timestamps: bool,
+    /// Format of the timestamps of the log lines. `None` means the RFC 3339 UTC timestamps with
+    /// microseconds of `tracing-subscriber`. Not used by the ECS, GCP and CloudWatch formats, which have their own.
+    pub timestamp_format: Option<TimestampFormat>,
+    /// Timezone of the timestamps of the log lines, when their format is set.
+    pub timezone: Timezone,
+    /// Metadata written in the log lines.
+    pub fields: LogLineFields,
+    /// Buffering of the log lines, written to standard output by a background thread.
+    /// `None` means the lines are written by the threads recording the events.
+    pub buffer: Option<LogBufferConfig>,
+}
+
+
+/// Enum representing the possible output formats of the standard output log layer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum StdoutLogFormat {
+    /// Human-readable single lines, with the fields and the spans of each event.
+    #[default]
+    Full,
+    /// Multi-line, colorized events, for local development.
+    Pretty,
+    /// Shorter single lines, with the fields of the spans after those of the event.
+    Compact,
+    /// One JSON object per line, for log shippers such as fluent-bit or Vector.
+    Json,
+    /// One JSON object per line, with the fields of the Elastic Common Schema, e.g. `@timestamp`, `log.level`,
+    /// `trace.id` and `service.name`, so Elasticsearch and Filebeat ingest the logs without a custom processor.
+    Ecs,
+    /// One JSON object per line, with the fields understood natively by Google Cloud Logging, e.g. `severity`,
+    /// `time`, `logging.googleapis.com/trace` and `logging.googleapis.com/spanId`, so the logs of GKE and Cloud Run
+    /// services are correlated with their traces. The trace field is qualified with the project of
+    /// `CloudLogFormatConfig::gcp_project_id`, which is required.
+    GcpJson,
+    /// One JSON object per line, with field names suited to CloudWatch Logs Insights queries, e.g. `timestamp`,
+    /// `level`, `message` and `xray_trace_id`, for ECS and Lambda services whose standard output is ingested by
+    /// CloudWatch. With the namespace of `CloudLogFormatConfig::emf_namespace`, the numeric fields named `metric.<name>`
+    /// are embedded as CloudWatch metrics with the Embedded Metric Format, with the service as dimension.
+    CloudWatchJson,
+}
+
+
+/// Struct for OTLP log configuration.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OTLPLogConfig {
+    /// Authorization configuration.
+    pub auth_config: AuthConfig,
+    /// Limits applied to each exported log record.
+    pub limits: LogLimitsConfig,
+    /// Batching of the exported log records.
+    pub batch: LogBatchConfig,
+    /// Encoding policy for binary attribute values.
+    pub binary_policy: BinaryAttributePolicy,
+    /// Transport of the log exporter.
+    pub transport: LogTransport,
+    /// Endpoint of the log exporter, overriding the inherited or default endpoint.
+    pub endpoint: Option<String>,
+    /// Payload encoding of the log exporter, when exporting over HTTP.
+    pub encoding: HttpEncoding,
+    /// Compression of the payloads of the log exporter. `None` means the compression of the trace configuration
+    /// with the "inherit" transport, and no compression otherwise.
+    pub compression: Option<ExportCompression>,
+}
+
+
+/// Enum representing the possible payload encodings of the OTLP HTTP exporters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum HttpEncoding {
+    /// Binary protobuf payloads, sent as `application/x-protobuf`.
+    #[default]
+    Protobuf,
+    /// JSON payloads, sent as `application/json`, for collectors and proxies which only accept JSON.
+    Json,
+}
+
+
+/// Enum representing the possible compressions of the OTLP export payloads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ExportCompression {
+    /// Uncompressed payloads.
+    #[default]
+    None,
+    /// Gzip-compressed payloads, reducing the egress bandwidth at the cost of some CPU.
+    Gzip,
+    /// Zstd-compressed payloads, compressing better than gzip for less CPU. Requires the `zstd` feature.
+    Zstd,
+}
+
+
+/// Enum representing the possible HTTP versions of the OTLP HTTP exporters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum HttpVersion {
+    /// HTTP/1.1, or HTTP/2 when negotiated with the collector over TLS.
+    #[default]
+    Auto,
+    /// HTTP/1.1 only, for ingest proxies which misbehave with HTTP/2.
+    Http1,
+    /// HTTP/2 with prior knowledge, without negotiation, also over plain-text connections.
+    Http2,
+}
+
+
+/// Enum representing the possible transports of the OTLP log exporter, see `OTEL_EXPORTER_LOGS`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum LogTransport {
+    /// Use the transport, endpoint and authentication of the OTLP trace exporter.
+    /// If traces are not exported with OTLP, HTTP is used with the authentication of the log configuration.
+    #[default]
+    Inherit,
+    /// HTTP, with the authentication of the log configuration.
+    HTTP,
+    /// gRPC, with the authentication of the log configuration.
+    GRPC,
+}
+
+
+/// Struct for log record limits configuration.
+///
+/// A `None` value means the corresponding limit is not applied.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LogLimitsConfig {
+    /// Maximum number of attributes per log record.
+    pub max_attributes: Option<usize>,
+    /// Maximum length of string and byte attribute values.
+    pub max_attribute_value_length: Option<usize>,
+    /// Maximum length of the log record body.
+    pub max_body_length: Option<usize>,
+}
+
+
+/// Struct for the batch log record processor configuration.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LogBatchConfig {
+    /// Maximum number of log records waiting to be exported, past which new records are dropped.
+    pub max_queue_size: usize,
+    /// Maximum number of log records per export. Capped to the queue size.
+    pub max_export_batch_size: usize,
+    /// Delay between two exports.
+    pub scheduled_delay: Duration,
 }
 
 
@@ -19,18 +383,439 @@ pub enum TraceConfig {
     HTTP(OTLPTraceConfig),
     /// gRPC OTLP configuration.
     GRPC(OTLPTraceConfig),
-    /// gRPC OTLP configuration.
+    /// HTTP OTLP configuration, sent with a reqwest client. The endpoint must end with `/v1/traces`.
+    /// See `tracer::reqwest::get_reqwest_tracer_provider_with_client` to share the client of the application.
     REQWEST(OTLPTraceConfig),
     /// Standard output configuration.
-    StdOut,
+    StdOut(StdoutTraceConfig),
+    /// Zipkin configuration.
+    Zipkin(ZipkinConfig),
+    /// Datadog trace agent configuration, for agents without OTLP ingest.
+    DatadogAgent(DatadogAgentConfig),
+    /// AWS X-Ray configuration, exporting to the X-Ray daemon.
+    XRay(XRayTraceConfig),
+    /// JSON lines file configuration.
+    File(FileTraceConfig),
+    /// In-memory configuration, for integration tests.
+    InMemory(InMemoryTraceConfig),
+    /// No-op configuration, recording spans without exporting them.
+    Noop(NoopTraceConfig),
+}
+
+
+/// Struct for no-op trace configuration.
+///
+/// Spans are created and recorded as with the other configurations, but no span processor is registered,
+/// so there is no batch thread and no output. Meant for benchmarks and noisy test environments.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct NoopTraceConfig {
+    /// Sampler of the tracer provider.
+    pub sampler: SamplerConfig,
+    /// Limits applied to the recorded spans.
+    pub span_limits: SpanLimitsConfig,
+    /// Generator of the trace and span IDs.
+    pub id_generator: IdGeneratorConfig,
+}
+
+
+/// Struct for in-memory trace configuration.
+///
+/// Spans are exported as soon as they end to the store, which is shared by the clones of the configuration
+/// and read with `OpenTelemetryObject::finished_spans`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct InMemoryTraceConfig {
+    /// Store receiving the exported spans.
+    pub store: SpanStore,
+    /// Sampler of the tracer provider.
+    pub sampler: SamplerConfig,
+    /// Limits applied to the recorded spans.
+    pub span_limits: SpanLimitsConfig,
+    /// Generator of the trace and span IDs.
+    pub id_generator: IdGeneratorConfig,
+}
+
+
+/// A shared list of exported spans.
+///
+/// Stores are compared and hashed by identity.
+#[derive(Debug, Clone, Default)]
+pub struct SpanStore(pub Arc<Mutex<Vec<SpanData>>>);
+
+
+/// Enum representing the possible span export modes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ExportMode {
+    /// Spans are queued and exported in batches by a background thread.
+    #[default]
+    Batch,
+    /// Each span is exported when it ends, blocking the thread ending it. Suited to short-lived processes,
+    /// such as CLIs and lambdas, which could otherwise exit before the last batch is exported.
+    ///
+    /// With the network exporters, the thread ending the span waits for the request, so ending a span on a
+    /// single-threaded Tokio runtime can stall it until the export times out. Failed exports are not retried.
+    Simple,
+}
+
+
+/// Struct for JSON lines file trace configuration.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FileTraceConfig {
+    /// Path of the written file.
+    pub path: PathBuf,
+    /// Rotation policy of the file.
+    pub rotation: FileRotation,
+    /// Whether spans are exported in batches or as they end.
+    pub export_mode: ExportMode,
+    /// Sampler of the tracer provider.
+    pub sampler: SamplerConfig,
+    /// Limits applied to the recorded spans.
+    pub span_limits: SpanLimitsConfig,
+    /// Generator of the trace and span IDs.
+    pub id_generator: IdGeneratorConfig,
+}
+
+
+/// Struct for the rotation policy of a file.
+///
+/// The file is rotated when either limit is reached. A `None` value means the corresponding limit is not applied.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct FileRotation {
+    /// Maximum size of the file, in bytes.
+    pub max_size: Option<u64>,
+    /// Maximum time a file is written to.
+    pub max_age: Option<Duration>,
+}
+
+
+/// Struct for Zipkin trace configuration.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ZipkinConfig {
+    /// The endpoint of the Zipkin collector, e.g. `http://localhost:9411/api/v2/spans`.
+    pub endpoint: String,
+    /// Whether spans are exported in batches or as they end.
+    pub export_mode: ExportMode,
+    /// Authorization configuration.
+    pub auth_config: AuthConfig,
+    /// Sampler of the tracer provider.
+    pub sampler: SamplerConfig,
+    /// Limits applied to the recorded spans.
+    pub span_limits: SpanLimitsConfig,
+    /// Generator of the trace and span IDs.
+    pub id_generator: IdGeneratorConfig,
+}
+
+
+/// Struct for Datadog trace agent configuration.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DatadogAgentConfig {
+    /// The URL of the Datadog trace agent, e.g. `http://localhost:8126`.
+    pub agent_url: String,
+    /// Whether spans are exported in batches or as they end.
+    pub export_mode: ExportMode,
+    /// Authorization configuration, for agents behind an authenticating proxy.
+    pub auth_config: AuthConfig,
+    /// Sampler of the tracer provider.
+    pub sampler: SamplerConfig,
+    /// Limits applied to the recorded spans.
+    pub span_limits: SpanLimitsConfig,
+    /// Generator of the trace and span IDs.
+    pub id_generator: IdGeneratorConfig,
+}
+
+
+/// Struct for AWS X-Ray trace configuration.
+///
+/// The IDs are X-Ray compatible, and the X-Ray propagator is installed first unless `OTEL_PROPAGATORS`
+/// is set or `OpenTelemetryObjectBuilder::with_propagation` is used.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct XRayTraceConfig {
+    /// The UDP address of the X-Ray daemon, e.g. `127.0.0.1:2000`. `None` means spans are not exported,
+    /// e.g. when they are sent to a collector by an additional exporter.
+    pub daemon_address: Option<String>,
+    /// Whether spans are exported in batches or as they end.
+    pub export_mode: ExportMode,
+    /// Sampler of the tracer provider.
+    pub sampler: SamplerConfig,
+    /// Limits applied to the recorded spans.
+    pub span_limits: SpanLimitsConfig,
+    /// Generator of the trace and span IDs. X-Ray compatible by default.
+    pub id_generator: IdGeneratorConfig,
+}
+
+
+/// Enum representing the possible output formats of the standard output trace exporter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum StdoutFormat {
+    /// One block per batch listing the fields of each span.
+    #[default]
+    Default,
+    /// Each trace as an indented, colorized tree of spans, printed when its local root span ends.
+    Pretty,
+    /// One compact JSON object per span and line.
+    Json,
+}
+
+
+/// Struct for standard output trace configuration.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct StdoutTraceConfig {
+    /// Output format of the spans.
+    pub format: StdoutFormat,
+    /// Format of the rendered timestamps.
+    pub timestamp_format: TimestampFormat,
+    /// Timezone of the rendered timestamps.
+    pub timezone: Timezone,
+    /// Sampler of the tracer provider.
+    pub sampler: SamplerConfig,
+    /// Limits applied to the recorded spans.
+    pub span_limits: SpanLimitsConfig,
+    /// Generator of the trace and span IDs.
+    pub id_generator: IdGeneratorConfig,
+}
+
+
+/// Struct for span limits configuration.
+///
+/// Attributes, events and links beyond the limits are dropped when they are recorded.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SpanLimitsConfig {
+    /// Maximum number of attributes per span.
+    pub max_attributes_per_span: u32,
+    /// Maximum number of events per span.
+    pub max_events_per_span: u32,
+    /// Maximum number of links per span.
+    pub max_links_per_span: u32,
+}
+
+
+/// Enum representing the possible samplers.
+#[derive(Debug, Clone)]
+pub enum SamplerConfig {
+    /// Sample every span.
+    AlwaysOn,
+    /// Sample no span.
+    AlwaysOff,
+    /// Sample the given fraction of traces, between 0 and 1, based on the trace ID.
+    TraceIdRatio(f64),
+    /// Sample at most the given number of traces per second, allowing bursts of up to one second of traffic.
+    RateLimiting {
+        /// Maximum sustained number of sampled traces per second.
+        spans_per_second: u32,
+    },
+    /// Follow the sampling decision of the parent span, using the wrapped sampler for root spans.
+    ParentBased(Box<SamplerConfig>),
+    /// A user-implemented sampler.
+    Custom(CustomSampler),
+}
+
+
+/// A user-implemented sampler, e.g. for attribute or tenant based sampling decisions.
+///
+/// Custom samplers are compared and hashed by identity.
+#[derive(Debug, Clone)]
+pub struct CustomSampler(pub Arc<dyn ShouldSample>);
+
+
+/// Enum representing the possible trace and span ID generators.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub enum IdGeneratorConfig {
+    /// Random IDs, the default of the OpenTelemetry SDK.
+    #[default]
+    Random,
+    /// AWS X-Ray compatible IDs, whose trace IDs start with the epoch seconds of their creation.
+    XRay,
+    /// A user-implemented generator.
+    Custom(CustomIdGenerator),
+}
+
+
+/// A user-implemented trace and span ID generator.
+///
+/// Custom generators are compared and hashed by identity.
+#[derive(Debug, Clone)]
+pub struct CustomIdGenerator(pub Arc<dyn IdGenerator>);
+
+
+/// Enum representing the possible timestamp formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TimestampFormat {
+    /// `YYYY-MM-DD HH:MM:SS.ffffff`.
+    #[default]
+    Default,
+    /// RFC 3339, including the timezone offset.
+    Rfc3339,
+    /// Milliseconds since the UNIX epoch. The timezone is ignored.
+    EpochMillis,
+}
+
+
+/// Enum representing the possible timezones for rendered timestamps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Timezone {
+    /// Coordinated Universal Time.
+    #[default]
+    Utc,
+    /// The local timezone of the host.
+    Local,
+}
+
+
+/// Struct for heartbeat configuration.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HeartbeatConfig {
+    /// Interval between heartbeats.
+    pub interval: Duration,
+}
+
+
+/// Enum representing the possible context propagation formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Propagator {
+    /// W3C TraceContext, with the `traceparent` and `tracestate` headers.
+    TraceContext,
+    /// W3C Baggage, with the `baggage` header.
+    Baggage,
+    /// Zipkin B3, with the single `b3` header.
+    B3,
+    /// Zipkin B3, with the `X-B3-*` headers.
+    B3Multi,
+    /// Jaeger, with the `uber-trace-id` header.
+    Jaeger,
+    /// AWS X-Ray, with the `X-Amzn-Trace-Id` header.
+    XRay,
+}
+
+
+/// Struct for context propagation configuration.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PropagationConfig {
+    /// Propagation formats, in extraction order.
+    pub propagators: Vec<Propagator>,
+}
+
+
+/// Struct for span enrichment configuration.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SpanEnrichmentConfig {
+    /// Attributes recorded on every span, e.g. `("deployment.environment", "production")`.
+    pub attributes: Vec<(String, String)>,
+}
+
+
+/// Struct for flush on error configuration.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ErrorFlushConfig {
+    /// Minimum interval between two flushes.
+    pub min_interval: Duration,
+}
+
+
+/// Struct for log rate limiting configuration.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LogRateLimitConfig {
+    /// Maximum number of identical events recorded per window.
+    pub max_events: u32,
+    /// Duration of the window.
+    pub window: Duration,
+}
+
+
+/// Struct for process metrics configuration.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct ProcessMetricsConfig {
+    /// File persisting the number of starts of the service across restarts. `None` disables the
+    /// `process.restart.count` gauge.
+    pub restart_marker: Option<PathBuf>,
+}
+
+
+/// Struct for tail sampling configuration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TailSamplingConfig {
+    /// Maximum time the spans of a trace are buffered before the trace is decided.
+    pub decision_wait: Duration,
+    /// Duration from which a span makes its whole trace exported.
+    pub latency_threshold: Duration,
+    /// Fraction, between 0 and 1, of the other traces that are exported.
+    pub ratio: f64,
+}
+
+
+/// Pattern of the credit card numbers of `RedactionConfig::pii`, whose matches are only redacted
+/// when they pass the Luhn checksum, so other long numbers are kept.
+pub(crate) const CARD_NUMBER_PATTERN: &str = r"\b(?:\d[ -]?){12,18}\d\b";
+
+
+/// Struct for span attribute redaction configuration.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct RedactionConfig {
+    /// Keys of the redacted attributes, e.g. `http.request.header.authorization`, matched ignoring the ASCII case.
+    /// A key ending with `*` matches every key starting with the rest of it, e.g. `http.request.header.*`,
+    /// and a key starting and ending with `*` matches every key containing the rest of it, e.g. `*token*`.
+    pub keys: Vec<String>,
+    /// Regular expressions matched against string attribute values, e.g. email addresses or `\?.*` for
+    /// query strings. Only the matching parts of the values are redacted.
+    pub value_patterns: Vec<String>,
+    /// How redacted values are replaced.
+    pub action: RedactionAction,
+    /// Secret key of the HMAC of the hashed values with `RedactionAction::Hash`. Without it, a random key is
+    /// generated per redaction configuration, so hashes only correlate within the process.
+    pub hash_key: Option<String>,
+}
+
+
+/// Enum representing the possible actions applied to redacted attributes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum RedactionAction {
+    /// Attributes matching a key are dropped, and parts of values matching a pattern are replaced with `[REDACTED]`.
+    #[default]
+    Drop,
+    /// Attribute values matching a key, and parts of values matching a pattern, are replaced with their
+    /// hex-encoded HMAC-SHA256 keyed with `RedactionConfig::hash_key`, so equal values can still be correlated
+    /// without the hashes of guessable values, e.g. email addresses, being reversible by brute force.
+    Hash,
+}
+
+
+/// Struct for a span name rewriting rule.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SpanNameRule {
+    /// Regular expression matched against span names.
+    pub pattern: String,
+    /// Replacement of the matches, which may refer to capture groups, e.g. `$1`.
+    pub replacement: String,
+}
+
+
+/// Enum representing the instrumentation scopes whose spans are sent to an exporter.
+///
+/// A scope name matches a listed name if it is equal to it or is a module of it, e.g. `sqlx::query` matches `sqlx`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub enum ScopeFilter {
+    /// Every scope.
+    #[default]
+    All,
+    /// Only the listed scopes.
+    Only(Vec<String>),
+    /// Every scope except the listed ones.
+    Except(Vec<String>),
 }
 
 
 /// Struct for Loki configuration.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct LokiConfig {
-    /// The URL of the Loki instance.
+    /// The URL of the Loki instance. The log lines are pushed as snappy-compressed protobuf payloads.
     pub url: String,
+    /// Authorization configuration, e.g. basic auth for Grafana Cloud Loki. Its headers are retrieved once,
+    /// when the log layer is created, so only static headers are supported.
+    pub auth_config: AuthConfig,
+    /// Labels of the log streams, e.g. environment, region and pod, in addition to the `service` label.
+    pub labels: BTreeMap<String, String>,
+    /// Fields added to every log line, without creating new streams, e.g. high-cardinality identifiers.
+    pub extra_fields: BTreeMap<String, String>,
+    /// Tenant of the logs in multi-tenant deployments, sent as the `X-Scope-OrgID` header. `None` means no tenant.
+    pub tenant_id: Option<String>,
 }
 
 
@@ -41,6 +826,100 @@ pub struct OTLPTraceConfig {
     pub endpoint: String,
     /// Authorization configuration.
     pub auth_config: AuthConfig,
+    /// Encoding policy for binary attribute values.
+    pub binary_policy: BinaryAttributePolicy,
+    /// Resilience policy of the exports.
+    pub export_policy: ExportPolicyConfig,
+    /// Whether spans are exported in batches or as they end.
+    pub export_mode: ExportMode,
+    /// Payload encoding of the HTTP and reqwest transports. Not used by gRPC.
+    pub encoding: HttpEncoding,
+    /// HTTP version of the HTTP and reqwest transports. Not used by gRPC.
+    pub http_version: HttpVersion,
+    /// Metadata added to every request of the gRPC transport, e.g. tenant identifiers. Not used by HTTP.
+    pub metadata: Vec<(String, String)>,
+    /// Keep-alive and connect timeout of the gRPC transport. Not used by HTTP.
+    pub channel: GrpcChannelConfig,
+    /// Whether the certificate of the collector is not verified, for development collectors with
+    /// self-signed certificates. Never enable it in production.
+    pub insecure_skip_verify: bool,
+    /// Sampler of the tracer provider.
+    pub sampler: SamplerConfig,
+    /// Limits applied to the recorded spans.
+    pub span_limits: SpanLimitsConfig,
+    /// Generator of the trace and span IDs.
+    pub id_generator: IdGeneratorConfig,
+    /// Compression of the payloads of the gRPC and HTTP exporters. With `ExportCompression::None`, the
+    /// OpenTelemetry SDK still applies the compression of the `OTEL_EXPORTER_OTLP_TRACES_COMPRESSION` and
+    /// `OTEL_EXPORTER_OTLP_COMPRESSION` environment variables, if set.
+    pub compression: ExportCompression,
+}
+
+
+/// Struct for the resilience policy of OTLP exports.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ExportPolicyConfig {
+    /// Timeout of each export request. Applied by the gRPC channel and the hyper HTTP client.
+    pub timeout: Duration,
+    /// Maximum number of concurrent export requests of the gRPC channel. `None` means no limit.
+    pub concurrency_limit: Option<usize>,
+    /// Maximum number of retries of a failed export.
+    pub max_retries: u32,
+    /// Maximum time spent exporting a batch, retries included, after which the batch is dropped.
+    pub max_elapsed: Duration,
+    /// Retries allowed, as a percentage of the exports, on top of a minimum of one retry per second.
+    pub retry_budget_percent: u32,
+    /// On-disk buffer of the batches whose export failed. `None` means failed batches are dropped.
+    pub disk_buffer: Option<DiskBufferConfig>,
+    /// Maximum number of batches exported at once in batch mode. Only used with the `concurrent-exports` feature.
+    pub max_concurrent_exports: usize,
+    /// Maximum size of an export request, in bytes. Larger batches are split into several requests.
+    /// `None` means batches are not split.
+    pub max_message_size: Option<usize>,
+}
+
+
+/// Struct for the tuning of the gRPC export channel, e.g. so load balancers do not drop long-lived idle channels.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GrpcChannelConfig {
+    /// Interval of the TCP keep-alive probes of the connection. `None` means TCP keep-alive is disabled.
+    pub tcp_keepalive: Option<Duration>,
+    /// Interval of the HTTP/2 keep-alive pings of the connection. `None` means no ping is sent.
+    pub http2_keepalive_interval: Option<Duration>,
+    /// Time waited for the acknowledgement of a ping before the connection is closed. `None` means the tonic default, 20 seconds.
+    pub keepalive_timeout: Option<Duration>,
+    /// Whether the HTTP/2 keep-alive pings are sent while no export is in flight, so idle channels are kept open.
+    pub keepalive_while_idle: bool,
+    /// Timeout of the connection to the collector. `None` means only the export timeout applies.
+    pub connect_timeout: Option<Duration>,
+}
+
+
+/// Struct for the on-disk buffer of the span batches whose export failed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DiskBufferConfig {
+    /// Directory holding the buffered batches. Created if missing.
+    pub path: PathBuf,
+    /// Maximum total size of the buffered batches, in bytes. The oldest batches are deleted beyond it.
+    pub max_size: u64,
+}
+
+
+/// Enum representing the possible encoding policies for attributes containing binary data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum BinaryAttributePolicy {
+    /// Export binary values unchanged.
+    #[default]
+    Keep,
+    /// Replace binary values with their base64 encoding, truncated to `max_length` characters.
+    Base64 {
+        /// Maximum length of the encoded value.
+        max_length: usize,
+    },
+    /// Replace binary values with their hex-encoded SHA-256 hash.
+    Hash,
+    /// Drop attributes with binary values.
+    Drop,
 }
 
 /// Enum representing the possible authentication configurations.
@@ -48,6 +927,14 @@ pub struct OTLPTraceConfig {
 pub enum AuthConfig {
     /// GCP authentication.
     GCPAuth(GCPAuthConfig),
+    /// HMAC request-signing authentication.
+    HMACAuth(HMACAuthConfig),
+    /// OAuth 2.0 token exchange (RFC 8693) authentication.
+    STSAuth(STSAuthConfig),
+    /// Fixed headers added to every request.
+    StaticHeaders(Vec<(String, String)>),
+    /// Headers merged from several authentication configurations, in order.
+    Chain(Vec<AuthConfig>),
     /// No authentication.
     Unauthenticated,
 }
@@ -58,6 +945,67 @@ pub enum AuthConfig {
 pub struct GCPAuthConfig {
     /// Google Cloud Project ID.
     pub project_id: String,
+    /// Interval after which the access token is refreshed.
+    pub refresh_interval: Duration,
+    /// Safety margin subtracted from the refresh interval, so tokens are refreshed before they expire.
+    pub expiry_skew: Duration,
+    /// OAuth scopes of the access token. If empty, the default scopes of the credentials are used.
+    pub scopes: Vec<String>,
+    /// Service account to impersonate. If set, the application default credentials are exchanged
+    /// for a token of this service account.
+    pub impersonate_service_account: Option<GCPImpersonationConfig>,
+}
+
+
+/// Struct for GCP service account impersonation configuration.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GCPImpersonationConfig {
+    /// Email of the service account to impersonate.
+    pub target_principal: String,
+    /// Chain of service accounts delegating to the target principal.
+    pub delegates: Vec<String>,
+    /// OAuth scopes of the impersonated token. If empty, the cloud-platform scope is used.
+    pub scopes: Vec<String>,
+}
+
+
+/// Struct for OAuth 2.0 token exchange (RFC 8693) authentication configuration.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct STSAuthConfig {
+    /// URL of the security token service.
+    pub endpoint: String,
+    /// Source of the local identity token exchanged for an access token.
+    pub subject_token_source: SubjectTokenSource,
+    /// Type of the subject token, e.g. `urn:ietf:params:oauth:token-type:jwt`.
+    pub subject_token_type: String,
+    /// Audience of the requested token, if any.
+    pub audience: Option<String>,
+    /// Scopes of the requested token.
+    pub scopes: Vec<String>,
+    /// Safety margin subtracted from the token lifetime, so tokens are refreshed before they expire.
+    pub expiry_skew: Duration,
+}
+
+
+/// Enum representing the possible sources of a subject token.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SubjectTokenSource {
+    /// The token is read from a file on each exchange, so rotated tokens are picked up.
+    File(String),
+    /// The token is read from an environment variable on each exchange.
+    Env(String),
+}
+
+
+/// Struct for HMAC request-signing authentication configuration.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct HMACAuthConfig {
+    /// Shared secret used to sign requests.
+    pub secret: String,
+    /// Name of the header carrying the request signature.
+    pub signature_header: String,
+    /// Name of the header carrying the signing timestamp.
+    pub timestamp_header: String,
 }
 
 impl LokiConfig {
@@ -65,9 +1013,41 @@ impl LokiConfig {
     ///
     /// The `LOKI_URL` environment variable is used to determine the Loki URL.
     /// If `LOKI_URL` is not set, "http://localhost:3100" is used as the default.
+    /// The `LOKI_USERNAME` and `LOKI_PASSWORD` environment variables set basic auth credentials, the
+    /// `LOKI_BEARER_TOKEN` environment variable a bearer token, and the `LOKI_HEADERS` environment variable
+    /// additional headers, as comma-separated key=value pairs. If none is set, requests are not authenticated.
+    /// `LOKI_USERNAME` and `LOKI_BEARER_TOKEN` both set the `Authorization` header, so setting both is an error.
+    /// The `LOKI_LABELS` and `LOKI_EXTRA_FIELDS` environment variables set the labels and the extra fields,
+    /// as comma-separated key=value pairs. If not set, only the `service` label is sent.
+    /// The `LOKI_TENANT_ID` environment variable sets the tenant. If not set, no tenant is sent. It cannot be combined
+    /// with an `X-Scope-OrgID` header in `LOKI_HEADERS`.
     pub fn from_env() -> Result<Self> {
-        let url = std::env::var("LOKI_URL").unwrap_or("http://localhost:3100".to_string());
-        Ok(LokiConfig { url })
+        let url = read_env_var("LOKI_URL").unwrap_or("http://localhost:3100".to_string());
+        let mut headers = Vec::new();
+        if read_env_var("LOKI_USERNAME").is_ok() && read_env_var("LOKI_BEARER_TOKEN").is_ok() {
+            return Err(anyhow!("LOKI_USERNAME and LOKI_BEARER_TOKEN environment variables are mutually exclusive"));
+        }
+        if let Ok(username) = read_env_var("LOKI_USERNAME") {
+            let password = read_env_var("LOKI_PASSWORD").unwrap_or_default();
+            let credentials = base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", username, password));
+            headers.push(("Authorization".to_string(), format!("Basic {}", credentials)));
+        }
+        if let Ok(token) = read_env_var("LOKI_BEARER_TOKEN") {
+            headers.push(("Authorization".to_string(), format!("Bearer {}", token)));
+        }
+        headers.extend(parse_key_value_list(&read_env_var("LOKI_HEADERS").unwrap_or_default())?);
+        let tenant_id = read_env_var("LOKI_TENANT_ID").ok();
+        if tenant_id.is_some() && headers.iter().any(|(key, _)| key.eq_ignore_ascii_case("X-Scope-OrgID")) {
+            return Err(anyhow!("LOKI_TENANT_ID environment variable conflicts with the X-Scope-OrgID header of LOKI_HEADERS"));
+        }
+        let auth_config = if headers.is_empty() {
+            AuthConfig::Unauthenticated
+        } else {
+            AuthConfig::StaticHeaders(headers)
+        };
+        let labels = parse_key_value_list(&read_env_var("LOKI_LABELS").unwrap_or_default())?.into_iter().collect();
+        let extra_fields = parse_key_value_list(&read_env_var("LOKI_EXTRA_FIELDS").unwrap_or_default())?.into_iter().collect();
+        Ok(LokiConfig { url, auth_config, labels, extra_fields, tenant_id })
     }
 }
 
@@ -76,13 +1056,58 @@ impl AuthConfig {
     /// Creates an `AuthConfig` from environment variables.
     ///
     /// The `AUTH_PROVIDER` environment variable is used to determine the authentication provider.
-    /// Supported values are "gcp" and "unauthenticated". If not set, "unauthenticated" is used as the default.
+    /// Supported values are "gcp", "hmac", "sts", "static" and "unauthenticated". If not set, "unauthenticated" is used as the default.
+    /// A comma-separated list of providers (e.g. "gcp,static") chains them, merging their headers in order.
+    ///
+    /// If a provider is "static", the `AUTH_STATIC_HEADERS` environment variable is used to determine the headers,
+    /// as a comma-separated list of `name=value` pairs.
     pub fn from_env() -> Result<Self> {
-        match std::env::var("AUTH_PROVIDER").unwrap_or("unauthenticated".to_string()).as_str() {
+        let providers = read_env_var("AUTH_PROVIDER").unwrap_or("unauthenticated".to_string());
+        let mut configs = providers
+            .split(',')
+            .map(|provider| Self::provider_from_env(provider.trim()))
+            .collect::<Result<Vec<_>>>()?;
+        match configs.len() {
+            1 => Ok(configs.remove(0)),
+            _ => Ok(AuthConfig::Chain(configs)),
+        }
+    }
+
+    /// Creates the `AuthConfig` of a single provider from environment variables.
+    fn provider_from_env(provider: &str) -> Result<Self> {
+        match provider {
             "gcp" => Ok(AuthConfig::GCPAuth(GCPAuthConfig::from_env()?)),
+            "hmac" => Ok(AuthConfig::HMACAuth(HMACAuthConfig::from_env()?)),
+            "sts" => Ok(AuthConfig::STSAuth(STSAuthConfig::from_env()?)),
+            "static" => Ok(AuthConfig::StaticHeaders(parse_key_value_list(
+                &read_env_var("AUTH_STATIC_HEADERS").unwrap_or_default(),
+            )?)),
             _ => Ok(AuthConfig::Unauthenticated),
         }
     }
+
+    /// Returns whether the headers of the configuration sign each request, e.g. HMAC, alone or in a chain.
+    /// Such headers cannot be sent by the gRPC transports, which only retrieve headers not bound to a request.
+    #[cfg(not(feature = "noop"))]
+    pub(crate) fn signs_requests(&self) -> bool {
+        match self {
+            AuthConfig::HMACAuth(_) => true,
+            AuthConfig::Chain(configs) => configs.iter().any(AuthConfig::signs_requests),
+            AuthConfig::GCPAuth(_) | AuthConfig::STSAuth(_) | AuthConfig::StaticHeaders(_) | AuthConfig::Unauthenticated => false,
+        }
+    }
+}
+
+
+/// Implementation of the Debug trait for HMACAuthConfig, which does not print the secret
+impl std::fmt::Debug for HMACAuthConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HMACAuthConfig")
+            .field("secret", &"<redacted>")
+            .field("signature_header", &self.signature_header)
+            .field("timestamp_header", &self.timestamp_header)
+            .finish()
+    }
 }
 
 
@@ -91,63 +1116,1669 @@ impl GCPAuthConfig {
     ///
     /// The `GOOGLE_PROJECT_ID` environment variable is used to determine the GCP project ID.
     /// If `GOOGLE_PROJECT_ID` is not set, an error is returned.
+    ///
+    /// The `GCP_TOKEN_REFRESH_INTERVAL_SECS` and `GCP_TOKEN_EXPIRY_SKEW_SECS` environment variables set the
+    /// token refresh interval and expiry skew. If not set, 600 and 0 seconds are used as the defaults.
+    ///
+    /// The `GCP_TOKEN_SCOPES` environment variable sets the comma-separated OAuth scopes of the access token.
+    /// If not set, the default scopes of the credentials are used.
     pub fn from_env() -> Result<Self> {
-        let project_id = std::env::var("GOOGLE_PROJECT_ID")
+        let project_id = read_env_var("GOOGLE_PROJECT_ID")
             .map_err(|_| anyhow!("GOOGLE_PROJECT_ID environment variable not set"))?;
-        Ok(GCPAuthConfig { project_id })
+        let refresh_interval = Duration::from_secs(parse_env_var("GCP_TOKEN_REFRESH_INTERVAL_SECS")?.unwrap_or(600));
+        let expiry_skew = Duration::from_secs(parse_env_var("GCP_TOKEN_EXPIRY_SKEW_SECS")?.unwrap_or(0));
+        let scopes = parse_list(&read_env_var("GCP_TOKEN_SCOPES").unwrap_or_default());
+        let impersonate_service_account = GCPImpersonationConfig::from_env();
+        Ok(GCPAuthConfig { project_id, refresh_interval, expiry_skew, scopes, impersonate_service_account })
     }
 }
 
 
-impl LogConfig {
-    /// Creates a `LogConfig` from environment variables.
-    ///
-    /// The `LOG_PROVIDER` environment variable is used to determine the log provider.
-    /// The supported values are "loki", "otlp", and "stdout".
-    /// If `LOG_PROVIDER` is not set, "stdout" is used as the default.
+impl GCPImpersonationConfig {
+    /// Creates a `GCPImpersonationConfig` from environment variables.
     ///
-    /// If `LOG_PROVIDER` is "loki", the `LOKI_URL` environment variable is used to determine the Loki URL.
-    /// If `LOKI_URL` is not set, "http://localhost:3100" is used as the default.
-    pub fn from_env() -> Result<Self> {
-        match std::env::var("LOG_PROVIDER").unwrap_or("stdout".to_string()).as_str(){
-            "loki" => Ok(LogConfig::Loki(LokiConfig::from_env()?)),
-            "otlp" => Ok(LogConfig::OTLP),
-            "stdout" => Ok(LogConfig::Stdout),
-            _ => Err(anyhow!("Unsupported log config or not set")),
-        }
+    /// The `GCP_IMPERSONATE_SERVICE_ACCOUNT` environment variable is used to determine the service account
+    /// to impersonate. If it is not set, `None` is returned.
+    /// The `GCP_IMPERSONATION_DELEGATES` and `GCP_IMPERSONATION_SCOPES` environment variables set the
+    /// delegates and scopes, as comma-separated lists. If not set, they are empty.
+    pub fn from_env() -> Option<Self> {
+        let target_principal = read_env_var("GCP_IMPERSONATE_SERVICE_ACCOUNT").ok()?;
+        let delegates = parse_list(&read_env_var("GCP_IMPERSONATION_DELEGATES").unwrap_or_default());
+        let scopes = parse_list(&read_env_var("GCP_IMPERSONATION_SCOPES").unwrap_or_default());
+        Some(GCPImpersonationConfig { target_principal, delegates, scopes })
     }
 }
 
 
-impl OTLPTraceConfig {
-    /// Creates a new `OTLPTraceConfig` from environment variables.
+impl HMACAuthConfig {
+    /// Creates a new `HMACAuthConfig` from environment variables.
     ///
-    /// The `OTEL_EXPORTER_OTLP_ENDPOINT` environment variable is used to determine the OTLP endpoint.
-    /// If `OTEL_EXPORTER_OTLP_ENDPOINT` is not set, "http://localhost:4317" is used as the default.
+    /// The `HMAC_SECRET` environment variable is used as the shared secret. If it is not set, an error is returned.
+    /// The `HMAC_SIGNATURE_HEADER` and `HMAC_TIMESTAMP_HEADER` environment variables set the header names.
+    /// If not set, "x-signature" and "x-signature-timestamp" are used as the defaults.
     pub fn from_env() -> Result<Self> {
-        let endpoint = std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT")
-            .unwrap_or("http://localhost:4317".to_string());
-        let auth_config = AuthConfig::from_env()?;
-        Ok(OTLPTraceConfig { endpoint, auth_config })
+        let secret = read_env_var("HMAC_SECRET")
+            .map_err(|_| anyhow!("HMAC_SECRET environment variable not set"))?;
+        let signature_header = read_env_var("HMAC_SIGNATURE_HEADER").unwrap_or("x-signature".to_string());
+        let timestamp_header = read_env_var("HMAC_TIMESTAMP_HEADER").unwrap_or("x-signature-timestamp".to_string());
+        Ok(HMACAuthConfig { secret, signature_header, timestamp_header })
     }
 }
 
-impl TraceConfig {
+
+impl STSAuthConfig {
+    /// Creates a new `STSAuthConfig` from environment variables.
+    ///
+    /// The `STS_ENDPOINT` environment variable is used as the token service URL. If it is not set, an error is returned.
+    /// The subject token is read from the file named by `STS_SUBJECT_TOKEN_FILE` or, if not set, from the
+    /// environment variable named by `STS_SUBJECT_TOKEN_ENV`. If neither is set, an error is returned.
+    ///
+    /// The `STS_SUBJECT_TOKEN_TYPE` environment variable sets the subject token type. If not set,
+    /// "urn:ietf:params:oauth:token-type:jwt" is used as the default.
+    /// The `STS_AUDIENCE` and `STS_SCOPES` environment variables set the audience and the comma-separated scopes.
+    /// The `STS_TOKEN_EXPIRY_SKEW_SECS` environment variable sets the expiry skew. If not set, 60 seconds is used.
+    pub fn from_env() -> Result<Self> {
+        let endpoint = read_env_var("STS_ENDPOINT")
+            .map_err(|_| anyhow!("STS_ENDPOINT environment variable not set"))?;
+        let subject_token_source = match (read_env_var("STS_SUBJECT_TOKEN_FILE"), read_env_var("STS_SUBJECT_TOKEN_ENV")) {
+            (Ok(path), _) => SubjectTokenSource::File(path),
+            (Err(_), Ok(name)) => SubjectTokenSource::Env(name),
+            _ => return Err(anyhow!("STS_SUBJECT_TOKEN_FILE or STS_SUBJECT_TOKEN_ENV environment variable not set")),
+        };
+        let subject_token_type = read_env_var("STS_SUBJECT_TOKEN_TYPE")
+            .unwrap_or("urn:ietf:params:oauth:token-type:jwt".to_string());
+        let audience = read_env_var("STS_AUDIENCE").ok();
+        let scopes = parse_list(&read_env_var("STS_SCOPES").unwrap_or_default());
+        let expiry_skew = Duration::from_secs(parse_env_var("STS_TOKEN_EXPIRY_SKEW_SECS")?.unwrap_or(60));
+        Ok(STSAuthConfig { endpoint, subject_token_source, subject_token_type, audience, scopes, expiry_skew })
+    }
+}
+
+
+impl LogConfig {
+    /// Returns the authentication configuration of the exporter, if it authenticates.
+    ///
+    /// With several sinks, the configuration of the first sink that authenticates is returned.
+    pub fn auth_config(&self) -> Option<&AuthConfig> {
+        match self {
+            LogConfig::OTLP(config) => Some(&config.auth_config),
+            LogConfig::Loki(config) => Some(&config.auth_config),
+            LogConfig::Stdout(_) | LogConfig::File(_) | LogConfig::Syslog(_) => None,
+            #[cfg(feature = "journald")]
+            LogConfig::Journald => None,
+            LogConfig::Multi(sinks) => sinks.iter().find_map(|sink| sink.config.auth_config()),
+        }
+    }
+
+    /// Returns the authentication configurations of every sink that authenticates.
+    pub fn auth_configs(&self) -> Vec<&AuthConfig> {
+        match self {
+            LogConfig::Multi(sinks) => sinks.iter().flat_map(|sink| sink.config.auth_configs()).collect(),
+            _ => self.auth_config().into_iter().collect(),
+        }
+    }
+
+    /// Creates a `LogConfig` from environment variables.
+    ///
+    /// The `LOG_PROVIDER` environment variable is used to determine the log provider, or a comma-separated list
+    /// of log providers receiving the same events.
+    /// The supported values are "loki", "otlp", "stdout", "file", "syslog" and, with the `journald` feature, "journald".
+    /// If `LOG_PROVIDER` is not set, "stdout" is used as the default.
+    ///
+    /// The `LOG_<PROVIDER>_LEVEL` and `LOG_<PROVIDER>_MIN_LEVEL` environment variables, e.g. `LOG_STDOUT_LEVEL`, set the
+    /// level filter and the minimum level of each provider. With several providers, a level filter or a minimum level,
+    /// a `LogConfig::Multi` configuration is returned.
+    ///
+    /// If `LOG_PROVIDER` is "loki", the `LOKI_URL` environment variable is used to determine the Loki URL.
+    /// If `LOKI_URL` is not set, "http://localhost:3100" is used as the default.
+    pub fn from_env() -> Result<Self> {
+        let providers = parse_list(&read_env_var("LOG_PROVIDER").unwrap_or("stdout".to_string()));
+        let mut sinks = providers.iter().map(|provider| LogSinkConfig::from_env(provider)).collect::<Result<Vec<_>>>()?;
+        if sinks.is_empty() {
+            return Err(anyhow!("Unsupported log config or not set"));
+        }
+        if sinks.len() == 1 && sinks[0].level.is_none() && sinks[0].min_level.is_none() {
+            return Ok(sinks.remove(0).config);
+        }
+        Ok(LogConfig::Multi(sinks))
+    }
+
+    /// Creates the configuration of one log provider from environment variables.
+    fn provider_from_env(provider: &str) -> Result<Self> {
+        match provider {
+            "loki" => Ok(LogConfig::Loki(LokiConfig::from_env()?)),
+            "otlp" => Ok(LogConfig::OTLP(OTLPLogConfig::from_env()?)),
+            "stdout" => Ok(LogConfig::Stdout(StdoutLogConfig::from_env()?)),
+            "file" => Ok(LogConfig::File(FileLogConfig::from_env()?)),
+            "syslog" => Ok(LogConfig::Syslog(SyslogLogConfig::from_env()?)),
+            #[cfg(feature = "journald")]
+            "journald" => Ok(LogConfig::Journald),
+            _ => Err(anyhow!("Unsupported log config or not set")),
+        }
+    }
+}
+
+
+impl LogSinkConfig {
+    /// Creates the configuration of one sink from environment variables.
+    ///
+    /// The `LOG_<PROVIDER>_LEVEL` environment variable, e.g. `LOG_OTLP_LEVEL`, sets its level filter.
+    /// The `LOG_<PROVIDER>_MIN_LEVEL` environment variable, e.g. `LOG_OTLP_MIN_LEVEL`, sets its minimum level.
+    /// The supported values are "trace", "debug", "info", "warn" and "error".
+    fn from_env(provider: &str) -> Result<Self> {
+        let config = LogConfig::provider_from_env(provider)?;
+        let level = read_env_var(format!("LOG_{}_LEVEL", provider.to_uppercase())).ok();
+        let min_level = parse_env_var(&format!("LOG_{}_MIN_LEVEL", provider.to_uppercase()))?;
+        Ok(LogSinkConfig { config, level, min_level })
+    }
+}
+
+
+impl FileLogConfig {
+    /// Creates a new `FileLogConfig` from environment variables.
+    ///
+    /// The `LOG_FILE_PATH` environment variable sets the path of the file. If not set, "app.log" is used.
+    /// The `LOG_FILE_ROTATION` environment variable is used to determine the rotation policy.
+    /// The supported values are "daily", "hourly" and "size". If not set, "daily" is used.
+    /// With "size", the `LOG_FILE_MAX_SIZE_BYTES` environment variable sets the maximum size of the file.
+    /// If not set, 104857600 (100 MiB) is used.
+    /// The `LOG_FILE_FORMAT` environment variable is used to determine the output format, with the values of
+    /// `LOG_STDOUT_FORMAT`. If not set, "full" is used. See `CloudLogFormatConfig::from_env` for the variables of
+    /// the GCP and CloudWatch formats.
+    /// See `LogLineFields::from_env_with_prefix` and `LogBufferConfig::from_env_with_prefix` for the `LOG_FILE_*`
+    /// variables setting the metadata and the buffering of the lines.
+    pub fn from_env() -> Result<Self> {
+        let path = PathBuf::from(read_env_var("LOG_FILE_PATH").unwrap_or("app.log".to_string()));
+        let rotation = match read_env_var("LOG_FILE_ROTATION").unwrap_or("daily".to_string()).as_str() {
+            "daily" => LogRotation::Daily,
+            "hourly" => LogRotation::Hourly,
+            "size" => LogRotation::Size(parse_env_var("LOG_FILE_MAX_SIZE_BYTES")?.unwrap_or(104_857_600)),
+            _ => return Err(anyhow!("Unsupported log file rotation")),
+        };
+        let format = StdoutLogFormat::from_env_var("LOG_FILE_FORMAT")?;
+        let cloud_format = CloudLogFormatConfig::from_env(format)?;
+        let fields = LogLineFields::from_env_with_prefix("LOG_FILE")?;
+        let buffer = LogBufferConfig::from_env_with_prefix("LOG_FILE")?;
+        Ok(FileLogConfig { path, rotation, format, cloud_format, fields, buffer })
+    }
+}
+
+
+impl SyslogLogConfig {
+    /// Creates a new `SyslogLogConfig` from environment variables.
+    ///
+    /// The `LOG_SYSLOG_ENDPOINT` environment variable sets the endpoint of the syslog server, as `udp://host:port`,
+    /// `tcp://host:port` or `unix:///path`. If not set, the local syslog socket is used.
+    /// The `LOG_SYSLOG_FACILITY` environment variable sets the facility of the messages. If not set, "user" is used.
+    /// The `LOG_SYSLOG_FORMAT` environment variable is used to determine the message format.
+    /// The supported values are "rfc3164" and "rfc5424". If not set, "rfc5424" is used.
+    /// See `LogBufferConfig::from_env_with_prefix` for the `LOG_SYSLOG_*` variables setting the buffering of the messages.
+    pub fn from_env() -> Result<Self> {
+        let endpoint = read_env_var("LOG_SYSLOG_ENDPOINT").ok();
+        if let Some(endpoint) = endpoint.as_ref().filter(|e| !["udp://", "tcp://", "unix://"].iter().any(|s| e.starts_with(s))) {
+            return Err(anyhow!("Unsupported syslog endpoint: {}", endpoint));
+        }
+        let facility = read_env_var("LOG_SYSLOG_FACILITY").unwrap_or("user".to_string()).to_lowercase();
+        if syslog::Facility::from_str(&facility).is_err() {
+            return Err(anyhow!("Unsupported syslog facility: {}", facility));
+        }
+        let format = match read_env_var("LOG_SYSLOG_FORMAT").unwrap_or("rfc5424".to_string()).as_str() {
+            "rfc3164" => SyslogFormat::RFC3164,
+            "rfc5424" => SyslogFormat::RFC5424,
+            _ => return Err(anyhow!("Unsupported syslog format")),
+        };
+        let buffer = LogBufferConfig::from_env_with_prefix("LOG_SYSLOG")?;
+        Ok(SyslogLogConfig { endpoint, facility, format, buffer })
+    }
+}
+
+
+impl StdoutLogConfig {
+    /// Creates a new `StdoutLogConfig` from environment variables.
+    ///
+    /// The `LOG_STDOUT_FORMAT` environment variable is used to determine the output format.
+    /// The supported values are "full", "pretty", "compact", "json", "ecs", "gcp" and "cloudwatch". If not set, "full" is used.
+    /// See `CloudLogFormatConfig::from_env` for the variables of the "gcp" and "cloudwatch" formats.
+    ///
+    /// The `LOG_STDOUT_ANSI` environment variable enables or disables the colors when set to "true" or "false".
+    /// If not set, the lines are colored unless the `NO_COLOR` environment variable is set.
+    ///
+    /// The `LOG_STDOUT_TIMESTAMP_FORMAT` environment variable is used to determine the timestamp format.
+    /// The supported values are "default", "rfc3339", "epoch_millis" and "none", which writes no timestamp.
+    /// If not set, the timestamps of `tracing-subscriber` are kept.
+    ///
+    /// The `LOG_STDOUT_TIMEZONE` environment variable is used to determine the timezone.
+    /// The supported values are "utc" and "local". If not set, "utc" is used.
+    ///
+    /// See `LogLineFields::from_env_with_prefix` for the `LOG_STDOUT_*` variables setting the metadata of the lines.
+    /// The lines are buffered if the `LOG_STDOUT_BUFFERED_LINES` environment variable is set; see
+    /// `LogBufferConfig::from_env_with_prefix` for the `LOG_STDOUT_*` variables setting the buffering.
+    pub fn from_env() -> Result<Self> {
+        let default = StdoutLogConfig::default();
+        let format = StdoutLogFormat::from_env_var("LOG_STDOUT_FORMAT")?;
+        let cloud_format = CloudLogFormatConfig::from_env(format)?;
+        let ansi = parse_env_var("LOG_STDOUT_ANSI")?.or(default.ansi);
+        let (timestamps, timestamp_format) = match read_env_var("LOG_STDOUT_TIMESTAMP_FORMAT").ok().as_deref() {
+            None => (default.timestamps, default.timestamp_format),
+            Some("default") => (true, Some(TimestampFormat::Default)),
+            Some("rfc3339") => (true, Some(TimestampFormat::Rfc3339)),
+            Some("epoch_millis") => (true, Some(TimestampFormat::EpochMillis)),
+            Some("none") => (false, None),
+            _ => return Err(anyhow!("Unsupported stdout log timestamp format")),
+        };
+        let timezone = match read_env_var("LOG_STDOUT_TIMEZONE").unwrap_or("utc".to_string()).as_str() {
+            "utc" => Timezone::Utc,
+            "local" => Timezone::Local,
+            _ => return Err(anyhow!("Unsupported stdout log timezone")),
+        };
+        let fields = LogLineFields::from_env_with_prefix("LOG_STDOUT")?;
+        let buffer = match read_env_var("LOG_STDOUT_BUFFERED_LINES") {
+            Ok(_) => Some(LogBufferConfig::from_env_with_prefix("LOG_STDOUT")?),
+            Err(_) => None,
+        };
+        Ok(StdoutLogConfig { format, cloud_format, ansi, timestamps, timestamp_format, timezone, fields, buffer })
+    }
+}
+
+
+impl Default for LogBufferConfig {
+    /// Creates a `LogBufferConfig` buffering up to 128000 lines, the default of `tracing-appender`,
+    /// and dropping the lines recorded while the buffer is full.
+    fn default() -> Self {
+        LogBufferConfig {
+            buffered_lines: 128_000,
+            policy: LogBufferPolicy::Drop,
+        }
+    }
+}
+
+
+impl LogBufferConfig {
+    /// Creates a new `LogBufferConfig` from the environment variables of a log sink, e.g. `LOG_FILE`.
+    ///
+    /// The `<prefix>_BUFFERED_LINES` environment variable sets the maximum number of buffered lines.
+    /// If not set, 128000 is used.
+    /// The `<prefix>_BUFFER_POLICY` environment variable is used to determine the policy of a full buffer.
+    /// The supported values are "drop" and "block". If not set, "drop" is used.
+    fn from_env_with_prefix(prefix: &str) -> Result<Self> {
+        let default = LogBufferConfig::default();
+        let buffered_lines = parse_env_var(&format!("{}_BUFFERED_LINES", prefix))?.unwrap_or(default.buffered_lines);
+        let policy = match read_env_var(format!("{}_BUFFER_POLICY", prefix)).unwrap_or("drop".to_string()).as_str() {
+            "drop" => LogBufferPolicy::Drop,
+            "block" => LogBufferPolicy::Block,
+            _ => return Err(anyhow!("Unsupported log buffer policy")),
+        };
+        Ok(LogBufferConfig { buffered_lines, policy })
+    }
+}
+
+
+impl Default for LogLineFields {
+    /// Creates a `LogLineFields` with the metadata written by default by `tracing-subscriber`:
+    /// the target and the spans, without source location and thread.
+    fn default() -> Self {
+        LogLineFields {
+            source_location: false,
+            target: true,
+            thread_names: false,
+            thread_ids: false,
+            span_list: true,
+        }
+    }
+}
+
+
+impl LogLineFields {
+    /// Creates a new `LogLineFields` from the environment variables of a log sink, e.g. `LOG_STDOUT`.
+    ///
+    /// The `<prefix>_SOURCE_LOCATION`, `<prefix>_TARGET`, `<prefix>_THREAD_NAMES`, `<prefix>_THREAD_IDS` and
+    /// `<prefix>_SPAN_LIST` environment variables enable or disable each metadata when set to "true" or "false".
+    /// If not set, the defaults of `LogLineFields::default` are used.
+    fn from_env_with_prefix(prefix: &str) -> Result<Self> {
+        let default = LogLineFields::default();
+        Ok(LogLineFields {
+            source_location: parse_env_var(&format!("{}_SOURCE_LOCATION", prefix))?.unwrap_or(default.source_location),
+            target: parse_env_var(&format!("{}_TARGET", prefix))?.unwrap_or(default.target),
+            thread_names: parse_env_var(&format!("{}_THREAD_NAMES", prefix))?.unwrap_or(default.thread_names),
+            thread_ids: parse_env_var(&format!("{}_THREAD_IDS", prefix))?.unwrap_or(default.thread_ids),
+            span_list: parse_env_var(&format!("{}_SPAN_LIST", prefix))?.unwrap_or(default.span_list),
+        })
+    }
+}
+
+
+impl Default for StdoutLogConfig {
+    /// Creates a `StdoutLogConfig` with the full format and the colors and timestamps of `tracing-subscriber`.
+    fn default() -> Self {
+        StdoutLogConfig {
+            format: StdoutLogFormat::default(),
+            cloud_format: CloudLogFormatConfig::default(),
+            ansi: None,
+            timestamps: true,
+            timestamp_format: None,
+            timezone: Timezone::Utc,
+            fields: LogLineFields::default(),
+            buffer: None,
+        }
+    }
+}
+
+
+impl StdoutLogFormat {
+    /// Reads the output format of a log sink from the given environment variable, "full" if not set.
+    fn from_env_var(name: &str) -> Result<Self> {
+        match read_env_var(name).unwrap_or("full".to_string()).as_str() {
+            "full" => Ok(StdoutLogFormat::Full),
+            "pretty" => Ok(StdoutLogFormat::Pretty),
+            "compact" => Ok(StdoutLogFormat::Compact),
+            "json" => Ok(StdoutLogFormat::Json),
+            "ecs" => Ok(StdoutLogFormat::Ecs),
+            "gcp" => Ok(StdoutLogFormat::GcpJson),
+            "cloudwatch" => Ok(StdoutLogFormat::CloudWatchJson),
+            _ => Err(anyhow!("Unsupported log format: {}", name)),
+        }
+    }
+}
+
+
+impl CloudLogFormatConfig {
+    /// Creates a new `CloudLogFormatConfig` from environment variables, for a log sink with the given format.
+    ///
+    /// The `GOOGLE_PROJECT_ID` environment variable sets the project of the traces of the "gcp" format.
+    /// If it is not set with that format, an error is returned, as Cloud Logging only correlates the log lines
+    /// with the traces qualified with their project.
+    /// The `LOG_EMF_NAMESPACE` environment variable sets the namespace of the metrics embedded by the "cloudwatch" format.
+    /// If not set, no metric is embedded.
+    fn from_env(format: StdoutLogFormat) -> Result<Self> {
+        let gcp_project_id = read_env_var("GOOGLE_PROJECT_ID").ok();
+        if format == StdoutLogFormat::GcpJson && gcp_project_id.is_none() {
+            return Err(anyhow!("GOOGLE_PROJECT_ID environment variable not set, required by the gcp log format"));
+        }
+        Ok(CloudLogFormatConfig { gcp_project_id, emf_namespace: read_env_var("LOG_EMF_NAMESPACE").ok() })
+    }
+}
+
+
+impl OTLPLogConfig {
+    /// Creates a new `OTLPLogConfig` from environment variables.
+    ///
+    /// The `OTEL_EXPORTER_LOGS` environment variable is used to determine the transport.
+    /// The supported values are "inherit", "http" and "grpc". If not set, "inherit" is used as the default.
+    /// The `OTEL_EXPORTER_OTLP_LOGS_ENDPOINT` environment variable overrides the endpoint. If not set, the
+    /// endpoint is inherited from the trace configuration. With the "http" and "grpc" transports, the
+    /// `OTEL_EXPORTER_OTLP_ENDPOINT` environment variable is used instead, with the `/v1/logs` path for HTTP
+    /// replacing a `/v1/traces` path, and if it is not set either, the default endpoint of the transport is used.
+    ///
+    /// The `OTEL_EXPORTER_OTLP_LOGS_COMPRESSION` environment variable is used to determine the compression of the payloads,
+    /// falling back to `OTEL_EXPORTER_OTLP_COMPRESSION`. The supported values are "none", "gzip" and, with the `zstd`
+    /// feature, "zstd". If neither is set, the compression is inherited from the trace configuration with the "inherit"
+    /// transport, and the payloads are not compressed otherwise.
+    ///
+    /// See `AuthConfig::from_env`, `LogLimitsConfig::from_env`, `LogBatchConfig::from_env`,
+    /// `BinaryAttributePolicy::from_env` and `HttpEncoding::from_env` for the other variables used.
+    pub fn from_env() -> Result<Self> {
+        let auth_config = AuthConfig::from_env()?;
+        let limits = LogLimitsConfig::from_env()?;
+        let batch = LogBatchConfig::from_env()?;
+        let binary_policy = BinaryAttributePolicy::from_env()?;
+        let transport = match read_env_var("OTEL_EXPORTER_LOGS").unwrap_or("inherit".to_string()).as_str() {
+            "inherit" => LogTransport::Inherit,
+            "http" => LogTransport::HTTP,
+            "grpc" => LogTransport::GRPC,
+            _ => return Err(anyhow!("Unsupported log exporter transport")),
+        };
+        let endpoint = match read_env_var("OTEL_EXPORTER_OTLP_LOGS_ENDPOINT").ok() {
+            Some(endpoint) => Some(endpoint),
+            None => match transport {
+                LogTransport::Inherit => None,
+                LogTransport::HTTP => read_env_var("OTEL_EXPORTER_OTLP_ENDPOINT").ok()
+                    .map(|base| http_logs_endpoint(&base)),
+                LogTransport::GRPC => read_env_var("OTEL_EXPORTER_OTLP_ENDPOINT").ok(),
+            },
+        };
+        let encoding = HttpEncoding::from_env()?;
+        let compression = compression_from_env("OTEL_EXPORTER_OTLP_LOGS_COMPRESSION")?;
+        Ok(OTLPLogConfig { auth_config, limits, batch, binary_policy, transport, endpoint, encoding, compression })
+    }
+}
+
+
+impl Default for LogLimitsConfig {
+    /// Creates a `LogLimitsConfig` with the limits recommended by the OpenTelemetry specification:
+    /// 128 attributes per record and no length limits.
+    fn default() -> Self {
+        LogLimitsConfig {
+            max_attributes: Some(128),
+            max_attribute_value_length: None,
+            max_body_length: None,
+        }
+    }
+}
+
+
+impl LogLimitsConfig {
+    /// Creates a new `LogLimitsConfig` from environment variables.
+    ///
+    /// The `OTEL_LOGRECORD_ATTRIBUTE_COUNT_LIMIT` environment variable sets the maximum number of attributes.
+    /// If not set, 128 is used as the default.
+    /// The `OTEL_LOGRECORD_ATTRIBUTE_VALUE_LENGTH_LIMIT` and `OTEL_LOGRECORD_BODY_LENGTH_LIMIT` environment variables
+    /// set the maximum attribute value and body lengths. If not set, no length limit is applied.
+    pub fn from_env() -> Result<Self> {
+        let default = LogLimitsConfig::default();
+        Ok(LogLimitsConfig {
+            max_attributes: parse_env_var("OTEL_LOGRECORD_ATTRIBUTE_COUNT_LIMIT")?.or(default.max_attributes),
+            max_attribute_value_length: parse_env_var("OTEL_LOGRECORD_ATTRIBUTE_VALUE_LENGTH_LIMIT")?,
+            max_body_length: parse_env_var("OTEL_LOGRECORD_BODY_LENGTH_LIMIT")?,
+        })
+    }
+}
+
+
+impl Default for LogBatchConfig {
+    /// Creates a `LogBatchConfig` with the defaults of the OpenTelemetry specification:
+    /// 2048 queued records, 512 records per export and one export per second.
+    fn default() -> Self {
+        LogBatchConfig {
+            max_queue_size: 2048,
+            max_export_batch_size: 512,
+            scheduled_delay: Duration::from_millis(1000),
+        }
+    }
+}
+
+
+impl LogBatchConfig {
+    /// Creates a new `LogBatchConfig` from environment variables.
+    ///
+    /// The `OTEL_BLRP_MAX_QUEUE_SIZE` and `OTEL_BLRP_MAX_EXPORT_BATCH_SIZE` environment variables set the maximum
+    /// number of queued records and of records per export. If not set, 2048 and 512 are used as the defaults.
+    /// The `OTEL_BLRP_SCHEDULE_DELAY` environment variable sets the delay between two exports, in milliseconds.
+    /// If not set, 1000 is used as the default.
+    pub fn from_env() -> Result<Self> {
+        let default = LogBatchConfig::default();
+        Ok(LogBatchConfig {
+            max_queue_size: parse_env_var("OTEL_BLRP_MAX_QUEUE_SIZE")?.unwrap_or(default.max_queue_size),
+            max_export_batch_size: parse_env_var("OTEL_BLRP_MAX_EXPORT_BATCH_SIZE")?.unwrap_or(default.max_export_batch_size),
+            scheduled_delay: parse_env_var("OTEL_BLRP_SCHEDULE_DELAY")?.map(Duration::from_millis).unwrap_or(default.scheduled_delay),
+        })
+    }
+}
+
+
+impl OTLPTraceConfig {
+    /// Creates a new `OTLPTraceConfig` from environment variables.
+    ///
+    /// The `OTEL_EXPORTER_OTLP_ENDPOINT` environment variable is used to determine the OTLP endpoint.
+    /// If `OTEL_EXPORTER_OTLP_ENDPOINT` is not set, "http://localhost:4317" is used as the default.
+    /// The `OTEL_EXPORTER_OTLP_GRPC_METADATA` environment variable sets the metadata of the gRPC requests,
+    /// as comma-separated key=value pairs. If not set, no metadata is added. An invalid metadata key or value is an error.
+    /// The `OTEL_EXPORTER_OTLP_INSECURE_SKIP_VERIFY` environment variable disables the verification of the
+    /// certificate of the collector when set to "true". If not set, certificates are verified.
+    /// The `OTEL_EXPORTER_OTLP_TRACES_COMPRESSION` environment variable is used to determine the compression of the payloads,
+    /// falling back to `OTEL_EXPORTER_OTLP_COMPRESSION`. The supported values are "none", "gzip" and, with the `zstd`
+    /// feature, "zstd". If neither is set, "none" is used as the default.
+    ///
+    /// See `ExportPolicyConfig::from_env`, `GrpcChannelConfig::from_env`, `ExportMode::from_env`, `HttpEncoding::from_env`, `HttpVersion::from_env`,
+    /// `SamplerConfig::from_env`, `SpanLimitsConfig::from_env` and `IdGeneratorConfig::from_env` for the variables setting
+    /// the export resilience policy, the export mode, the HTTP payload encoding and version, the sampler, the span limits
+    /// and the ID generator, as well as the tuning of the gRPC channel.
+    pub fn from_env() -> Result<Self> {
+        let endpoint = read_env_var("OTEL_EXPORTER_OTLP_ENDPOINT")
+            .unwrap_or("http://localhost:4317".to_string());
+        let auth_config = AuthConfig::from_env()?;
+        let binary_policy = BinaryAttributePolicy::from_env()?;
+        let export_policy = ExportPolicyConfig::from_env()?;
+        let export_mode = ExportMode::from_env()?;
+        let encoding = HttpEncoding::from_env()?;
+        let http_version = HttpVersion::from_env()?;
+        let metadata = parse_key_value_list(&read_env_var("OTEL_EXPORTER_OTLP_GRPC_METADATA").unwrap_or_default())?;
+        validate_grpc_metadata(&metadata)?;
+        let channel = GrpcChannelConfig::from_env()?;
+        let insecure_skip_verify = parse_env_var("OTEL_EXPORTER_OTLP_INSECURE_SKIP_VERIFY")?.unwrap_or(false);
+        let sampler = SamplerConfig::from_env()?;
+        let span_limits = SpanLimitsConfig::from_env()?;
+        let id_generator = IdGeneratorConfig::from_env()?;
+        let compression = compression_from_env("OTEL_EXPORTER_OTLP_TRACES_COMPRESSION")?.unwrap_or_default();
+        Ok(OTLPTraceConfig { endpoint, auth_config, binary_policy, export_policy, export_mode, encoding, http_version, metadata, channel, insecure_skip_verify, sampler, span_limits, id_generator, compression })
+    }
+}
+
+
+/// Reads the compression of the OTLP payloads of a signal from its environment variable, falling back to
+/// `OTEL_EXPORTER_OTLP_COMPRESSION`, like the OpenTelemetry SDK. Returns `None` if neither is set.
+fn compression_from_env(signal_var: &str) -> Result<Option<ExportCompression>> {
+    let Ok(compression) = read_env_var(signal_var).or(read_env_var("OTEL_EXPORTER_OTLP_COMPRESSION")) else {
+        return Ok(None);
+    };
+    match compression.as_str() {
+        "none" => Ok(Some(ExportCompression::None)),
+        "gzip" => Ok(Some(ExportCompression::Gzip)),
+        "zstd" if cfg!(feature = "zstd") => Ok(Some(ExportCompression::Zstd)),
+        "zstd" => Err(anyhow!("The zstd OTLP compression requires the zstd feature")),
+        _ => Err(anyhow!("Unsupported OTLP compression")),
+    }
+}
+
+
+impl Default for ExportPolicyConfig {
+    /// Creates an `ExportPolicyConfig` with a 10 second timeout, no concurrency limit, no retries, a 30 second
+    /// limit on the retries once enabled, no disk buffer, one batch exported at a time and no message size limit.
+    fn default() -> Self {
+        ExportPolicyConfig {
+            timeout: Duration::from_secs(10),
+            concurrency_limit: None,
+            max_retries: 0,
+            max_elapsed: Duration::from_secs(30),
+            retry_budget_percent: 20,
+            disk_buffer: None,
+            max_concurrent_exports: 1,
+            max_message_size: None,
+        }
+    }
+}
+
+
+impl ExportPolicyConfig {
+    /// Creates a new `ExportPolicyConfig` from environment variables.
+    ///
+    /// The `OTEL_EXPORTER_OTLP_TIMEOUT` environment variable sets the export timeout, in milliseconds.
+    /// If not set, 10000 is used as the default.
+    /// The `OTEL_EXPORTER_OTLP_CONCURRENCY_LIMIT` environment variable sets the concurrency limit. If not set, no limit is applied.
+    /// The `OTEL_EXPORTER_OTLP_MAX_RETRIES` and `OTEL_EXPORTER_OTLP_RETRY_BUDGET_PERCENT` environment variables
+    /// set the maximum retries per export and the retry budget. If not set, 0 and 20 are used as the defaults.
+    /// The `OTEL_EXPORTER_OTLP_RETRY_MAX_ELAPSED_MS` environment variable sets the maximum time spent exporting
+    /// a batch, retries included, in milliseconds. If not set, 30000 is used as the default.
+    /// The `OTEL_BSP_MAX_CONCURRENT_EXPORTS` environment variable sets the maximum number of batches exported at once.
+    /// If not set, 1 is used as the default.
+    /// The `OTEL_EXPORTER_OTLP_MAX_MESSAGE_SIZE_BYTES` environment variable sets the maximum size of an export
+    /// request, in bytes. If not set, batches are not split.
+    /// See `DiskBufferConfig::from_env` for the disk buffer.
+    pub fn from_env() -> Result<Self> {
+        let default = ExportPolicyConfig::default();
+        Ok(ExportPolicyConfig {
+            timeout: parse_env_var("OTEL_EXPORTER_OTLP_TIMEOUT")?.map(Duration::from_millis).unwrap_or(default.timeout),
+            concurrency_limit: parse_env_var("OTEL_EXPORTER_OTLP_CONCURRENCY_LIMIT")?,
+            max_retries: parse_env_var("OTEL_EXPORTER_OTLP_MAX_RETRIES")?.unwrap_or(default.max_retries),
+            max_elapsed: parse_env_var("OTEL_EXPORTER_OTLP_RETRY_MAX_ELAPSED_MS")?.map(Duration::from_millis).unwrap_or(default.max_elapsed),
+            retry_budget_percent: parse_env_var("OTEL_EXPORTER_OTLP_RETRY_BUDGET_PERCENT")?.unwrap_or(default.retry_budget_percent),
+            disk_buffer: DiskBufferConfig::from_env()?,
+            max_concurrent_exports: parse_env_var("OTEL_BSP_MAX_CONCURRENT_EXPORTS")?.unwrap_or(default.max_concurrent_exports),
+            max_message_size: parse_env_var("OTEL_EXPORTER_OTLP_MAX_MESSAGE_SIZE_BYTES")?,
+        })
+    }
+}
+
+
+impl Default for GrpcChannelConfig {
+    /// Creates a `GrpcChannelConfig` without keep-alive or connect timeout, whose pings,
+    /// once enabled, are also sent while the channel is idle.
+    fn default() -> Self {
+        GrpcChannelConfig {
+            tcp_keepalive: None,
+            http2_keepalive_interval: None,
+            keepalive_timeout: None,
+            keepalive_while_idle: true,
+            connect_timeout: None,
+        }
+    }
+}
+
+
+impl GrpcChannelConfig {
+    /// Creates a new `GrpcChannelConfig` from environment variables.
+    ///
+    /// The `OTEL_EXPORTER_OTLP_GRPC_TCP_KEEPALIVE_SECS` environment variable sets the interval of the TCP keep-alive
+    /// probes, in seconds. If not set, TCP keep-alive is disabled.
+    /// The `OTEL_EXPORTER_OTLP_GRPC_KEEPALIVE_INTERVAL_SECS` and `OTEL_EXPORTER_OTLP_GRPC_KEEPALIVE_TIMEOUT_SECS` environment
+    /// variables set the interval of the HTTP/2 keep-alive pings and the time waited for their acknowledgement, in seconds.
+    /// If not set, no ping is sent and the timeout is 20 seconds.
+    /// The `OTEL_EXPORTER_OTLP_GRPC_KEEPALIVE_WHILE_IDLE` environment variable stops the pings while the channel is idle
+    /// when set to "false". If not set, "true" is used as the default.
+    /// The `OTEL_EXPORTER_OTLP_GRPC_CONNECT_TIMEOUT_MS` environment variable sets the connect timeout, in milliseconds.
+    /// If not set, only the export timeout applies.
+    pub fn from_env() -> Result<Self> {
+        let default = GrpcChannelConfig::default();
+        Ok(GrpcChannelConfig {
+            tcp_keepalive: parse_env_var("OTEL_EXPORTER_OTLP_GRPC_TCP_KEEPALIVE_SECS")?.map(Duration::from_secs),
+            http2_keepalive_interval: parse_env_var("OTEL_EXPORTER_OTLP_GRPC_KEEPALIVE_INTERVAL_SECS")?.map(Duration::from_secs),
+            keepalive_timeout: parse_env_var("OTEL_EXPORTER_OTLP_GRPC_KEEPALIVE_TIMEOUT_SECS")?.map(Duration::from_secs),
+            keepalive_while_idle: parse_env_var("OTEL_EXPORTER_OTLP_GRPC_KEEPALIVE_WHILE_IDLE")?.unwrap_or(default.keepalive_while_idle),
+            connect_timeout: parse_env_var("OTEL_EXPORTER_OTLP_GRPC_CONNECT_TIMEOUT_MS")?.map(Duration::from_millis),
+        })
+    }
+}
+
+
+impl DiskBufferConfig {
+    /// Creates a new `DiskBufferConfig` from environment variables.
+    ///
+    /// The `OTEL_EXPORTER_DISK_BUFFER_PATH` environment variable sets the buffer directory. If not set,
+    /// no buffer is used and `None` is returned.
+    /// The `OTEL_EXPORTER_DISK_BUFFER_MAX_BYTES` environment variable sets the maximum size of the buffer,
+    /// in bytes. If not set, 67108864 (64 MiB) is used as the default.
+    pub fn from_env() -> Result<Option<Self>> {
+        let Ok(path) = read_env_var("OTEL_EXPORTER_DISK_BUFFER_PATH") else {
+            return Ok(None);
+        };
+        Ok(Some(DiskBufferConfig {
+            path: PathBuf::from(path),
+            max_size: parse_env_var("OTEL_EXPORTER_DISK_BUFFER_MAX_BYTES")?.unwrap_or(64 * 1024 * 1024),
+        }))
+    }
+}
+
+
+impl BinaryAttributePolicy {
+    /// Creates a `BinaryAttributePolicy` from environment variables.
+    ///
+    /// The `OTEL_BINARY_ATTRIBUTE_POLICY` environment variable is used to determine the policy.
+    /// The supported values are "keep", "base64", "hash" and "drop".
+    /// If `OTEL_BINARY_ATTRIBUTE_POLICY` is not set, "keep" is used as the default.
+    ///
+    /// If `OTEL_BINARY_ATTRIBUTE_POLICY` is "base64", the `OTEL_BINARY_ATTRIBUTE_MAX_LENGTH` environment variable
+    /// is used to determine the maximum encoded length. If not set, 1024 is used as the default.
+    pub fn from_env() -> Result<Self> {
+        match read_env_var("OTEL_BINARY_ATTRIBUTE_POLICY").unwrap_or("keep".to_string()).as_str() {
+            "keep" => Ok(BinaryAttributePolicy::Keep),
+            "base64" => Ok(BinaryAttributePolicy::Base64 {
+                max_length: parse_env_var("OTEL_BINARY_ATTRIBUTE_MAX_LENGTH")?.unwrap_or(1024),
+            }),
+            "hash" => Ok(BinaryAttributePolicy::Hash),
+            "drop" => Ok(BinaryAttributePolicy::Drop),
+            _ => Err(anyhow!("Unsupported binary attribute policy")),
+        }
+    }
+}
+
+impl StdoutTraceConfig {
+    /// Creates a new `StdoutTraceConfig` from environment variables.
+    ///
+    /// The `OTEL_STDOUT_FORMAT` environment variable is used to determine the output format.
+    /// The supported values are "default", "pretty" and "json". If not set, "default" is used.
+    ///
+    /// The `OTEL_STDOUT_TIMESTAMP_FORMAT` environment variable is used to determine the timestamp format.
+    /// The supported values are "default", "rfc3339" and "epoch_millis". If not set, "default" is used.
+    ///
+    /// The `OTEL_STDOUT_TIMEZONE` environment variable is used to determine the timezone.
+    /// The supported values are "utc" and "local". If not set, "utc" is used.
+    ///
+    /// See `SamplerConfig::from_env`, `SpanLimitsConfig::from_env` and `IdGeneratorConfig::from_env` for the
+    /// variables setting the sampler, the span limits and the ID generator.
+    pub fn from_env() -> Result<Self> {
+        let format = match read_env_var("OTEL_STDOUT_FORMAT").unwrap_or("default".to_string()).as_str() {
+            "default" => StdoutFormat::Default,
+            "pretty" => StdoutFormat::Pretty,
+            "json" => StdoutFormat::Json,
+            _ => return Err(anyhow!("Unsupported stdout format")),
+        };
+        let timestamp_format = match read_env_var("OTEL_STDOUT_TIMESTAMP_FORMAT").unwrap_or("default".to_string()).as_str() {
+            "default" => TimestampFormat::Default,
+            "rfc3339" => TimestampFormat::Rfc3339,
+            "epoch_millis" => TimestampFormat::EpochMillis,
+            _ => return Err(anyhow!("Unsupported stdout timestamp format")),
+        };
+        let timezone = match read_env_var("OTEL_STDOUT_TIMEZONE").unwrap_or("utc".to_string()).as_str() {
+            "utc" => Timezone::Utc,
+            "local" => Timezone::Local,
+            _ => return Err(anyhow!("Unsupported stdout timezone")),
+        };
+        let sampler = SamplerConfig::from_env()?;
+        let span_limits = SpanLimitsConfig::from_env()?;
+        let id_generator = IdGeneratorConfig::from_env()?;
+        Ok(StdoutTraceConfig { format, timestamp_format, timezone, sampler, span_limits, id_generator })
+    }
+}
+
+
+impl ScopeFilter {
+    /// Returns whether the spans of the given instrumentation scope are accepted.
+    ///
+    /// # Arguments
+    ///
+    /// * `scope_name` - The name of the instrumentation scope.
+    pub fn matches(&self, scope_name: &str) -> bool {
+        let listed = |names: &[String]| names.iter().any(|name| {
+            scope_name.strip_prefix(name.as_str()).is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+        });
+        match self {
+            ScopeFilter::All => true,
+            ScopeFilter::Only(names) => listed(names),
+            ScopeFilter::Except(names) => !listed(names),
+        }
+    }
+}
+
+
+impl ZipkinConfig {
+    /// Creates a new `ZipkinConfig` from environment variables.
+    ///
+    /// The `OTEL_EXPORTER_ZIPKIN_ENDPOINT` environment variable is used to determine the Zipkin collector endpoint.
+    /// If not set, "http://localhost:9411/api/v2/spans" is used as the default.
+    ///
+    /// See `AuthConfig::from_env`, `SamplerConfig::from_env`, `SpanLimitsConfig::from_env` and
+    /// `IdGeneratorConfig::from_env` for the other variables used.
+    pub fn from_env() -> Result<Self> {
+        let endpoint = read_env_var("OTEL_EXPORTER_ZIPKIN_ENDPOINT")
+            .unwrap_or("http://localhost:9411/api/v2/spans".to_string());
+        let export_mode = ExportMode::from_env()?;
+        let auth_config = AuthConfig::from_env()?;
+        let sampler = SamplerConfig::from_env()?;
+        let span_limits = SpanLimitsConfig::from_env()?;
+        let id_generator = IdGeneratorConfig::from_env()?;
+        Ok(ZipkinConfig { endpoint, export_mode, auth_config, sampler, span_limits, id_generator })
+    }
+}
+
+
+impl DatadogAgentConfig {
+    /// Creates a new `DatadogAgentConfig` from environment variables.
+    ///
+    /// The `OTEL_EXPORTER_DATADOG_AGENT_URL` environment variable is used to determine the URL of the Datadog trace agent.
+    /// If not set, "http://localhost:8126" is used as the default.
+    ///
+    /// See `AuthConfig::from_env`, `SamplerConfig::from_env`, `SpanLimitsConfig::from_env` and
+    /// `IdGeneratorConfig::from_env` for the other variables used.
+    pub fn from_env() -> Result<Self> {
+        let agent_url = read_env_var("OTEL_EXPORTER_DATADOG_AGENT_URL")
+            .unwrap_or("http://localhost:8126".to_string());
+        let export_mode = ExportMode::from_env()?;
+        let auth_config = AuthConfig::from_env()?;
+        let sampler = SamplerConfig::from_env()?;
+        let span_limits = SpanLimitsConfig::from_env()?;
+        let id_generator = IdGeneratorConfig::from_env()?;
+        Ok(DatadogAgentConfig { agent_url, export_mode, auth_config, sampler, span_limits, id_generator })
+    }
+}
+
+
+impl XRayTraceConfig {
+    /// Creates a new `XRayTraceConfig` from environment variables.
+    ///
+    /// The `AWS_XRAY_DAEMON_ADDRESS` environment variable is used to determine the UDP address of the X-Ray daemon.
+    /// If not set, "127.0.0.1:2000" is used as the default.
+    /// The `OTEL_EXPORTER_XRAY_DAEMON_EXPORT` environment variable disables the export to the daemon when set
+    /// to "false". If not set, spans are exported.
+    ///
+    /// See `SamplerConfig::from_env` and `SpanLimitsConfig::from_env` for the other variables used.
+    /// The ID generator is always X-Ray compatible.
+    pub fn from_env() -> Result<Self> {
+        let daemon_address = parse_env_var::<bool>("OTEL_EXPORTER_XRAY_DAEMON_EXPORT")?
+            .unwrap_or(true)
+            .then(|| read_env_var("AWS_XRAY_DAEMON_ADDRESS").unwrap_or("127.0.0.1:2000".to_string()));
+        let export_mode = ExportMode::from_env()?;
+        let sampler = SamplerConfig::from_env()?;
+        let span_limits = SpanLimitsConfig::from_env()?;
+        Ok(XRayTraceConfig { daemon_address, export_mode, sampler, span_limits, id_generator: IdGeneratorConfig::XRay })
+    }
+}
+
+
+impl HttpEncoding {
+    /// Creates an `HttpEncoding` from environment variables.
+    ///
+    /// The `OTEL_EXPORTER_OTLP_ENCODING` environment variable is used to determine the encoding.
+    /// The supported values are "protobuf" and "json". If not set, "protobuf" is used as the default.
+    pub fn from_env() -> Result<Self> {
+        match read_env_var("OTEL_EXPORTER_OTLP_ENCODING").unwrap_or("protobuf".to_string()).as_str() {
+            "protobuf" => Ok(HttpEncoding::Protobuf),
+            "json" => Ok(HttpEncoding::Json),
+            _ => Err(anyhow!("Unsupported OTLP HTTP encoding")),
+        }
+    }
+}
+
+
+impl HttpVersion {
+    /// Creates an `HttpVersion` from environment variables.
+    ///
+    /// The `OTEL_EXPORTER_OTLP_HTTP_VERSION` environment variable is used to determine the version.
+    /// The supported values are "auto", "http1" and "http2". If not set, "auto" is used as the default.
+    pub fn from_env() -> Result<Self> {
+        match read_env_var("OTEL_EXPORTER_OTLP_HTTP_VERSION").unwrap_or("auto".to_string()).as_str() {
+            "auto" => Ok(HttpVersion::Auto),
+            "http1" => Ok(HttpVersion::Http1),
+            "http2" => Ok(HttpVersion::Http2),
+            _ => Err(anyhow!("Unsupported OTLP HTTP version")),
+        }
+    }
+}
+
+
+impl ExportMode {
+    /// Creates an `ExportMode` from environment variables.
+    ///
+    /// The `OTEL_TRACES_EXPORT_MODE` environment variable is used to determine the mode.
+    /// The supported values are "batch" and "simple". If not set, "batch" is used as the default.
+    pub fn from_env() -> Result<Self> {
+        match read_env_var("OTEL_TRACES_EXPORT_MODE").unwrap_or("batch".to_string()).as_str() {
+            "batch" => Ok(ExportMode::Batch),
+            "simple" => Ok(ExportMode::Simple),
+            _ => Err(anyhow!("Unsupported traces export mode")),
+        }
+    }
+}
+
+
+impl NoopTraceConfig {
+    /// Creates a new `NoopTraceConfig` from environment variables.
+    ///
+    /// See `SamplerConfig::from_env`, `SpanLimitsConfig::from_env` and `IdGeneratorConfig::from_env` for the
+    /// variables setting the sampler, the span limits and the ID generator.
+    pub fn from_env() -> Result<Self> {
+        let sampler = SamplerConfig::from_env()?;
+        let span_limits = SpanLimitsConfig::from_env()?;
+        let id_generator = IdGeneratorConfig::from_env()?;
+        Ok(NoopTraceConfig { sampler, span_limits, id_generator })
+    }
+}
+
+
+impl FileTraceConfig {
+    /// Creates a new `FileTraceConfig` from environment variables.
+    ///
+    /// The `OTEL_EXPORTER_FILE_PATH` environment variable sets the path of the file. If not set, "spans.jsonl" is used.
+    /// The `OTEL_EXPORTER_FILE_MAX_SIZE_BYTES` and `OTEL_EXPORTER_FILE_MAX_AGE_SECS` environment variables set the
+    /// rotation limits. If not set, the corresponding limit is not applied.
+    ///
+    /// See `SamplerConfig::from_env`, `SpanLimitsConfig::from_env` and `IdGeneratorConfig::from_env` for the other variables used.
+    pub fn from_env() -> Result<Self> {
+        let path = PathBuf::from(read_env_var("OTEL_EXPORTER_FILE_PATH").unwrap_or("spans.jsonl".to_string()));
+        let rotation = FileRotation {
+            max_size: parse_env_var("OTEL_EXPORTER_FILE_MAX_SIZE_BYTES")?,
+            max_age: parse_env_var("OTEL_EXPORTER_FILE_MAX_AGE_SECS")?.map(Duration::from_secs),
+        };
+        let export_mode = ExportMode::from_env()?;
+        let sampler = SamplerConfig::from_env()?;
+        let span_limits = SpanLimitsConfig::from_env()?;
+        let id_generator = IdGeneratorConfig::from_env()?;
+        Ok(FileTraceConfig { path, rotation, export_mode, sampler, span_limits, id_generator })
+    }
+}
+
+
+impl Default for SpanLimitsConfig {
+    /// Creates a `SpanLimitsConfig` with the limits recommended by the OpenTelemetry specification:
+    /// 128 attributes, events and links per span.
+    fn default() -> Self {
+        SpanLimitsConfig {
+            max_attributes_per_span: 128,
+            max_events_per_span: 128,
+            max_links_per_span: 128,
+        }
+    }
+}
+
+
+impl SpanLimitsConfig {
+    /// Creates a new `SpanLimitsConfig` from environment variables.
+    ///
+    /// The `OTEL_SPAN_ATTRIBUTE_COUNT_LIMIT`, `OTEL_SPAN_EVENT_COUNT_LIMIT` and `OTEL_SPAN_LINK_COUNT_LIMIT`
+    /// environment variables set the maximum number of attributes, events and links per span.
+    /// If not set, 128 is used as the default.
+    pub fn from_env() -> Result<Self> {
+        let default = SpanLimitsConfig::default();
+        Ok(SpanLimitsConfig {
+            max_attributes_per_span: parse_env_var("OTEL_SPAN_ATTRIBUTE_COUNT_LIMIT")?.unwrap_or(default.max_attributes_per_span),
+            max_events_per_span: parse_env_var("OTEL_SPAN_EVENT_COUNT_LIMIT")?.unwrap_or(default.max_events_per_span),
+            max_links_per_span: parse_env_var("OTEL_SPAN_LINK_COUNT_LIMIT")?.unwrap_or(default.max_links_per_span),
+        })
+    }
+}
+
+
+/// Implementation of the PartialEq trait for CustomSampler, comparing samplers by identity.
+impl PartialEq for CustomSampler {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+
+impl Eq for CustomSampler {}
+
+
+/// Implementation of the Hash trait for CustomSampler, hashing samplers by identity.
+impl Hash for CustomSampler {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Arc::as_ptr(&self.0).cast::<()>().hash(state);
+    }
+}
+
+
+/// Implementation of the PartialEq trait for SpanStore, comparing stores by identity.
+impl PartialEq for SpanStore {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+
+impl Eq for SpanStore {}
+
+
+/// Implementation of the Hash trait for SpanStore, hashing stores by identity.
+impl Hash for SpanStore {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Arc::as_ptr(&self.0).hash(state);
+    }
+}
+
+
+/// Implementation of the PartialEq trait for CustomIdGenerator, comparing generators by identity.
+impl PartialEq for CustomIdGenerator {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+
+impl Eq for CustomIdGenerator {}
+
+
+/// Implementation of the Hash trait for CustomIdGenerator, hashing generators by identity.
+impl Hash for CustomIdGenerator {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Arc::as_ptr(&self.0).cast::<()>().hash(state);
+    }
+}
+
+
+impl IdGeneratorConfig {
+    /// Creates a new `IdGeneratorConfig` from environment variables.
+    ///
+    /// The `OTEL_TRACES_ID_GENERATOR` environment variable is used to determine the generator.
+    /// The supported values are "random" and "xray". If not set, "random" is used as the default.
+    pub fn from_env() -> Result<Self> {
+        match read_env_var("OTEL_TRACES_ID_GENERATOR").unwrap_or("random".to_string()).as_str() {
+            "random" => Ok(IdGeneratorConfig::Random),
+            "xray" => Ok(IdGeneratorConfig::XRay),
+            _ => Err(anyhow!("Unsupported traces ID generator")),
+        }
+    }
+}
+
+
+/// Implementation of the PartialEq trait for SamplerConfig, comparing ratios by their bit pattern like `Hash`,
+/// so `0.0` and `-0.0` differ and NaN, which can be constructed directly, equals itself.
+impl PartialEq for SamplerConfig {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (SamplerConfig::AlwaysOn, SamplerConfig::AlwaysOn) | (SamplerConfig::AlwaysOff, SamplerConfig::AlwaysOff) => true,
+            (SamplerConfig::TraceIdRatio(ratio), SamplerConfig::TraceIdRatio(other)) => ratio.to_bits() == other.to_bits(),
+            (SamplerConfig::RateLimiting { spans_per_second }, SamplerConfig::RateLimiting { spans_per_second: other }) => spans_per_second == other,
+            (SamplerConfig::ParentBased(root), SamplerConfig::ParentBased(other)) => root == other,
+            (SamplerConfig::Custom(sampler), SamplerConfig::Custom(other)) => sampler == other,
+            _ => false,
+        }
+    }
+}
+
+
+/// Comparing ratios by their bit pattern makes the equality of `SamplerConfig` reflexive, even for NaN.
+impl Eq for SamplerConfig {}
+
+
+/// Implementation of the Hash trait for SamplerConfig, hashing ratios by their bit pattern.
+impl Hash for SamplerConfig {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            SamplerConfig::TraceIdRatio(ratio) => ratio.to_bits().hash(state),
+            SamplerConfig::RateLimiting { spans_per_second } => spans_per_second.hash(state),
+            SamplerConfig::ParentBased(root) => root.hash(state),
+            SamplerConfig::Custom(sampler) => sampler.hash(state),
+            SamplerConfig::AlwaysOn | SamplerConfig::AlwaysOff => {},
+        }
+    }
+}
+
+
+impl Default for SamplerConfig {
+    /// Creates the default sampler of the OpenTelemetry SDK, `ParentBased(AlwaysOn)`.
+    fn default() -> Self {
+        SamplerConfig::ParentBased(Box::new(SamplerConfig::AlwaysOn))
+    }
+}
+
+
+impl SamplerConfig {
+    /// Creates a `SamplerConfig` from environment variables.
+    ///
+    /// The `OTEL_TRACES_SAMPLER` environment variable is used to determine the sampler.
+    /// The supported values are "always_on", "always_off", "traceidratio", "ratelimiting", "parentbased_always_on",
+    /// "parentbased_always_off", "parentbased_traceidratio" and "parentbased_ratelimiting".
+    /// If not set, "parentbased_always_on" is used.
+    ///
+    /// For the ratio samplers, the `OTEL_TRACES_SAMPLER_ARG` environment variable sets the ratio, between 0 and 1.
+    /// If not set, 1.0 is used as the default.
+    /// For the rate limiting samplers, it sets the number of spans per second. If not set, 100 is used as the default.
+    pub fn from_env() -> Result<Self> {
+        let ratio = || -> Result<SamplerConfig> {
+            let ratio: f64 = parse_env_var("OTEL_TRACES_SAMPLER_ARG")?.unwrap_or(1.0);
+            if !(0.0..=1.0).contains(&ratio) {
+                return Err(anyhow!("OTEL_TRACES_SAMPLER_ARG must be between 0 and 1"));
+            }
+            Ok(SamplerConfig::TraceIdRatio(ratio))
+        };
+        let rate_limiting = || -> Result<SamplerConfig> {
+            let spans_per_second = parse_env_var("OTEL_TRACES_SAMPLER_ARG")?.unwrap_or(100);
+            Ok(SamplerConfig::RateLimiting { spans_per_second })
+        };
+        match read_env_var("OTEL_TRACES_SAMPLER").unwrap_or("parentbased_always_on".to_string()).as_str() {
+            "always_on" => Ok(SamplerConfig::AlwaysOn),
+            "always_off" => Ok(SamplerConfig::AlwaysOff),
+            "traceidratio" => ratio(),
+            "ratelimiting" => rate_limiting(),
+            "parentbased_always_on" => Ok(SamplerConfig::ParentBased(Box::new(SamplerConfig::AlwaysOn))),
+            "parentbased_always_off" => Ok(SamplerConfig::ParentBased(Box::new(SamplerConfig::AlwaysOff))),
+            "parentbased_traceidratio" => Ok(SamplerConfig::ParentBased(Box::new(ratio()?))),
+            "parentbased_ratelimiting" => Ok(SamplerConfig::ParentBased(Box::new(rate_limiting()?))),
+            _ => Err(anyhow!("Unsupported traces sampler")),
+        }
+    }
+}
+
+
+impl SpanNameRule {
+    /// Creates a new `SpanNameRule`.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - The regular expression matched against span names.
+    /// * `replacement` - The replacement of the matches.
+    pub fn new(pattern: &str, replacement: &str) -> Self {
+        SpanNameRule { pattern: pattern.to_string(), replacement: replacement.to_string() }
+    }
+
+    /// Returns rules replacing UUID and numeric path segments with `{uuid}` and `{id}`,
+    /// e.g. `GET /users/12345` with `GET /users/{id}`.
+    pub fn path_ids() -> Vec<Self> {
+        vec![
+            SpanNameRule::new(r"/[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}\b", "/{uuid}"),
+            SpanNameRule::new(r"/[0-9]+\b", "/{id}"),
+        ]
+    }
+}
+
+
+impl RedactionConfig {
+    /// Creates a `RedactionConfig` from environment variables.
+    ///
+    /// The `OTEL_REDACTION_KEYS` environment variable sets the comma-separated keys of the redacted attributes,
+    /// and the `OTEL_REDACTION_VALUE_PATTERNS` environment variable sets the whitespace-separated regular
+    /// expressions matched against attribute values. If neither is set, `None` is returned and redaction is disabled.
+    ///
+    /// The `OTEL_REDACTION_ACTION` environment variable sets the action, "drop" or "hash". If not set, "drop" is used as the default.
+    /// The `OTEL_REDACTION_HASH_KEY` environment variable sets the secret key of the hashes.
+    pub fn from_env() -> Result<Option<Self>> {
+        Self::from_env_with_prefix("OTEL_REDACTION")
+    }
+
+    /// Creates the `RedactionConfig` of the log events from environment variables.
+    ///
+    /// The `LOG_REDACTION_KEYS`, `LOG_REDACTION_VALUE_PATTERNS`, `LOG_REDACTION_ACTION` and `LOG_REDACTION_HASH_KEY`
+    /// environment variables set the redacted field names, value patterns, action and hash key, as their `OTEL_REDACTION_` counterparts do for spans.
+    /// If the `LOG_REDACTION_PII` environment variable is "true", the rules of `RedactionConfig::pii` are added.
+    /// If none is set, `None` is returned and redaction is disabled.
+    pub fn log_from_env() -> Result<Option<Self>> {
+        let config = Self::from_env_with_prefix("LOG_REDACTION")?;
+        if !parse_env_var::<bool>("LOG_REDACTION_PII")?.unwrap_or(false) {
+            return Ok(config);
+        }
+        let mut pii = Self::pii();
+        if let Some(config) = config {
+            pii.keys.extend(config.keys);
+            pii.value_patterns.extend(config.value_patterns);
+            pii.action = config.action;
+            pii.hash_key = config.hash_key;
+        }
+        Ok(Some(pii))
+    }
+
+    /// Returns the rules redacting common personal data and secrets: email addresses, bearer tokens, JWTs
+    /// and credit card numbers passing the Luhn checksum, as well as the values of fields whose name contains
+    /// password, secret, token, API key or authorization, in any case, e.g. `X-Auth-Token` or `db_password`.
+    pub fn pii() -> Self {
+        RedactionConfig {
+            keys: ["*password*", "*passwd*", "*secret*", "*token*", "*api_key*", "*apikey*", "*authorization*"]
+                .iter()
+                .map(|key| key.to_string())
+                .collect(),
+            value_patterns: vec![
+                r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}".to_string(),
+                r"(?i)bearer\s+[A-Za-z0-9._~+/=-]+".to_string(),
+                r"eyJ[A-Za-z0-9_-]+\.[A-Za-z0-9_-]+\.[A-Za-z0-9_-]*".to_string(),
+                CARD_NUMBER_PATTERN.to_string(),
+            ],
+            action: RedactionAction::Drop,
+            hash_key: None,
+        }
+    }
+
+    /// Creates a `RedactionConfig` from the `<prefix>_KEYS`, `<prefix>_VALUE_PATTERNS`, `<prefix>_ACTION`
+    /// and `<prefix>_HASH_KEY` environment variables.
+    fn from_env_with_prefix(prefix: &str) -> Result<Option<Self>> {
+        let keys = read_env_var(format!("{}_KEYS", prefix)).map(|value| parse_list(&value)).unwrap_or_default();
+        let value_patterns: Vec<String> = read_env_var(format!("{}_VALUE_PATTERNS", prefix))
+            .map(|value| value.split_whitespace().map(str::to_string).collect())
+            .unwrap_or_default();
+        if keys.is_empty() && value_patterns.is_empty() {
+            return Ok(None);
+        }
+        let action = match read_env_var(format!("{}_ACTION", prefix)).unwrap_or("drop".to_string()).as_str() {
+            "drop" => RedactionAction::Drop,
+            "hash" => RedactionAction::Hash,
+            _ => return Err(anyhow!("Unsupported redaction action")),
+        };
+        let hash_key = read_env_var(format!("{}_HASH_KEY", prefix)).ok();
+        Ok(Some(RedactionConfig { keys, value_patterns, action, hash_key }))
+    }
+}
+
+
+impl TailSamplingConfig {
+    /// Creates a `TailSamplingConfig` from environment variables.
+    ///
+    /// The `OTEL_TAIL_SAMPLING_RATIO` environment variable sets the fraction of traces without errors or slow
+    /// spans that are exported, between 0 and 1. If it is not set, `None` is returned and tail sampling is disabled.
+    ///
+    /// The `OTEL_TAIL_SAMPLING_LATENCY_THRESHOLD_MS` and `OTEL_TAIL_SAMPLING_DECISION_WAIT_MS` environment variables
+    /// set the latency threshold and decision wait, in milliseconds. If not set, 1000 and 10000 are used as the defaults.
+    pub fn from_env() -> Result<Option<Self>> {
+        let Some(ratio) = parse_env_var::<f64>("OTEL_TAIL_SAMPLING_RATIO")? else {
+            return Ok(None);
+        };
+        if !(0.0..=1.0).contains(&ratio) {
+            return Err(anyhow!("OTEL_TAIL_SAMPLING_RATIO must be between 0 and 1"));
+        }
+        let latency_threshold = Duration::from_millis(parse_env_var("OTEL_TAIL_SAMPLING_LATENCY_THRESHOLD_MS")?.unwrap_or(1000));
+        let decision_wait = Duration::from_millis(parse_env_var("OTEL_TAIL_SAMPLING_DECISION_WAIT_MS")?.unwrap_or(10000));
+        Ok(Some(TailSamplingConfig { decision_wait, latency_threshold, ratio }))
+    }
+}
+
+
+impl Default for PropagationConfig {
+    /// Creates a `PropagationConfig` with the W3C TraceContext and Baggage formats.
+    fn default() -> Self {
+        PropagationConfig { propagators: vec![Propagator::TraceContext, Propagator::Baggage] }
+    }
+}
+
+
+impl PropagationConfig {
+    /// Creates a `PropagationConfig` from environment variables.
+    ///
+    /// The `OTEL_PROPAGATORS` environment variable is a comma-separated list of propagation formats.
+    /// The supported values are "tracecontext", "baggage", "b3", "b3multi", "jaeger", "xray" and "none",
+    /// which disables propagation.
+    /// If not set, "tracecontext,baggage" is used as the default.
+    pub fn from_env() -> Result<Self> {
+        PropagationConfig::from_env_or(PropagationConfig::default())
+    }
+
+    /// Creates a `PropagationConfig` with the X-Ray, W3C TraceContext and Baggage formats, for services
+    /// traced with AWS X-Ray.
+    pub fn xray() -> Self {
+        PropagationConfig { propagators: vec![Propagator::XRay, Propagator::TraceContext, Propagator::Baggage] }
+    }
+
+    /// Creates a `PropagationConfig` from environment variables, like `from_env`, with the given default.
+    ///
+    /// # Arguments
+    ///
+    /// * `default` - The configuration used if `OTEL_PROPAGATORS` is not set.
+    pub fn from_env_or(default: PropagationConfig) -> Result<Self> {
+        let Ok(value) = read_env_var("OTEL_PROPAGATORS") else {
+            return Ok(default);
+        };
+        let mut propagators = Vec::new();
+        for name in parse_list(&value) {
+            match name.as_str() {
+                "tracecontext" => propagators.push(Propagator::TraceContext),
+                "baggage" => propagators.push(Propagator::Baggage),
+                "b3" => propagators.push(Propagator::B3),
+                "b3multi" => propagators.push(Propagator::B3Multi),
+                "jaeger" => propagators.push(Propagator::Jaeger),
+                "xray" => propagators.push(Propagator::XRay),
+                "none" => {},
+                _ => return Err(anyhow!("Unsupported propagator: {}", name)),
+            }
+        }
+        Ok(PropagationConfig { propagators })
+    }
+}
+
+
+impl SpanEnrichmentConfig {
+    /// Creates a `SpanEnrichmentConfig` from environment variables.
+    ///
+    /// The `OTEL_DEPLOYMENT_ENVIRONMENT` environment variable sets the `deployment.environment` attribute.
+    /// The service version is a resource attribute, see `resource::get_resource`.
+    /// The `OTEL_SPAN_ENRICHMENT_ATTRIBUTES` environment variable adds fixed attributes, as comma-separated
+    /// key=value pairs.
+    /// The `OTEL_SPAN_ENRICHMENT_FROM_ENV` environment variable adds attributes read from other environment
+    /// variables, as comma-separated attribute=VARIABLE pairs, e.g. `k8s.pod.name=POD_NAME,cloud.region=AWS_REGION`.
+    /// Unset variables are skipped.
+    /// If no attribute is set, `None` is returned and spans are not enriched.
+    pub fn from_env() -> Result<Option<Self>> {
+        let mut attributes = Vec::new();
+        if let Ok(environment) = read_env_var("OTEL_DEPLOYMENT_ENVIRONMENT") {
+            attributes.push(("deployment.environment".to_string(), environment));
+        }
+        attributes.extend(parse_key_value_list(&read_env_var("OTEL_SPAN_ENRICHMENT_ATTRIBUTES").unwrap_or_default())?);
+        for (key, variable) in parse_key_value_list(&read_env_var("OTEL_SPAN_ENRICHMENT_FROM_ENV").unwrap_or_default())? {
+            if let Ok(value) = std::env::var(&variable) {
+                attributes.push((key, value));
+            }
+        }
+        Ok((!attributes.is_empty()).then_some(SpanEnrichmentConfig { attributes }))
+    }
+}
+
+
+impl ErrorFlushConfig {
+    /// Creates an `ErrorFlushConfig` from environment variables.
+    ///
+    /// The `OTEL_FLUSH_ON_ERROR_INTERVAL_MS` environment variable sets the minimum interval between flushes, in milliseconds.
+    /// If it is not set, `None` is returned and ERROR events do not trigger flushes.
+    pub fn from_env() -> Result<Option<Self>> {
+        Ok(parse_env_var::<u64>("OTEL_FLUSH_ON_ERROR_INTERVAL_MS")?
+            .map(|millis| ErrorFlushConfig { min_interval: Duration::from_millis(millis) }))
+    }
+}
+
+
+impl LogRateLimitConfig {
+    /// Creates a `LogRateLimitConfig` from environment variables.
+    ///
+    /// The `OTEL_LOG_RATE_LIMIT_MAX_EVENTS` environment variable sets the maximum number of identical events per window.
+    /// If it is not set, `None` is returned and events are not rate limited.
+    /// The `OTEL_LOG_RATE_LIMIT_WINDOW_MS` environment variable sets the duration of the window, in milliseconds.
+    /// If it is not set, 60000 is used.
+    pub fn from_env() -> Result<Option<Self>> {
+        let Some(max_events) = parse_env_var::<u32>("OTEL_LOG_RATE_LIMIT_MAX_EVENTS")? else {
+            return Ok(None);
+        };
+        let window = Duration::from_millis(parse_env_var("OTEL_LOG_RATE_LIMIT_WINDOW_MS")?.unwrap_or(60_000));
+        Ok(Some(LogRateLimitConfig { max_events, window }))
+    }
+}
+
+
+impl ProcessMetricsConfig {
+    /// Creates a `ProcessMetricsConfig` from environment variables.
+    ///
+    /// The `OTEL_RESTART_MARKER_FILE` environment variable sets the file persisting the number of starts.
+    /// If not set, no file is used.
+    pub fn from_env() -> Self {
+        ProcessMetricsConfig { restart_marker: read_env_var("OTEL_RESTART_MARKER_FILE").ok().map(PathBuf::from) }
+    }
+}
+
+
+impl HeartbeatConfig {
+    /// Creates a `HeartbeatConfig` from environment variables.
+    ///
+    /// The `OTEL_HEARTBEAT_INTERVAL_SECS` environment variable is used to determine the heartbeat interval.
+    /// If it is not set, `None` is returned and no heartbeat is emitted. A value of 0 is rejected.
+    pub fn from_env() -> Result<Option<Self>> {
+        match parse_env_var::<u64>("OTEL_HEARTBEAT_INTERVAL_SECS")? {
+            Some(0) => Err(anyhow!("OTEL_HEARTBEAT_INTERVAL_SECS must be greater than 0")),
+            Some(secs) => Ok(Some(HeartbeatConfig { interval: Duration::from_secs(secs) })),
+            None => Ok(None),
+        }
+    }
+}
+
+
+impl TraceConfig {
+    /// Returns the sampler of the tracer provider.
+    pub fn sampler(&self) -> &SamplerConfig {
+        match self {
+            TraceConfig::HTTP(config) | TraceConfig::GRPC(config) | TraceConfig::REQWEST(config) => &config.sampler,
+            TraceConfig::StdOut(config) => &config.sampler,
+            TraceConfig::Zipkin(config) => &config.sampler,
+            TraceConfig::DatadogAgent(config) => &config.sampler,
+            TraceConfig::XRay(config) => &config.sampler,
+            TraceConfig::File(config) => &config.sampler,
+            TraceConfig::InMemory(config) => &config.sampler,
+            TraceConfig::Noop(config) => &config.sampler,
+        }
+    }
+
+    /// Returns the limits applied to the recorded spans.
+    pub fn span_limits(&self) -> &SpanLimitsConfig {
+        match self {
+            TraceConfig::HTTP(config) | TraceConfig::GRPC(config) | TraceConfig::REQWEST(config) => &config.span_limits,
+            TraceConfig::StdOut(config) => &config.span_limits,
+            TraceConfig::Zipkin(config) => &config.span_limits,
+            TraceConfig::DatadogAgent(config) => &config.span_limits,
+            TraceConfig::XRay(config) => &config.span_limits,
+            TraceConfig::File(config) => &config.span_limits,
+            TraceConfig::InMemory(config) => &config.span_limits,
+            TraceConfig::Noop(config) => &config.span_limits,
+        }
+    }
+
+    /// Replaces the sampler of the tracer provider with a user-implemented sampler.
+    ///
+    /// # Arguments
+    ///
+    /// * `sampler` - The sampler deciding which spans are recorded and exported.
+    pub fn with_custom_sampler(mut self, sampler: Box<dyn ShouldSample>) -> Self {
+        let custom = SamplerConfig::Custom(CustomSampler(Arc::from(sampler)));
+        match &mut self {
+            TraceConfig::HTTP(config) | TraceConfig::GRPC(config) | TraceConfig::REQWEST(config) => config.sampler = custom,
+            TraceConfig::StdOut(config) => config.sampler = custom,
+            TraceConfig::Zipkin(config) => config.sampler = custom,
+            TraceConfig::DatadogAgent(config) => config.sampler = custom,
+            TraceConfig::XRay(config) => config.sampler = custom,
+            TraceConfig::File(config) => config.sampler = custom,
+            TraceConfig::InMemory(config) => config.sampler = custom,
+            TraceConfig::Noop(config) => config.sampler = custom,
+        }
+        self
+    }
+
+    /// Returns the generator of the trace and span IDs.
+    pub fn id_generator(&self) -> &IdGeneratorConfig {
+        match self {
+            TraceConfig::HTTP(config) | TraceConfig::GRPC(config) | TraceConfig::REQWEST(config) => &config.id_generator,
+            TraceConfig::StdOut(config) => &config.id_generator,
+            TraceConfig::Zipkin(config) => &config.id_generator,
+            TraceConfig::DatadogAgent(config) => &config.id_generator,
+            TraceConfig::XRay(config) => &config.id_generator,
+            TraceConfig::File(config) => &config.id_generator,
+            TraceConfig::InMemory(config) => &config.id_generator,
+            TraceConfig::Noop(config) => &config.id_generator,
+        }
+    }
+
+    /// Replaces the ID generator of the tracer provider with a user-implemented generator.
+    ///
+    /// # Arguments
+    ///
+    /// * `id_generator` - The generator of the trace and span IDs.
+    pub fn with_custom_id_generator(mut self, id_generator: Box<dyn IdGenerator>) -> Self {
+        let custom = IdGeneratorConfig::Custom(CustomIdGenerator(Arc::from(id_generator)));
+        match &mut self {
+            TraceConfig::HTTP(config) | TraceConfig::GRPC(config) | TraceConfig::REQWEST(config) => config.id_generator = custom,
+            TraceConfig::StdOut(config) => config.id_generator = custom,
+            TraceConfig::Zipkin(config) => config.id_generator = custom,
+            TraceConfig::DatadogAgent(config) => config.id_generator = custom,
+            TraceConfig::XRay(config) => config.id_generator = custom,
+            TraceConfig::File(config) => config.id_generator = custom,
+            TraceConfig::InMemory(config) => config.id_generator = custom,
+            TraceConfig::Noop(config) => config.id_generator = custom,
+        }
+        self
+    }
+
+    /// Returns the authentication configuration of the exporter, if it authenticates.
+    pub fn auth_config(&self) -> Option<&AuthConfig> {
+        match self {
+            TraceConfig::HTTP(config) | TraceConfig::GRPC(config) | TraceConfig::REQWEST(config) => Some(&config.auth_config),
+            TraceConfig::Zipkin(config) => Some(&config.auth_config),
+            TraceConfig::DatadogAgent(config) => Some(&config.auth_config),
+            TraceConfig::StdOut(_) | TraceConfig::XRay(_) | TraceConfig::File(_) | TraceConfig::InMemory(_) | TraceConfig::Noop(_) => None,
+        }
+    }
+
     /// Creates a `TraceConfig` from environment variables.
     ///
     /// The `OTEL_EXPORTER_TRACES` environment variable is used to determine the trace exporter.
-    /// The supported values are "grpc" and "stdout".
+    /// The supported values are "grpc", "http", "reqwest", "stdout", "zipkin", "datadog", "xray", "file" and "noop".
     /// If `OTEL_EXPORTER_TRACES` is not set, "stdout" is used as the default.
     ///
     /// If `OTEL_EXPORTER_TRACES` is "grpc", the `OTEL_EXPORTER_OTLP_ENDPOINT` environment variable is used to determine the OTLP endpoint.
     /// If `OTEL_EXPORTER_OTLP_ENDPOINT` is not set, "http://localhost:4317" is used as the default.
     pub fn from_env() -> Result<Self> {
-        match std::env::var("OTEL_EXPORTER_TRACES").unwrap_or("stdout".to_string()).as_str() {
+        match read_env_var("OTEL_EXPORTER_TRACES").unwrap_or("stdout".to_string()).as_str() {
             "grpc" => Ok(TraceConfig::GRPC(OTLPTraceConfig::from_env()?)),
             "http" => Ok(TraceConfig::HTTP(OTLPTraceConfig::from_env()?)),
             "reqwest" => Ok(TraceConfig::REQWEST(OTLPTraceConfig::from_env()?)),
-            "stdout" => Ok(TraceConfig::StdOut),
+            "stdout" => Ok(TraceConfig::StdOut(StdoutTraceConfig::from_env()?)),
+            "zipkin" => Ok(TraceConfig::Zipkin(ZipkinConfig::from_env()?)),
+            "datadog" => Ok(TraceConfig::DatadogAgent(DatadogAgentConfig::from_env()?)),
+            "xray" => Ok(TraceConfig::XRay(XRayTraceConfig::from_env()?)),
+            "file" => Ok(TraceConfig::File(FileTraceConfig::from_env()?)),
+            "noop" => Ok(TraceConfig::Noop(NoopTraceConfig::from_env()?)),
             _ => Err(anyhow!("Unsupported trace config or not set")),
         }
     }
 }
+
+
+/// Enum representing the possible value types of an environment variable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EnvVarType {
+    /// Free-form string.
+    String,
+    /// Non-negative integer.
+    Integer,
+    /// Decimal number.
+    Decimal,
+    /// Comma-separated list of values.
+    List,
+    /// Comma-separated list of `key=value` pairs.
+    KeyValueList,
+    /// One of the listed values.
+    OneOf(&'static [&'static str]),
+}
+
+
+/// Description of an environment variable read by the configuration layer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EnvVar {
+    /// Name of the variable.
+    pub name: &'static str,
+    /// Default value used when the variable is not set, if any.
+    pub default: Option<&'static str>,
+    /// Type of the value.
+    pub value_type: EnvVarType,
+    /// Configuration field fed by the variable, e.g. `GCPAuthConfig::project_id`.
+    pub field: &'static str,
+}
+
+
+/// Prefixes of the environment variables owned by this crate, used to detect unknown variables.
+const ENV_VAR_PREFIXES: &[&str] = &["OTEL_", "AUTH_", "GCP_", "GOOGLE_PROJECT_ID", "HMAC_", "STS_", "LOKI_", "LOG_PROVIDER", "LOG_STDOUT_", "LOG_OTLP_LEVEL", "LOG_OTLP_MIN_LEVEL", "LOG_LOKI_LEVEL", "LOG_LOKI_MIN_LEVEL", "LOG_JOURNALD_LEVEL", "LOG_JOURNALD_MIN_LEVEL", "LOG_FILE_", "LOG_SYSLOG_", "LOG_REDACTION_", "LOG_EMF_"];
+
+
+/// Environment variables of the OpenTelemetry specification that share the `OTEL_` prefix but are read by the
+/// OpenTelemetry SDK and exporters, or by any SDK, rather than by the configuration layer. They are not unknown.
+const SDK_ENV_VARS: &[&str] = &[
+    "OTEL_SDK_DISABLED", "OTEL_LOG_LEVEL", "OTEL_SERVICE_NAME", "OTEL_RESOURCE_ATTRIBUTES",
+    "OTEL_TRACES_EXPORTER", "OTEL_METRICS_EXPORTER", "OTEL_LOGS_EXPORTER",
+    "OTEL_BSP_SCHEDULE_DELAY", "OTEL_BSP_EXPORT_TIMEOUT", "OTEL_BSP_MAX_EXPORT_BATCH_SIZE",
+    "OTEL_BLRP_EXPORT_TIMEOUT", "OTEL_METRIC_EXPORT_INTERVAL", "OTEL_METRIC_EXPORT_TIMEOUT",
+    "OTEL_ATTRIBUTE_COUNT_LIMIT", "OTEL_ATTRIBUTE_VALUE_LENGTH_LIMIT", "OTEL_SPAN_ATTRIBUTE_VALUE_LENGTH_LIMIT",
+    "OTEL_EVENT_ATTRIBUTE_COUNT_LIMIT", "OTEL_LINK_ATTRIBUTE_COUNT_LIMIT",
+    "OTEL_EXPORTER_OTLP_PROTOCOL", "OTEL_EXPORTER_OTLP_HEADERS",
+    "OTEL_EXPORTER_OTLP_TRACES_ENDPOINT", "OTEL_EXPORTER_OTLP_TRACES_HEADERS", "OTEL_EXPORTER_OTLP_TRACES_TIMEOUT", "OTEL_EXPORTER_OTLP_TRACES_PROTOCOL",
+    "OTEL_EXPORTER_OTLP_LOGS_HEADERS", "OTEL_EXPORTER_OTLP_LOGS_TIMEOUT", "OTEL_EXPORTER_OTLP_LOGS_PROTOCOL",
+    "OTEL_EXPORTER_OTLP_METRICS_ENDPOINT", "OTEL_EXPORTER_OTLP_METRICS_HEADERS", "OTEL_EXPORTER_OTLP_METRICS_TIMEOUT",
+    "OTEL_EXPORTER_OTLP_METRICS_COMPRESSION", "OTEL_EXPORTER_OTLP_METRICS_PROTOCOL",
+    "OTEL_EXPORTER_ZIPKIN_TIMEOUT",
+];
+
+
+/// Shorthand to declare an `EnvVar`.
+const fn env_var(name: &'static str, default: Option<&'static str>, value_type: EnvVarType, field: &'static str) -> EnvVar {
+    EnvVar { name, default, value_type, field }
+}
+
+
+/// Environment variables read by the configuration layer. Every variable read with `read_env_var` must be listed.
+const ENV_VARS: &[EnvVar] = &[
+    env_var("OTEL_EXPORTER_TRACES", Some("stdout"), EnvVarType::OneOf(&["grpc", "http", "reqwest", "stdout", "zipkin", "datadog", "xray", "file", "noop"]), "TraceConfig"),
+    env_var("OTEL_EXPORTER_FILE_PATH", Some("spans.jsonl"), EnvVarType::String, "FileTraceConfig::path"),
+    env_var("OTEL_EXPORTER_FILE_MAX_SIZE_BYTES", None, EnvVarType::Integer, "FileRotation::max_size"),
+    env_var("OTEL_EXPORTER_FILE_MAX_AGE_SECS", None, EnvVarType::Integer, "FileRotation::max_age"),
+    env_var("OTEL_EXPORTER_OTLP_ENCODING", Some("protobuf"), EnvVarType::OneOf(&["protobuf", "json"]), "HttpEncoding"),
+    env_var("OTEL_EXPORTER_OTLP_HTTP_VERSION", Some("auto"), EnvVarType::OneOf(&["auto", "http1", "http2"]), "HttpVersion"),
+    env_var("OTEL_TRACES_EXPORT_MODE", Some("batch"), EnvVarType::OneOf(&["batch", "simple"]), "ExportMode"),
+    env_var("OTEL_EXPORTER_ZIPKIN_ENDPOINT", Some("http://localhost:9411/api/v2/spans"), EnvVarType::String, "ZipkinConfig::endpoint"),
+    env_var("OTEL_EXPORTER_DATADOG_AGENT_URL", Some("http://localhost:8126"), EnvVarType::String, "DatadogAgentConfig::agent_url"),
+    env_var("AWS_XRAY_DAEMON_ADDRESS", Some("127.0.0.1:2000"), EnvVarType::String, "XRayTraceConfig::daemon_address"),
+    env_var("OTEL_EXPORTER_XRAY_DAEMON_EXPORT", Some("true"), EnvVarType::OneOf(&["true", "false"]), "XRayTraceConfig::daemon_address"),
+    env_var("OTEL_EXPORTER_OTLP_ENDPOINT", Some("http://localhost:4317"), EnvVarType::String, "OTLPTraceConfig::endpoint"),
+    env_var("OTEL_EXPORTER_OTLP_GRPC_METADATA", None, EnvVarType::KeyValueList, "OTLPTraceConfig::metadata"),
+    env_var("OTEL_EXPORTER_OTLP_INSECURE_SKIP_VERIFY", Some("false"), EnvVarType::OneOf(&["true", "false"]), "OTLPTraceConfig::insecure_skip_verify"),
+    env_var("OTEL_EXPORTER_OTLP_TRACES_COMPRESSION", None, EnvVarType::OneOf(&["none", "gzip", "zstd"]), "OTLPTraceConfig::compression"),
+    env_var("OTEL_EXPORTER_OTLP_COMPRESSION", Some("none"), EnvVarType::OneOf(&["none", "gzip", "zstd"]), "OTLPTraceConfig::compression"),
+    env_var("OTEL_EXPORTER_OTLP_GRPC_TCP_KEEPALIVE_SECS", None, EnvVarType::Integer, "GrpcChannelConfig::tcp_keepalive"),
+    env_var("OTEL_EXPORTER_OTLP_GRPC_KEEPALIVE_INTERVAL_SECS", None, EnvVarType::Integer, "GrpcChannelConfig::http2_keepalive_interval"),
+    env_var("OTEL_EXPORTER_OTLP_GRPC_KEEPALIVE_TIMEOUT_SECS", Some("20"), EnvVarType::Integer, "GrpcChannelConfig::keepalive_timeout"),
+    env_var("OTEL_EXPORTER_OTLP_GRPC_KEEPALIVE_WHILE_IDLE", Some("true"), EnvVarType::OneOf(&["true", "false"]), "GrpcChannelConfig::keepalive_while_idle"),
+    env_var("OTEL_EXPORTER_OTLP_GRPC_CONNECT_TIMEOUT_MS", None, EnvVarType::Integer, "GrpcChannelConfig::connect_timeout"),
+    env_var("OTEL_EXPORTER_OTLP_MAX_MESSAGE_SIZE_BYTES", None, EnvVarType::Integer, "ExportPolicyConfig::max_message_size"),
+    env_var("OTEL_EXPORTER_OTLP_TIMEOUT", Some("10000"), EnvVarType::Integer, "ExportPolicyConfig::timeout"),
+    env_var("OTEL_EXPORTER_OTLP_CONCURRENCY_LIMIT", None, EnvVarType::Integer, "ExportPolicyConfig::concurrency_limit"),
+    env_var("OTEL_EXPORTER_OTLP_MAX_RETRIES", Some("0"), EnvVarType::Integer, "ExportPolicyConfig::max_retries"),
+    env_var("OTEL_EXPORTER_OTLP_RETRY_MAX_ELAPSED_MS", Some("30000"), EnvVarType::Integer, "ExportPolicyConfig::max_elapsed"),
+    env_var("OTEL_EXPORTER_OTLP_RETRY_BUDGET_PERCENT", Some("20"), EnvVarType::Integer, "ExportPolicyConfig::retry_budget_percent"),
+    env_var("OTEL_EXPORTER_DISK_BUFFER_PATH", None, EnvVarType::String, "DiskBufferConfig::path"),
+    env_var("OTEL_EXPORTER_DISK_BUFFER_MAX_BYTES", Some("67108864"), EnvVarType::Integer, "DiskBufferConfig::max_size"),
+    env_var("OTEL_TRACES_SAMPLER", Some("parentbased_always_on"), EnvVarType::OneOf(&[
+        "always_on", "always_off", "traceidratio", "ratelimiting",
+        "parentbased_always_on", "parentbased_always_off", "parentbased_traceidratio", "parentbased_ratelimiting",
+    ]), "SamplerConfig"),
+    env_var("OTEL_TRACES_SAMPLER_ARG", None, EnvVarType::Decimal, "SamplerConfig"),
+    env_var("OTEL_SPAN_ATTRIBUTE_COUNT_LIMIT", Some("128"), EnvVarType::Integer, "SpanLimitsConfig::max_attributes_per_span"),
+    env_var("OTEL_SPAN_EVENT_COUNT_LIMIT", Some("128"), EnvVarType::Integer, "SpanLimitsConfig::max_events_per_span"),
+    env_var("OTEL_SPAN_LINK_COUNT_LIMIT", Some("128"), EnvVarType::Integer, "SpanLimitsConfig::max_links_per_span"),
+    env_var("OTEL_TRACES_ID_GENERATOR", Some("random"), EnvVarType::OneOf(&["random", "xray"]), "IdGeneratorConfig"),
+    env_var("OTEL_STDOUT_FORMAT", Some("default"), EnvVarType::OneOf(&["default", "pretty", "json"]), "StdoutTraceConfig::format"),
+    env_var("OTEL_STDOUT_TIMESTAMP_FORMAT", Some("default"), EnvVarType::OneOf(&["default", "rfc3339", "epoch_millis"]), "StdoutTraceConfig::timestamp_format"),
+    env_var("OTEL_STDOUT_TIMEZONE", Some("utc"), EnvVarType::OneOf(&["utc", "local"]), "StdoutTraceConfig::timezone"),
+    env_var("OTEL_BINARY_ATTRIBUTE_POLICY", Some("keep"), EnvVarType::OneOf(&["keep", "base64", "hash", "drop"]), "BinaryAttributePolicy"),
+    env_var("OTEL_BINARY_ATTRIBUTE_MAX_LENGTH", Some("1024"), EnvVarType::Integer, "BinaryAttributePolicy::Base64::max_length"),
+    env_var("OTEL_HEARTBEAT_INTERVAL_SECS", None, EnvVarType::Integer, "HeartbeatConfig::interval"),
+    env_var("OTEL_PROPAGATORS", Some("tracecontext,baggage"), EnvVarType::List, "PropagationConfig::propagators"),
+    env_var("OTEL_DEPLOYMENT_ENVIRONMENT", None, EnvVarType::String, "SpanEnrichmentConfig::attributes"),
+    env_var("OTEL_SERVICE_VERSION", None, EnvVarType::String, "resource::get_resource"),
+    env_var("OTEL_SPAN_ENRICHMENT_ATTRIBUTES", None, EnvVarType::KeyValueList, "SpanEnrichmentConfig::attributes"),
+    env_var("OTEL_SPAN_ENRICHMENT_FROM_ENV", None, EnvVarType::KeyValueList, "SpanEnrichmentConfig::attributes"),
+    env_var("OTEL_FLUSH_ON_ERROR_INTERVAL_MS", None, EnvVarType::Integer, "ErrorFlushConfig::min_interval"),
+    env_var("OTEL_LOG_RATE_LIMIT_MAX_EVENTS", None, EnvVarType::Integer, "LogRateLimitConfig::max_events"),
+    env_var("OTEL_LOG_RATE_LIMIT_WINDOW_MS", Some("60000"), EnvVarType::Integer, "LogRateLimitConfig::window"),
+    env_var("OTEL_RESTART_MARKER_FILE", None, EnvVarType::String, "ProcessMetricsConfig::restart_marker"),
+    env_var("OTEL_REDACTION_KEYS", None, EnvVarType::List, "RedactionConfig::keys"),
+    env_var("OTEL_REDACTION_VALUE_PATTERNS", None, EnvVarType::String, "RedactionConfig::value_patterns"),
+    env_var("OTEL_REDACTION_ACTION", Some("drop"), EnvVarType::OneOf(&["drop", "hash"]), "RedactionConfig::action"),
+    env_var("OTEL_REDACTION_HASH_KEY", None, EnvVarType::String, "RedactionConfig::hash_key"),
+    env_var("LOG_REDACTION_KEYS", None, EnvVarType::List, "RedactionConfig::keys"),
+    env_var("LOG_REDACTION_VALUE_PATTERNS", None, EnvVarType::String, "RedactionConfig::value_patterns"),
+    env_var("LOG_REDACTION_ACTION", Some("drop"), EnvVarType::OneOf(&["drop", "hash"]), "RedactionConfig::action"),
+    env_var("LOG_REDACTION_HASH_KEY", None, EnvVarType::String, "RedactionConfig::hash_key"),
+    env_var("LOG_REDACTION_PII", Some("false"), EnvVarType::OneOf(&["true", "false"]), "RedactionConfig::pii"),
+    env_var("OTEL_TAIL_SAMPLING_RATIO", None, EnvVarType::Decimal, "TailSamplingConfig::ratio"),
+    env_var("OTEL_TAIL_SAMPLING_LATENCY_THRESHOLD_MS", Some("1000"), EnvVarType::Integer, "TailSamplingConfig::latency_threshold"),
+    env_var("OTEL_TAIL_SAMPLING_DECISION_WAIT_MS", Some("10000"), EnvVarType::Integer, "TailSamplingConfig::decision_wait"),
+    env_var("OTEL_BSP_MAX_QUEUE_SIZE", Some("2048"), EnvVarType::Integer, "batch span processor queue size"),
+    env_var("OTEL_BSP_MAX_CONCURRENT_EXPORTS", Some("1"), EnvVarType::Integer, "ExportPolicyConfig::max_concurrent_exports"),
+    env_var("LOG_PROVIDER", Some("stdout"), EnvVarType::List, "LogConfig"),
+    env_var("LOG_STDOUT_FORMAT", Some("full"), EnvVarType::OneOf(&["full", "pretty", "compact", "json", "ecs", "gcp", "cloudwatch"]), "StdoutLogConfig::format"),
+    env_var("LOG_STDOUT_ANSI", None, EnvVarType::OneOf(&["true", "false"]), "StdoutLogConfig::ansi"),
+    env_var("LOG_STDOUT_TIMESTAMP_FORMAT", None, EnvVarType::OneOf(&["default", "rfc3339", "epoch_millis", "none"]), "StdoutLogConfig::timestamp_format"),
+    env_var("LOG_STDOUT_TIMEZONE", Some("utc"), EnvVarType::OneOf(&["utc", "local"]), "StdoutLogConfig::timezone"),
+    env_var("LOG_STDOUT_SOURCE_LOCATION", Some("false"), EnvVarType::OneOf(&["true", "false"]), "StdoutLogConfig::fields::source_location"),
+    env_var("LOG_STDOUT_TARGET", Some("true"), EnvVarType::OneOf(&["true", "false"]), "StdoutLogConfig::fields::target"),
+    env_var("LOG_STDOUT_THREAD_NAMES", Some("false"), EnvVarType::OneOf(&["true", "false"]), "StdoutLogConfig::fields::thread_names"),
+    env_var("LOG_STDOUT_THREAD_IDS", Some("false"), EnvVarType::OneOf(&["true", "false"]), "StdoutLogConfig::fields::thread_ids"),
+    env_var("LOG_STDOUT_SPAN_LIST", Some("true"), EnvVarType::OneOf(&["true", "false"]), "StdoutLogConfig::fields::span_list"),
+    env_var("LOG_FILE_SOURCE_LOCATION", Some("false"), EnvVarType::OneOf(&["true", "false"]), "FileLogConfig::fields::source_location"),
+    env_var("LOG_FILE_TARGET", Some("true"), EnvVarType::OneOf(&["true", "false"]), "FileLogConfig::fields::target"),
+    env_var("LOG_FILE_THREAD_NAMES", Some("false"), EnvVarType::OneOf(&["true", "false"]), "FileLogConfig::fields::thread_names"),
+    env_var("LOG_FILE_THREAD_IDS", Some("false"), EnvVarType::OneOf(&["true", "false"]), "FileLogConfig::fields::thread_ids"),
+    env_var("LOG_FILE_SPAN_LIST", Some("true"), EnvVarType::OneOf(&["true", "false"]), "FileLogConfig::fields::span_list"),
+    env_var("LOG_STDOUT_BUFFERED_LINES", None, EnvVarType::Integer, "LogBufferConfig::buffered_lines"),
+    env_var("LOG_STDOUT_BUFFER_POLICY", Some("drop"), EnvVarType::OneOf(&["drop", "block"]), "LogBufferConfig::policy"),
+    env_var("LOG_FILE_BUFFERED_LINES", Some("128000"), EnvVarType::Integer, "LogBufferConfig::buffered_lines"),
+    env_var("LOG_FILE_BUFFER_POLICY", Some("drop"), EnvVarType::OneOf(&["drop", "block"]), "LogBufferConfig::policy"),
+    env_var("LOG_EMF_NAMESPACE", None, EnvVarType::String, "CloudLogFormatConfig::emf_namespace"),
+    env_var("LOG_FILE_PATH", Some("app.log"), EnvVarType::String, "FileLogConfig::path"),
+    env_var("LOG_FILE_ROTATION", Some("daily"), EnvVarType::OneOf(&["daily", "hourly", "size"]), "FileLogConfig::rotation"),
+    env_var("LOG_FILE_MAX_SIZE_BYTES", Some("104857600"), EnvVarType::Integer, "LogRotation::Size"),
+    env_var("LOG_FILE_FORMAT", Some("full"), EnvVarType::OneOf(&["full", "pretty", "compact", "json", "ecs", "gcp", "cloudwatch"]), "FileLogConfig::format"),
+    env_var("LOG_SYSLOG_ENDPOINT", None, EnvVarType::String, "SyslogLogConfig::endpoint"),
+    env_var("LOG_SYSLOG_FACILITY", Some("user"), EnvVarType::String, "SyslogLogConfig::facility"),
+    env_var("LOG_SYSLOG_FORMAT", Some("rfc5424"), EnvVarType::OneOf(&["rfc3164", "rfc5424"]), "SyslogLogConfig::format"),
+    env_var("LOG_SYSLOG_BUFFERED_LINES", Some("128000"), EnvVarType::Integer, "LogBufferConfig::buffered_lines"),
+    env_var("LOG_SYSLOG_BUFFER_POLICY", Some("drop"), EnvVarType::OneOf(&["drop", "block"]), "LogBufferConfig::policy"),
+    env_var("LOG_LOKI_LEVEL", None, EnvVarType::String, "LogSinkConfig::level"),
+    env_var("LOG_OTLP_LEVEL", None, EnvVarType::String, "LogSinkConfig::level"),
+    env_var("LOG_STDOUT_LEVEL", None, EnvVarType::String, "LogSinkConfig::level"),
+    env_var("LOG_FILE_LEVEL", None, EnvVarType::String, "LogSinkConfig::level"),
+    env_var("LOG_SYSLOG_LEVEL", None, EnvVarType::String, "LogSinkConfig::level"),
+    env_var("LOG_JOURNALD_LEVEL", None, EnvVarType::String, "LogSinkConfig::level"),
+    env_var("LOG_LOKI_MIN_LEVEL", None, EnvVarType::OneOf(&["trace", "debug", "info", "warn", "error"]), "LogSinkConfig::min_level"),
+    env_var("LOG_OTLP_MIN_LEVEL", None, EnvVarType::OneOf(&["trace", "debug", "info", "warn", "error"]), "LogSinkConfig::min_level"),
+    env_var("LOG_STDOUT_MIN_LEVEL", None, EnvVarType::OneOf(&["trace", "debug", "info", "warn", "error"]), "LogSinkConfig::min_level"),
+    env_var("LOG_FILE_MIN_LEVEL", None, EnvVarType::OneOf(&["trace", "debug", "info", "warn", "error"]), "LogSinkConfig::min_level"),
+    env_var("LOG_SYSLOG_MIN_LEVEL", None, EnvVarType::OneOf(&["trace", "debug", "info", "warn", "error"]), "LogSinkConfig::min_level"),
+    env_var("LOG_JOURNALD_MIN_LEVEL", None, EnvVarType::OneOf(&["trace", "debug", "info", "warn", "error"]), "LogSinkConfig::min_level"),
+    env_var("OTEL_EXPORTER_LOGS", Some("inherit"), EnvVarType::OneOf(&["inherit", "http", "grpc"]), "OTLPLogConfig::transport"),
+    env_var("OTEL_EXPORTER_OTLP_LOGS_ENDPOINT", None, EnvVarType::String, "OTLPLogConfig::endpoint"),
+    env_var("OTEL_EXPORTER_OTLP_LOGS_COMPRESSION", None, EnvVarType::OneOf(&["none", "gzip", "zstd"]), "OTLPLogConfig::compression"),
+    env_var("LOKI_URL", Some("http://localhost:3100"), EnvVarType::String, "LokiConfig::url"),
+    env_var("LOKI_USERNAME", None, EnvVarType::String, "LokiConfig::auth_config"),
+    env_var("LOKI_PASSWORD", None, EnvVarType::String, "LokiConfig::auth_config"),
+    env_var("LOKI_BEARER_TOKEN", None, EnvVarType::String, "LokiConfig::auth_config"),
+    env_var("LOKI_HEADERS", None, EnvVarType::KeyValueList, "LokiConfig::auth_config"),
+    env_var("LOKI_LABELS", None, EnvVarType::KeyValueList, "LokiConfig::labels"),
+    env_var("LOKI_EXTRA_FIELDS", None, EnvVarType::KeyValueList, "LokiConfig::extra_fields"),
+    env_var("LOKI_TENANT_ID", None, EnvVarType::String, "LokiConfig::tenant_id"),
+    env_var("OTEL_LOGRECORD_ATTRIBUTE_COUNT_LIMIT", Some("128"), EnvVarType::Integer, "LogLimitsConfig::max_attributes"),
+    env_var("OTEL_LOGRECORD_ATTRIBUTE_VALUE_LENGTH_LIMIT", None, EnvVarType::Integer, "LogLimitsConfig::max_attribute_value_length"),
+    env_var("OTEL_LOGRECORD_BODY_LENGTH_LIMIT", None, EnvVarType::Integer, "LogLimitsConfig::max_body_length"),
+    env_var("OTEL_BLRP_MAX_QUEUE_SIZE", Some("2048"), EnvVarType::Integer, "LogBatchConfig::max_queue_size"),
+    env_var("OTEL_BLRP_MAX_EXPORT_BATCH_SIZE", Some("512"), EnvVarType::Integer, "LogBatchConfig::max_export_batch_size"),
+    env_var("OTEL_BLRP_SCHEDULE_DELAY", Some("1000"), EnvVarType::Integer, "LogBatchConfig::scheduled_delay"),
+    env_var("AUTH_PROVIDER", Some("unauthenticated"), EnvVarType::List, "AuthConfig"),
+    env_var("AUTH_STATIC_HEADERS", None, EnvVarType::KeyValueList, "AuthConfig::StaticHeaders"),
+    env_var("GOOGLE_PROJECT_ID", None, EnvVarType::String, "GCPAuthConfig::project_id"),
+    env_var("GCP_TOKEN_REFRESH_INTERVAL_SECS", Some("600"), EnvVarType::Integer, "GCPAuthConfig::refresh_interval"),
+    env_var("GCP_TOKEN_EXPIRY_SKEW_SECS", Some("0"), EnvVarType::Integer, "GCPAuthConfig::expiry_skew"),
+    env_var("GCP_TOKEN_SCOPES", None, EnvVarType::List, "GCPAuthConfig::scopes"),
+    env_var("GCP_IMPERSONATE_SERVICE_ACCOUNT", None, EnvVarType::String, "GCPImpersonationConfig::target_principal"),
+    env_var("GCP_IMPERSONATION_DELEGATES", None, EnvVarType::List, "GCPImpersonationConfig::delegates"),
+    env_var("GCP_IMPERSONATION_SCOPES", None, EnvVarType::List, "GCPImpersonationConfig::scopes"),
+    env_var("HMAC_SECRET", None, EnvVarType::String, "HMACAuthConfig::secret"),
+    env_var("HMAC_SIGNATURE_HEADER", Some("x-signature"), EnvVarType::String, "HMACAuthConfig::signature_header"),
+    env_var("HMAC_TIMESTAMP_HEADER", Some("x-signature-timestamp"), EnvVarType::String, "HMACAuthConfig::timestamp_header"),
+    env_var("STS_ENDPOINT", None, EnvVarType::String, "STSAuthConfig::endpoint"),
+    env_var("STS_SUBJECT_TOKEN_FILE", None, EnvVarType::String, "STSAuthConfig::subject_token_source"),
+    env_var("STS_SUBJECT_TOKEN_ENV", None, EnvVarType::String, "STSAuthConfig::subject_token_source"),
+    env_var("STS_SUBJECT_TOKEN_TYPE", Some("urn:ietf:params:oauth:token-type:jwt"), EnvVarType::String, "STSAuthConfig::subject_token_type"),
+    env_var("STS_AUDIENCE", None, EnvVarType::String, "STSAuthConfig::audience"),
+    env_var("STS_SCOPES", None, EnvVarType::List, "STSAuthConfig::scopes"),
+    env_var("STS_TOKEN_EXPIRY_SKEW_SECS", Some("60"), EnvVarType::Integer, "STSAuthConfig::expiry_skew"),
+];
+
+
+/// Returns every environment variable read by the configuration layer.
+///
+/// Meant for deployment tooling, e.g. to validate manifests or generate documentation.
+pub fn env_vars() -> &'static [EnvVar] {
+    ENV_VARS
+}
+
+
+/// Returns the variables of the current environment that look like this crate's variables,
+/// by prefix, but are not read by it, e.g. misspelled names such as `OTEL_EXPORTER_TRACE`.
+///
+/// Variables of the OpenTelemetry specification read by the OpenTelemetry SDK and exporters instead,
+/// e.g. `OTEL_SERVICE_NAME` or `OTEL_BSP_SCHEDULE_DELAY`, are not reported.
+pub fn unknown_env_vars() -> Vec<String> {
+    let mut unknown: Vec<String> = std::env::vars()
+        .map(|(name, _)| name)
+        .filter(|name| ENV_VAR_PREFIXES.iter().any(|prefix| name.starts_with(prefix)))
+        .filter(|name| !ENV_VARS.iter().any(|var| var.name == name))
+        .filter(|name| !SDK_ENV_VARS.contains(&name.as_str()))
+        .collect();
+    unknown.sort();
+    unknown
+}
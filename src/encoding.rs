@@ -0,0 +1,102 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use opentelemetry::{Array, StringValue, Value};
+use opentelemetry::logs::AnyValue;
+use sha2::{Digest, Sha256};
+use crate::config::BinaryAttributePolicy;
+
+
+/// Encodes binary data following the given policy.
+///
+/// # Arguments
+///
+/// * `bytes` - The binary data.
+/// * `policy` - The encoding policy.
+///
+/// # Returns
+///
+/// The encoded value, or `None` if the value must be dropped.
+fn encode_bytes(bytes: &[u8], policy: BinaryAttributePolicy) -> Option<String> {
+    match policy {
+        BinaryAttributePolicy::Keep => Some(String::from_utf8_lossy(bytes).into_owned()),
+        BinaryAttributePolicy::Base64 { max_length } => {
+            let mut encoded = STANDARD.encode(bytes);
+            encoded.truncate(max_length);
+            Some(encoded)
+        },
+        BinaryAttributePolicy::Hash => Some(hex::encode(Sha256::digest(bytes))),
+        BinaryAttributePolicy::Drop => None,
+    }
+}
+
+
+/// Returns whether a string holds binary data.
+///
+/// Span attributes have no binary type, so binary data reaches them as strings. A string is
+/// considered binary if it contains control characters other than whitespace and the escape character
+/// starting ANSI escape codes, e.g. the colors of a captured terminal output, or the replacement
+/// character produced by lossy UTF-8 decoding.
+pub fn is_binary_str(s: &str) -> bool {
+    s.chars().any(|c| c == char::REPLACEMENT_CHARACTER || (c.is_control() && !c.is_whitespace() && c != '\u{1b}'))
+}
+
+
+/// Returns whether a log value contains binary data.
+pub fn any_value_has_binary(value: &AnyValue) -> bool {
+    match value {
+        AnyValue::Bytes(_) => true,
+        AnyValue::ListAny(l) => l.iter().any(any_value_has_binary),
+        AnyValue::Map(m) => m.values().any(any_value_has_binary),
+        _ => false,
+    }
+}
+
+
+/// Applies the binary encoding policy to a log value.
+///
+/// # Returns
+///
+/// The encoded value, or `None` if the value must be dropped.
+pub fn encode_any_value(value: &AnyValue, policy: BinaryAttributePolicy) -> Option<AnyValue> {
+    match value {
+        AnyValue::Bytes(b) if policy != BinaryAttributePolicy::Keep => encode_bytes(b, policy).map(|s| AnyValue::String(s.into())),
+        AnyValue::ListAny(l) => Some(AnyValue::ListAny(Box::new(
+            l.iter().filter_map(|v| encode_any_value(v, policy)).collect(),
+        ))),
+        AnyValue::Map(m) => Some(AnyValue::Map(Box::new(
+            m.iter().filter_map(|(k, v)| encode_any_value(v, policy).map(|v| (k.clone(), v))).collect(),
+        ))),
+        _ => Some(value.clone()),
+    }
+}
+
+
+/// Returns whether a span value contains binary data.
+pub fn value_has_binary(value: &Value) -> bool {
+    match value {
+        Value::String(s) => is_binary_str(s.as_str()),
+        Value::Array(Array::String(l)) => l.iter().any(|s| is_binary_str(s.as_str())),
+        _ => false,
+    }
+}
+
+
+/// Applies the binary encoding policy to a span value.
+///
+/// # Returns
+///
+/// The encoded value, or `None` if the value must be dropped.
+pub fn encode_value(value: &Value, policy: BinaryAttributePolicy) -> Option<Value> {
+    let encode_str = |s: &StringValue| -> Option<StringValue> {
+        if is_binary_str(s.as_str()) {
+            encode_bytes(s.as_str().as_bytes(), policy).map(StringValue::from)
+        } else {
+            Some(s.clone())
+        }
+    };
+    match value {
+        Value::String(s) => encode_str(s).map(Value::String),
+        Value::Array(Array::String(l)) => Some(Value::Array(Array::String(l.iter().filter_map(encode_str).collect()))),
+        _ => Some(value.clone()),
+    }
+}
@@ -0,0 +1,98 @@
+//! # Error Module
+//!
+//! This module provides errors carrying the trace context in which they were created, so errors
+//! reported far from their source still point at the span that produced them.
+use std::error::Error;
+use std::fmt;
+use opentelemetry::trace::{SpanContext, SpanId, TraceContextExt, TraceId};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+
+/// Name of the event attribute holding the trace ID of the span in which an error was created.
+pub const ORIGIN_TRACE_ID_ATTRIBUTE: &str = "error.origin.trace_id";
+
+/// Name of the event attribute holding the ID of the span in which an error was created.
+pub const ORIGIN_SPAN_ID_ATTRIBUTE: &str = "error.origin.span_id";
+
+
+/// An error annotated with the span context that was current when it was created.
+#[derive(Debug)]
+pub struct TracedError<E> {
+    inner: E,
+    span_context: SpanContext,
+}
+
+
+impl<E> TracedError<E> {
+    /// Returns the wrapped error.
+    pub fn inner(&self) -> &E {
+        &self.inner
+    }
+
+    /// Consumes the `TracedError`, returning the wrapped error.
+    pub fn into_inner(self) -> E {
+        self.inner
+    }
+
+    /// Returns the span context in which the error was created.
+    pub fn span_context(&self) -> &SpanContext {
+        &self.span_context
+    }
+
+    /// Returns the trace ID of the span in which the error was created.
+    pub fn trace_id(&self) -> TraceId {
+        self.span_context.trace_id()
+    }
+
+    /// Returns the ID of the span in which the error was created.
+    pub fn span_id(&self) -> SpanId {
+        self.span_context.span_id()
+    }
+}
+
+
+impl<E: fmt::Display> TracedError<E> {
+    /// Logs the error at ERROR level in the current span, with the origin span as event attributes.
+    ///
+    /// The tracer providers of this crate turn these attributes into a link from the reporting span
+    /// to the origin span.
+    pub fn report(&self) {
+        tracing::error!(
+            error.origin.trace_id = %self.trace_id(),
+            error.origin.span_id = %self.span_id(),
+            "{}",
+            self.inner,
+        );
+    }
+}
+
+
+/// Implementation of the Display trait for TracedError, displaying the wrapped error.
+impl<E: fmt::Display> fmt::Display for TracedError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+
+/// Implementation of the Error trait for TracedError, delegating to the wrapped error.
+impl<E: Error> Error for TracedError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.inner.source()
+    }
+}
+
+
+/// Extension trait capturing the current span context into errors.
+pub trait ErrorExt: Sized {
+    /// Wraps the error with the span context of the current `tracing` span.
+    fn in_current_span(self) -> TracedError<Self>;
+}
+
+
+impl<E: Error> ErrorExt for E {
+    fn in_current_span(self) -> TracedError<Self> {
+        let span_context = tracing::Span::current().context().span().span_context().clone();
+        TracedError { inner: self, span_context }
+    }
+}
@@ -4,6 +4,13 @@
 //! It provides a simple way to set up OpenTelemetry with logging and tracing capabilities.
 pub mod otel;
 pub mod config;
-mod tracer;
-mod auth;
-pub mod resource;
\ No newline at end of file
+pub mod tracer;
+pub mod auth;
+pub mod resource;
+pub mod process;
+pub mod error;
+#[cfg(not(feature = "noop"))]
+mod encoding;
+mod self_observability;
+#[cfg(feature = "testing")]
+pub mod testing;
\ No newline at end of file
@@ -0,0 +1,37 @@
+//! # Baggage Module
+//!
+//! This module provides helpers to read and write the W3C Baggage of the current OpenTelemetry context,
+//! so request-scoped metadata such as a tenant ID flows across service boundaries.
+//! The entries are propagated by the Baggage propagator, installed by default.
+use opentelemetry::{Context, ContextGuard, KeyValue};
+use opentelemetry::baggage::BaggageExt;
+
+
+/// Adds an entry to the baggage of the current context.
+///
+/// The entry is visible until the returned guard is dropped. Spans started meanwhile carry it, and it
+/// is recorded as a span attribute if its key is listed in `OpenTelemetryObjectBuilder::with_baggage_span_attributes`.
+///
+/// # Arguments
+///
+/// * `key` - The key of the entry.
+/// * `value` - The value of the entry.
+pub fn set(key: impl Into<String>, value: impl Into<String>) -> ContextGuard {
+    let mut entries: Vec<KeyValue> = Context::current()
+        .baggage()
+        .iter()
+        .map(|(k, (v, _))| KeyValue::new(k.clone(), v.clone()))
+        .collect();
+    entries.push(KeyValue::new(key.into(), value.into()));
+    Context::current_with_baggage(entries).attach()
+}
+
+
+/// Returns the value of an entry of the baggage of the current context, if any.
+///
+/// # Arguments
+///
+/// * `key` - The key of the entry.
+pub fn get(key: &str) -> Option<String> {
+    Context::current().baggage().get(key).map(|value| value.to_string())
+}
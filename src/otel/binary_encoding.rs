@@ -0,0 +1,53 @@
+use opentelemetry::InstrumentationScope;
+use opentelemetry_sdk::error::OTelSdkResult;
+use opentelemetry_sdk::logs::{LogProcessor, SdkLogRecord};
+use crate::config::BinaryAttributePolicy;
+use crate::encoding::{any_value_has_binary, encode_any_value};
+use crate::otel::log_record::LogRecordRewriter;
+
+
+/// A log processor that applies a `BinaryAttributePolicy` to the body and attributes of log records.
+///
+/// It must be registered before the exporting processor, as the changes made to the record
+/// are passed on to the next processor in the chain.
+#[derive(Debug)]
+pub struct BinaryEncodingProcessor {
+    policy: BinaryAttributePolicy,
+    rewriter: LogRecordRewriter,
+}
+
+
+impl BinaryEncodingProcessor {
+    /// Creates a new instance of `BinaryEncodingProcessor`.
+    /// # Arguments
+    /// * `policy` - The encoding policy to apply.
+    /// # Returns
+    /// A new `BinaryEncodingProcessor` instance.
+    pub fn new(policy: BinaryAttributePolicy) -> Self {
+        Self { policy, rewriter: LogRecordRewriter::new() }
+    }
+}
+
+
+/// Implementation of the LogProcessor trait for BinaryEncodingProcessor
+impl LogProcessor for BinaryEncodingProcessor {
+    fn emit(&self, data: &mut SdkLogRecord, _instrumentation: &InstrumentationScope) {
+        if self.policy == BinaryAttributePolicy::Keep {
+            return;
+        }
+        let has_binary = data.body().is_some_and(any_value_has_binary)
+            || data.attributes_iter().any(|(_, v)| any_value_has_binary(v));
+        if !has_binary {
+            return;
+        }
+
+        let body = data.body().and_then(|b| encode_any_value(b, self.policy));
+        let attributes = data.attributes_iter()
+            .filter_map(|(k, v)| encode_any_value(v, self.policy).map(|v| (k.clone(), v)));
+        *data = self.rewriter.rewrite(data, body, attributes);
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        Ok(())
+    }
+}
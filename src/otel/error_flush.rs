@@ -0,0 +1,66 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use opentelemetry_sdk::logs::SdkLoggerProvider;
+use opentelemetry_sdk::trace::SdkTracerProvider as SDKTracerProvider;
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+
+/// A subscriber layer that flushes the trace and log batches when an ERROR event is recorded,
+/// so the telemetry preceding a crash reaches the backend even if the process dies shortly after.
+///
+/// Flushes run on a separate thread, so the thread recording the event is not blocked, and at most
+/// one flush is started per minimum interval.
+pub struct ErrorFlushLayer {
+    tracer_provider: SDKTracerProvider,
+    logger_providers: Vec<SdkLoggerProvider>,
+    min_interval: Duration,
+    last_flush: Mutex<Option<Instant>>,
+}
+
+
+impl ErrorFlushLayer {
+    /// Creates a new instance of `ErrorFlushLayer`.
+    /// # Arguments
+    /// * `tracer_provider` - The tracer provider to flush.
+    /// * `min_interval` - The minimum interval between two flushes.
+    /// # Returns
+    /// A new `ErrorFlushLayer` instance.
+    pub fn new(tracer_provider: SDKTracerProvider, min_interval: Duration) -> Self {
+        Self { tracer_provider, logger_providers: Vec::new(), min_interval, last_flush: Mutex::new(None) }
+    }
+
+    /// Flushes the given logger provider too.
+    pub fn with_logger_provider(mut self, logger_provider: SdkLoggerProvider) -> Self {
+        self.logger_providers.push(logger_provider);
+        self
+    }
+
+    /// Returns whether a flush can start now, recording it if so.
+    fn acquire(&self) -> bool {
+        let mut last_flush = self.last_flush.lock().unwrap_or_else(|e| e.into_inner());
+        if last_flush.is_some_and(|last| last.elapsed() < self.min_interval) {
+            return false;
+        }
+        *last_flush = Some(Instant::now());
+        true
+    }
+}
+
+
+/// Implementation of the Layer trait for ErrorFlushLayer
+impl<S: Subscriber> Layer<S> for ErrorFlushLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        if *event.metadata().level() != Level::ERROR || !self.acquire() {
+            return;
+        }
+        let tracer_provider = self.tracer_provider.clone();
+        let logger_providers = self.logger_providers.clone();
+        std::thread::spawn(move || {
+            let _ = tracer_provider.force_flush();
+            for logger_provider in logger_providers {
+                let _ = logger_provider.force_flush();
+            }
+        });
+    }
+}
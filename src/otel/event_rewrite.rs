@@ -0,0 +1,300 @@
+use std::any::TypeId;
+use std::fmt::Debug;
+use tracing::field::{display, DisplayValue, Field, Value, ValueSet, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::subscriber::Interest;
+use tracing::{Dispatch, Event, Metadata, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+
+/// Upper bound of the number of fields of a rewritten event: the 32 fields allowed by the `tracing` macros,
+/// plus the fields added by the layers rewriting the events.
+const MAX_FIELDS: usize = 64;
+
+
+/// The value of an event field, copied to record it in a rewritten event.
+pub(crate) enum FieldValue {
+    Str(String),
+    Debug(DisplayValue<String>),
+    I64(i64),
+    U64(u64),
+    I128(i128),
+    U128(u128),
+    F64(f64),
+    Bool(bool),
+}
+
+
+impl FieldValue {
+    /// Returns a value recorded as a debug value, i.e. without quotes, like messages.
+    pub(crate) fn debug(text: String) -> Self {
+        FieldValue::Debug(display(text))
+    }
+
+    /// Returns the value, to record it in a rewritten event.
+    fn as_value(&self) -> &dyn Value {
+        match self {
+            FieldValue::Str(value) => value,
+            FieldValue::Debug(value) => value,
+            FieldValue::I64(value) => value,
+            FieldValue::U64(value) => value,
+            FieldValue::I128(value) => value,
+            FieldValue::U128(value) => value,
+            FieldValue::F64(value) => value,
+            FieldValue::Bool(value) => value,
+        }
+    }
+
+    /// Returns the text of a string or debug value.
+    pub(crate) fn text(&self) -> Option<String> {
+        match self {
+            FieldValue::Str(value) => Some(value.clone()),
+            FieldValue::Debug(value) => Some(format!("{:?}", value)),
+            _ => None,
+        }
+    }
+
+    /// Returns the value formatted as text.
+    pub(crate) fn to_text(&self) -> String {
+        match self {
+            FieldValue::I64(value) => value.to_string(),
+            FieldValue::U64(value) => value.to_string(),
+            FieldValue::I128(value) => value.to_string(),
+            FieldValue::U128(value) => value.to_string(),
+            FieldValue::F64(value) => value.to_string(),
+            FieldValue::Bool(value) => value.to_string(),
+            FieldValue::Str(_) | FieldValue::Debug(_) => self.text().unwrap_or_default(),
+        }
+    }
+}
+
+
+/// A visitor copying the fields of an event.
+#[derive(Default)]
+struct FieldCollector {
+    fields: Vec<(&'static str, FieldValue)>,
+}
+
+
+/// Implementation of the Visit trait for FieldCollector
+impl Visit for FieldCollector {
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.fields.push((field.name(), FieldValue::I64(value)));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.fields.push((field.name(), FieldValue::U64(value)));
+    }
+
+    fn record_i128(&mut self, field: &Field, value: i128) {
+        self.fields.push((field.name(), FieldValue::I128(value)));
+    }
+
+    fn record_u128(&mut self, field: &Field, value: u128) {
+        self.fields.push((field.name(), FieldValue::U128(value)));
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.fields.push((field.name(), FieldValue::F64(value)));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.fields.push((field.name(), FieldValue::Bool(value)));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.fields.push((field.name(), FieldValue::Str(value.to_string())));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.fields.push((field.name(), FieldValue::debug(format!("{:?}", value))));
+    }
+}
+
+
+/// Returns the names and values of the fields recorded by a function, e.g. `Event::record`.
+fn collect(record: impl FnOnce(&mut dyn Visit)) -> Vec<(&'static str, FieldValue)> {
+    let mut collector = FieldCollector::default();
+    record(&mut collector);
+    collector.fields
+}
+
+
+/// Returns the names and values of the fields of an event.
+pub(crate) fn collect_fields(event: &Event<'_>) -> Vec<(&'static str, FieldValue)> {
+    collect(|visitor| event.record(visitor))
+}
+
+
+/// Builds a value set of the given metadata with the given field values, and passes it to the given function.
+///
+/// Fields missing from the metadata are skipped. The metadata must have at least one field.
+fn with_value_set<T>(metadata: &'static Metadata<'static>, fields: &[(&'static str, FieldValue)], f: impl FnOnce(&ValueSet<'_>) -> T) -> T {
+    let field_set = metadata.fields();
+    let padding = field_set.iter().next().expect("metadata without fields");
+    let resolved: Vec<(Field, &FieldValue)> = fields.iter()
+        .filter_map(|(name, value)| Some((field_set.field(name)?, value)))
+        .collect();
+    // A value set is built from a fixed-size array, so the unused entries repeat a field without value.
+    let mut values: [(&Field, Option<&dyn Value>); MAX_FIELDS] = [(&padding, None); MAX_FIELDS];
+    for (entry, (field, value)) in values.iter_mut().zip(&resolved) {
+        *entry = (field, Some(value.as_value()));
+    }
+    f(&field_set.value_set(&values))
+}
+
+
+/// Builds an event with the given metadata and field values, with the parent of the original event,
+/// and passes it to the given function.
+///
+/// Fields missing from the metadata are skipped. If the metadata has no fields, the original event is passed.
+fn with_rewritten_event(event: &Event<'_>, metadata: &'static Metadata<'static>, fields: &[(&'static str, FieldValue)], f: impl FnOnce(&Event<'_>)) {
+    if metadata.fields().is_empty() {
+        return f(event);
+    }
+    with_value_set(metadata, fields, |value_set| {
+        let rewritten = if event.is_contextual() {
+            Event::new(metadata, value_set)
+        } else {
+            Event::new_child_of(event.parent().cloned(), metadata, value_set)
+        };
+        f(&rewritten)
+    })
+}
+
+
+/// A rewrite of the events passed to the layer wrapped by a `RewriteLayer`.
+pub(crate) trait RewriteEvent {
+    /// Returns the metadata and field values of the rewritten event, or `None` to pass the event unchanged.
+    /// # Arguments
+    /// * `event` - The original event.
+    /// * `ctx` - The context of the subscriber.
+    fn rewrite<S>(&self, event: &Event<'_>, ctx: &Context<'_, S>) -> Option<(&'static Metadata<'static>, Vec<(&'static str, FieldValue)>)>
+    where
+        S: Subscriber + for<'span> LookupSpan<'span>;
+
+    /// Returns the rewritten field values of a new span or of the values recorded on a span, or `None` to pass
+    /// them unchanged. By default, the span fields are passed unchanged.
+    /// # Arguments
+    /// * `fields` - The names and values of the fields.
+    fn rewrite_span_fields(&self, _fields: Vec<(&'static str, FieldValue)>) -> Option<Vec<(&'static str, FieldValue)>> {
+        None
+    }
+}
+
+
+/// A subscriber layer that rewrites the events before passing them to the wrapped layer, e.g. to redact
+/// or add fields, passing everything else through.
+pub struct RewriteLayer<L, R> {
+    inner: L,
+    rewrite: R,
+}
+
+
+impl<L, R> RewriteLayer<L, R> {
+    /// Creates a new instance of `RewriteLayer`.
+    /// # Arguments
+    /// * `inner` - The layer receiving the rewritten events.
+    /// * `rewrite` - The rewrite of the events.
+    /// # Returns
+    /// A new `RewriteLayer` instance.
+    pub(crate) fn new(inner: L, rewrite: R) -> Self {
+        Self { inner, rewrite }
+    }
+}
+
+
+/// Implementation of the Layer trait for RewriteLayer
+impl<S, L, R> Layer<S> for RewriteLayer<L, R>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+    L: Layer<S> + 'static,
+    R: RewriteEvent + Send + Sync + 'static,
+{
+    fn on_register_dispatch(&self, subscriber: &Dispatch) {
+        self.inner.on_register_dispatch(subscriber)
+    }
+
+    fn on_layer(&mut self, subscriber: &mut S) {
+        self.inner.on_layer(subscriber)
+    }
+
+    fn register_callsite(&self, metadata: &'static Metadata<'static>) -> Interest {
+        self.inner.register_callsite(metadata)
+    }
+
+    fn enabled(&self, metadata: &Metadata<'_>, ctx: Context<'_, S>) -> bool {
+        self.inner.enabled(metadata, ctx)
+    }
+
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let metadata = attrs.metadata();
+        let rewritten = match metadata.fields().is_empty() {
+            true => None,
+            false => self.rewrite.rewrite_span_fields(collect(|visitor| attrs.record(visitor))),
+        };
+        let Some(fields) = rewritten else {
+            return self.inner.on_new_span(attrs, id, ctx);
+        };
+        with_value_set(metadata, &fields, |value_set| {
+            let rewritten = match attrs.parent() {
+                Some(parent) => Attributes::child_of(parent.clone(), metadata, value_set),
+                None if attrs.is_root() => Attributes::new_root(metadata, value_set),
+                None => Attributes::new(metadata, value_set),
+            };
+            self.inner.on_new_span(&rewritten, id, ctx)
+        })
+    }
+
+    fn on_record(&self, span: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let rewritten = match ctx.metadata(span) {
+            Some(metadata) if !metadata.fields().is_empty() => self.rewrite.rewrite_span_fields(collect(|visitor| values.record(visitor)))
+                .map(|fields| (metadata, fields)),
+            _ => None,
+        };
+        let Some((metadata, fields)) = rewritten else {
+            return self.inner.on_record(span, values, ctx);
+        };
+        with_value_set(metadata, &fields, |value_set| self.inner.on_record(span, &Record::new(value_set), ctx))
+    }
+
+    fn on_follows_from(&self, span: &Id, follows: &Id, ctx: Context<'_, S>) {
+        self.inner.on_follows_from(span, follows, ctx)
+    }
+
+    fn event_enabled(&self, event: &Event<'_>, ctx: Context<'_, S>) -> bool {
+        self.inner.event_enabled(event, ctx)
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        match self.rewrite.rewrite(event, &ctx) {
+            Some((metadata, fields)) => with_rewritten_event(event, metadata, &fields, |rewritten| self.inner.on_event(rewritten, ctx)),
+            None => self.inner.on_event(event, ctx),
+        }
+    }
+
+    fn on_enter(&self, id: &Id, ctx: Context<'_, S>) {
+        self.inner.on_enter(id, ctx)
+    }
+
+    fn on_exit(&self, id: &Id, ctx: Context<'_, S>) {
+        self.inner.on_exit(id, ctx)
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        self.inner.on_close(id, ctx)
+    }
+
+    fn on_id_change(&self, old: &Id, new: &Id, ctx: Context<'_, S>) {
+        self.inner.on_id_change(old, new, ctx)
+    }
+
+    unsafe fn downcast_raw(&self, id: TypeId) -> Option<*const ()> {
+        if id == TypeId::of::<Self>() {
+            return Some(self as *const Self as *const ());
+        }
+        // SAFETY: the pointer returned by the wrapped layer is valid for the type it was requested for.
+        unsafe { self.inner.downcast_raw(id) }
+    }
+}
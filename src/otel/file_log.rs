@@ -0,0 +1,110 @@
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use anyhow::Result;
+use tracing_appender::non_blocking::{NonBlocking, NonBlockingBuilder, WorkerGuard};
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use crate::config::{FileLogConfig, LogBufferConfig, LogBufferPolicy, LogRotation};
+
+
+/// A writer appending to a local file, renamed to `<path>.<epoch millis>` once it would exceed a maximum size.
+///
+/// A new file is created at the configured path after each rotation, so an agent tailing the path follows the rotations.
+#[derive(Debug)]
+struct SizeRollingWriter {
+    path: PathBuf,
+    max_size: u64,
+    file: File,
+    size: u64,
+}
+
+
+/// Opens the file at the given path for appending, returning it with its current size.
+fn open(path: &Path) -> std::io::Result<(File, u64)> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    let size = file.metadata()?.len();
+    Ok((file, size))
+}
+
+
+impl SizeRollingWriter {
+    /// Creates a new `SizeRollingWriter`, opening the file at the given path.
+    fn new(path: PathBuf, max_size: u64) -> std::io::Result<Self> {
+        let (file, size) = open(&path)?;
+        Ok(Self { path, max_size, file, size })
+    }
+
+    /// Renames the current file and opens a new one.
+    fn rotate(&mut self) -> std::io::Result<()> {
+        self.file.flush()?;
+        let millis = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis()).unwrap_or_default();
+        let mut rotated = self.path.clone().into_os_string();
+        rotated.push(format!(".{}", millis));
+        std::fs::rename(&self.path, rotated)?;
+        (self.file, self.size) = open(&self.path)?;
+        Ok(())
+    }
+}
+
+
+/// Implementation of the Write trait for SizeRollingWriter
+impl Write for SizeRollingWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.size > 0 && self.size + buf.len() as u64 > self.max_size {
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+}
+
+
+/// Returns a non-blocking writer, buffering the lines for a background thread writing them to the given writer,
+/// with the guard flushing it when dropped.
+/// # Arguments
+/// * `writer` - The writer of the background thread.
+/// * `config` - The buffering configuration.
+/// # Returns
+/// The non-blocking writer and its guard.
+pub(crate) fn non_blocking<W: Write + Send + 'static>(writer: W, config: &LogBufferConfig) -> (NonBlocking, WorkerGuard) {
+    NonBlockingBuilder::default()
+        .buffered_lines_limit(config.buffered_lines)
+        .lossy(config.policy == LogBufferPolicy::Drop)
+        .finish(writer)
+}
+
+
+/// Returns the non-blocking writer of the file log sink, with the guard flushing it when dropped.
+///
+/// With daily and hourly rotation, the files are named after the configured path with the date as suffix.
+/// With size rotation, the configured path is written and renamed to `<path>.<epoch millis>` once full.
+/// # Arguments
+/// * `config` - The file log configuration.
+/// # Returns
+/// The non-blocking writer and its guard, or an error if the file cannot be opened.
+pub(crate) fn file_writer(config: &FileLogConfig) -> Result<(NonBlocking, WorkerGuard)> {
+    let rotation = match config.rotation {
+        LogRotation::Daily => Rotation::DAILY,
+        LogRotation::Hourly => Rotation::HOURLY,
+        LogRotation::Size(max_size) => {
+            let writer = SizeRollingWriter::new(config.path.clone(), max_size)?;
+            return Ok(non_blocking(writer, &config.buffer));
+        },
+    };
+    let directory = match config.path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let prefix = config.path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    let appender = RollingFileAppender::builder()
+        .rotation(rotation)
+        .filename_prefix(prefix)
+        .build(directory)?;
+    Ok(non_blocking(appender, &config.buffer))
+}
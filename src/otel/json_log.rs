@@ -0,0 +1,196 @@
+use chrono::{SecondsFormat, Utc};
+use serde_json::{json, Map, Value};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields};
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::registry::LookupSpan;
+use crate::otel::event_rewrite::{collect_fields, FieldValue};
+
+
+/// Version of the Elastic Common Schema of the ECS log lines.
+const ECS_VERSION: &str = "8.11.0";
+
+
+/// Cloud Logging field correlating a log line with its trace.
+const GCP_TRACE: &str = "logging.googleapis.com/trace";
+
+
+/// Prefix of the event fields embedded as CloudWatch metrics, e.g. `metric.latency_ms`.
+const METRIC_PREFIX: &str = "metric.";
+
+
+/// The field naming schemes of the JSON log lines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum JsonLogSchema {
+    /// The Elastic Common Schema, ingested by Elasticsearch and Filebeat without a custom processor.
+    Ecs,
+    /// The special fields of Google Cloud Logging, with the project of the traces.
+    Gcp { project_id: String },
+    /// The field names of CloudWatch Logs Insights queries, with the namespace of the embedded metrics, if enabled.
+    CloudWatch { emf_namespace: Option<String> },
+}
+
+
+/// An event formatter writing one JSON object per line, with the fields named after a schema
+/// understood natively by a log backend.
+///
+/// The `trace_id` and `span_id` fields added to the events recorded inside a span are renamed
+/// after the trace correlation fields of the schema.
+pub(crate) struct JsonLogFormat {
+    schema: JsonLogSchema,
+    service_name: String,
+}
+
+
+impl JsonLogFormat {
+    /// Creates a new instance of `JsonLogFormat`.
+    /// # Arguments
+    /// * `schema` - The field naming scheme.
+    /// * `service_name` - The name of the service, added to every line.
+    /// # Returns
+    /// A new `JsonLogFormat` instance.
+    pub(crate) fn new(schema: JsonLogSchema, service_name: &str) -> Self {
+        Self { schema, service_name: service_name.to_string() }
+    }
+
+    /// Returns the name and JSON value of an event field in the schema.
+    fn field(&self, name: &'static str, value: FieldValue) -> (&'static str, Value) {
+        match (&self.schema, name) {
+            (JsonLogSchema::Ecs, "trace_id") => ("trace.id", json_value(value)),
+            (JsonLogSchema::Ecs, "span_id") => ("span.id", json_value(value)),
+            (JsonLogSchema::Gcp { project_id }, "trace_id") =>
+                (GCP_TRACE, format!("projects/{}/traces/{}", project_id, value.to_text()).into()),
+            (JsonLogSchema::Gcp { .. }, "span_id") => ("logging.googleapis.com/spanId", json_value(value)),
+            (JsonLogSchema::CloudWatch { .. }, "trace_id") => ("xray_trace_id", xray_trace_id(&value.to_text()).into()),
+            (_, name) => (name, json_value(value)),
+        }
+    }
+
+    /// Returns the name of the CloudWatch metric of a numeric event field named `metric.<name>`,
+    /// if the metrics are embedded.
+    fn metric_name(&self, name: &'static str, value: &Value) -> Option<&'static str> {
+        match &self.schema {
+            JsonLogSchema::CloudWatch { emf_namespace: Some(_) } if value.is_number() => name.strip_prefix(METRIC_PREFIX),
+            _ => None,
+        }
+    }
+
+    /// Returns the `_aws` metadata of the CloudWatch Embedded Metric Format, declaring the metrics of an event
+    /// with the service as dimension, or `None` if the event has no metric or the metrics are not embedded.
+    fn emf_metadata(&self, metrics: &[&str]) -> Option<Value> {
+        let JsonLogSchema::CloudWatch { emf_namespace: Some(namespace) } = &self.schema else {
+            return None;
+        };
+        if metrics.is_empty() {
+            return None;
+        }
+        Some(json!({
+            "Timestamp": Utc::now().timestamp_millis(),
+            "CloudWatchMetrics": [{
+                "Namespace": namespace,
+                "Dimensions": [["service"]],
+                "Metrics": metrics.iter().map(|name| json!({ "Name": name })).collect::<Vec<_>>(),
+            }],
+        }))
+    }
+
+    /// Returns the fields of the schema describing an event, before the fields of the event.
+    fn event_fields(&self, event: &Event<'_>) -> Map<String, Value> {
+        let metadata = event.metadata();
+        let mut object = Map::new();
+        match self.schema {
+            JsonLogSchema::Ecs => {
+                object.insert("@timestamp".to_string(), Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true).into());
+                object.insert("log.level".to_string(), metadata.level().as_str().into());
+                object.insert("log.logger".to_string(), metadata.target().into());
+                if let Some(file) = metadata.file() {
+                    object.insert("log.origin.file.name".to_string(), file.into());
+                }
+                if let Some(line) = metadata.line() {
+                    object.insert("log.origin.file.line".to_string(), line.into());
+                }
+                object.insert("service.name".to_string(), self.service_name.clone().into());
+                object.insert("ecs.version".to_string(), ECS_VERSION.into());
+            },
+            JsonLogSchema::Gcp { .. } => {
+                object.insert("time".to_string(), Utc::now().to_rfc3339_opts(SecondsFormat::Nanos, true).into());
+                object.insert("severity".to_string(), gcp_severity(metadata.level()).into());
+                object.insert("logging.googleapis.com/sourceLocation".to_string(), json!({
+                    "file": metadata.file(),
+                    "line": metadata.line().map(|line| line.to_string()),
+                    "function": metadata.target(),
+                }));
+                object.insert("serviceContext".to_string(), json!({ "service": self.service_name }));
+            },
+            JsonLogSchema::CloudWatch { .. } => {
+                object.insert("timestamp".to_string(), Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true).into());
+                object.insert("level".to_string(), metadata.level().as_str().into());
+                object.insert("logger".to_string(), metadata.target().into());
+                if let (Some(file), Some(line)) = (metadata.file(), metadata.line()) {
+                    object.insert("location".to_string(), format!("{}:{}", file, line).into());
+                }
+                object.insert("service".to_string(), self.service_name.clone().into());
+            },
+        }
+        object
+    }
+}
+
+
+/// Returns the Cloud Logging severity of a level. TRACE events are reported as DEBUG.
+fn gcp_severity(level: &Level) -> &'static str {
+    match *level {
+        Level::TRACE | Level::DEBUG => "DEBUG",
+        Level::INFO => "INFO",
+        Level::WARN => "WARNING",
+        _ => "ERROR",
+    }
+}
+
+
+/// Returns a trace ID in the X-Ray format, `1-<8 hex digits>-<24 hex digits>`, so CloudWatch correlates
+/// the log lines with the X-Ray traces. Other IDs are returned unchanged.
+fn xray_trace_id(trace_id: &str) -> String {
+    if trace_id.len() != 32 || !trace_id.is_ascii() {
+        return trace_id.to_string();
+    }
+    format!("1-{}-{}", &trace_id[..8], &trace_id[8..])
+}
+
+
+/// Returns the JSON value of an event field. 128-bit integers are written as strings.
+fn json_value(value: FieldValue) -> Value {
+    match value {
+        FieldValue::I64(value) => value.into(),
+        FieldValue::U64(value) => value.into(),
+        FieldValue::F64(value) => value.into(),
+        FieldValue::Bool(value) => value.into(),
+        value => value.to_text().into(),
+    }
+}
+
+
+/// Implementation of the FormatEvent trait for JsonLogFormat
+impl<S, N> FormatEvent<S, N> for JsonLogFormat
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+    N: for<'writer> FormatFields<'writer> + 'static,
+{
+    fn format_event(&self, _ctx: &FmtContext<'_, S, N>, mut writer: Writer<'_>, event: &Event<'_>) -> std::fmt::Result {
+        let mut object = self.event_fields(event);
+        let mut metrics = Vec::new();
+        for (name, value) in collect_fields(event) {
+            let (mut name, value) = self.field(name, value);
+            if let Some(metric) = self.metric_name(name, &value) {
+                metrics.push(metric);
+                name = metric;
+            }
+            object.insert(name.to_string(), value);
+        }
+        if let Some(metadata) = self.emf_metadata(&metrics) {
+            object.insert("_aws".to_string(), metadata);
+        }
+        let line = serde_json::to_string(&object).map_err(|_| std::fmt::Error)?;
+        writeln!(writer, "{}", line)
+    }
+}
@@ -0,0 +1,100 @@
+use opentelemetry::InstrumentationScope;
+use opentelemetry::logs::AnyValue;
+use opentelemetry_sdk::error::OTelSdkResult;
+use opentelemetry_sdk::logs::{LogProcessor, SdkLogRecord};
+use crate::config::LogLimitsConfig;
+use crate::otel::log_record::LogRecordRewriter;
+
+
+/// A log processor that enforces attribute count and value length limits on log records.
+///
+/// It must be registered before the exporting processor, as the changes made to the record
+/// are passed on to the next processor in the chain.
+#[derive(Debug)]
+pub struct LogLimitsProcessor {
+    limits: LogLimitsConfig,
+    rewriter: LogRecordRewriter,
+}
+
+
+/// Truncates a value to the given length.
+///
+/// Strings are truncated by characters and byte arrays by bytes. Lists and maps are truncated recursively.
+fn truncate_value(value: &AnyValue, max_length: usize) -> AnyValue {
+    match value {
+        AnyValue::String(s) if s.as_str().chars().count() > max_length => {
+            AnyValue::String(s.as_str().chars().take(max_length).collect::<String>().into())
+        },
+        AnyValue::Bytes(b) if b.len() > max_length => AnyValue::Bytes(Box::new(b[..max_length].to_vec())),
+        AnyValue::ListAny(l) => AnyValue::ListAny(Box::new(l.iter().map(|v| truncate_value(v, max_length)).collect())),
+        AnyValue::Map(m) => AnyValue::Map(Box::new(m.iter().map(|(k, v)| (k.clone(), truncate_value(v, max_length))).collect())),
+        _ => value.clone(),
+    }
+}
+
+
+/// Returns whether a value exceeds the given length.
+fn value_exceeds(value: &AnyValue, max_length: usize) -> bool {
+    match value {
+        AnyValue::String(s) => s.as_str().chars().count() > max_length,
+        AnyValue::Bytes(b) => b.len() > max_length,
+        AnyValue::ListAny(l) => l.iter().any(|v| value_exceeds(v, max_length)),
+        AnyValue::Map(m) => m.values().any(|v| value_exceeds(v, max_length)),
+        _ => false,
+    }
+}
+
+
+impl LogLimitsProcessor {
+    /// Creates a new instance of `LogLimitsProcessor`.
+    /// # Arguments
+    /// * `limits` - The limits to enforce.
+    /// # Returns
+    /// A new `LogLimitsProcessor` instance.
+    pub fn new(limits: LogLimitsConfig) -> Self {
+        Self { limits, rewriter: LogRecordRewriter::new() }
+    }
+
+    /// Returns whether the record exceeds any of the limits.
+    fn exceeds_limits(&self, record: &SdkLogRecord) -> bool {
+        let too_many_attributes = self.limits.max_attributes
+            .is_some_and(|max| record.attributes_iter().count() > max);
+        let attribute_too_long = self.limits.max_attribute_value_length
+            .is_some_and(|max| record.attributes_iter().any(|(_, v)| value_exceeds(v, max)));
+        let body_too_long = self.limits.max_body_length
+            .is_some_and(|max| record.body().is_some_and(|b| value_exceeds(b, max)));
+        too_many_attributes || attribute_too_long || body_too_long
+    }
+
+    /// Returns a copy of the record with the limits applied.
+    fn limit(&self, record: &SdkLogRecord) -> SdkLogRecord {
+        let body = record.body().map(|body| match self.limits.max_body_length {
+            Some(max) => truncate_value(body, max),
+            None => body.clone(),
+        });
+
+        let max_attributes = self.limits.max_attributes.unwrap_or(usize::MAX);
+        let attributes = record.attributes_iter().take(max_attributes).map(|(key, value)| {
+            let value = match self.limits.max_attribute_value_length {
+                Some(max) => truncate_value(value, max),
+                None => value.clone(),
+            };
+            (key.clone(), value)
+        });
+        self.rewriter.rewrite(record, body, attributes)
+    }
+}
+
+
+/// Implementation of the LogProcessor trait for LogLimitsProcessor
+impl LogProcessor for LogLimitsProcessor {
+    fn emit(&self, data: &mut SdkLogRecord, _instrumentation: &InstrumentationScope) {
+        if self.exceeds_limits(data) {
+            *data = self.limit(data);
+        }
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        Ok(())
+    }
+}
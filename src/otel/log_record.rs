@@ -0,0 +1,59 @@
+use opentelemetry::Key;
+use opentelemetry::logs::{AnyValue, LogRecord, Logger, LoggerProvider};
+use opentelemetry_sdk::logs::{SdkLogRecord, SdkLogger, SdkLoggerProvider};
+
+
+/// Creates rewritten copies of log records.
+///
+/// `SdkLogRecord` has no public constructor and attributes cannot be removed from an existing
+/// record, so log processors that rewrite records build a fresh copy through a detached logger.
+#[derive(Debug)]
+pub struct LogRecordRewriter {
+    record_factory: SdkLogger,
+}
+
+
+impl LogRecordRewriter {
+    /// Creates a new instance of `LogRecordRewriter`.
+    pub fn new() -> Self {
+        let record_factory = SdkLoggerProvider::builder().build().logger("log-record-rewriter");
+        Self { record_factory }
+    }
+
+    /// Returns a copy of the record with the given body and attributes.
+    /// # Arguments
+    /// * `record` - The original log record.
+    /// * `body` - The body of the new record.
+    /// * `attributes` - The attributes of the new record.
+    /// # Returns
+    /// A new `SdkLogRecord` with every other field copied from `record`.
+    pub fn rewrite(&self, record: &SdkLogRecord, body: Option<AnyValue>, attributes: impl IntoIterator<Item = (Key, AnyValue)>) -> SdkLogRecord {
+        let mut rewritten = self.record_factory.create_log_record();
+        if let Some(name) = record.event_name() {
+            rewritten.set_event_name(name);
+        }
+        if let Some(target) = record.target() {
+            rewritten.set_target(target.clone());
+        }
+        if let Some(timestamp) = record.timestamp() {
+            rewritten.set_timestamp(timestamp);
+        }
+        if let Some(timestamp) = record.observed_timestamp() {
+            rewritten.set_observed_timestamp(timestamp);
+        }
+        if let Some(ctx) = record.trace_context() {
+            rewritten.set_trace_context(ctx.trace_id, ctx.span_id, ctx.trace_flags);
+        }
+        if let Some(text) = record.severity_text() {
+            rewritten.set_severity_text(text);
+        }
+        if let Some(number) = record.severity_number() {
+            rewritten.set_severity_number(number);
+        }
+        if let Some(body) = body {
+            rewritten.set_body(body);
+        }
+        rewritten.add_attributes(attributes);
+        rewritten
+    }
+}
@@ -0,0 +1,74 @@
+use tracing::{Event, Metadata, Subscriber};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+use crate::config::RedactionAction;
+use crate::otel::event_rewrite::{collect_fields, FieldValue, RewriteEvent};
+use crate::tracer::redaction::Redactor;
+
+
+/// The redaction of the fields of the events before they reach the log sinks, so personal data and secrets
+/// do not reach the log backends.
+///
+/// Fields of the events and spans whose name matches a key of the redaction configuration are dropped or hashed,
+/// and the parts of the messages and textual field values matching a value pattern are replaced, so the span fields
+/// formatted with the events are redacted too.
+pub(crate) struct LogRedaction {
+    redactor: Redactor,
+}
+
+
+impl LogRedaction {
+    /// Creates a new instance of `LogRedaction`.
+    /// # Arguments
+    /// * `redactor` - The compiled redaction rules.
+    /// # Returns
+    /// A new `LogRedaction` instance.
+    pub(crate) fn new(redactor: Redactor) -> Self {
+        Self { redactor }
+    }
+
+    /// Redacts the value of a field, returning `None` if the field is dropped and whether it changed.
+    fn redact(&self, name: &str, value: FieldValue) -> (Option<FieldValue>, bool) {
+        if self.redactor.matches_key(name) {
+            return match self.redactor.action() {
+                RedactionAction::Drop => (None, true),
+                RedactionAction::Hash => (Some(FieldValue::Str(self.redactor.hash(&value.to_text()))), true),
+            };
+        }
+        let redacted = value.text().and_then(|text| self.redactor.redact_text(&text));
+        match (redacted, value) {
+            (Some(redacted), FieldValue::Str(_)) => (Some(FieldValue::Str(redacted)), true),
+            (Some(redacted), _) => (Some(FieldValue::debug(redacted)), true),
+            (None, value) => (Some(value), false),
+        }
+    }
+
+    /// Redacts a list of fields, returning `None` if none changed.
+    fn redact_fields(&self, fields: Vec<(&'static str, FieldValue)>) -> Option<Vec<(&'static str, FieldValue)>> {
+        let mut changed = false;
+        let fields = fields
+            .into_iter()
+            .filter_map(|(name, value)| {
+                let (value, redacted) = self.redact(name, value);
+                changed |= redacted;
+                Some((name, value?))
+            })
+            .collect();
+        changed.then_some(fields)
+    }
+}
+
+
+/// Implementation of the RewriteEvent trait for LogRedaction
+impl RewriteEvent for LogRedaction {
+    fn rewrite<S>(&self, event: &Event<'_>, _ctx: &Context<'_, S>) -> Option<(&'static Metadata<'static>, Vec<(&'static str, FieldValue)>)>
+    where
+        S: Subscriber + for<'span> LookupSpan<'span>,
+    {
+        self.redact_fields(collect_fields(event)).map(|fields| (event.metadata(), fields))
+    }
+
+    fn rewrite_span_fields(&self, fields: Vec<(&'static str, FieldValue)>) -> Option<Vec<(&'static str, FieldValue)>> {
+        self.redact_fields(fields)
+    }
+}
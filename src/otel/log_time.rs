@@ -0,0 +1,33 @@
+use std::time::SystemTime;
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::time::FormatTime;
+use crate::config::{TimestampFormat, Timezone};
+use crate::tracer::stdout::format_timestamp;
+
+
+/// A timer writing the timestamps of the log lines in the configured format and timezone.
+pub(crate) struct LogTimer {
+    format: TimestampFormat,
+    timezone: Timezone,
+}
+
+
+impl LogTimer {
+    /// Creates a new instance of `LogTimer`.
+    /// # Arguments
+    /// * `format` - The format of the timestamps.
+    /// * `timezone` - The timezone of the timestamps.
+    /// # Returns
+    /// A new `LogTimer` instance.
+    pub(crate) fn new(format: TimestampFormat, timezone: Timezone) -> Self {
+        Self { format, timezone }
+    }
+}
+
+
+/// Implementation of the FormatTime trait for LogTimer
+impl FormatTime for LogTimer {
+    fn format_time(&self, w: &mut Writer<'_>) -> std::fmt::Result {
+        w.write_str(&format_timestamp(SystemTime::now(), self.format, self.timezone))
+    }
+}
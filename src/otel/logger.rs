@@ -1,34 +1,179 @@
-use anyhow::Result;
+use std::sync::Arc;
+use anyhow::{anyhow, Result};
+use opentelemetry_http::HttpClient;
 use opentelemetry_sdk::trace::Tracer;
-use opentelemetry_sdk::logs::SdkLoggerProvider;
+use opentelemetry_sdk::logs::{BatchConfigBuilder, BatchLogProcessor, SdkLoggerProvider};
 use opentelemetry_appender_tracing::layer::OpenTelemetryTracingBridge;
-use opentelemetry_otlp::LogExporter;
-use tracing_loki::BackgroundTask;
+use opentelemetry_otlp::{LogExporter, WithExportConfig, WithHttpConfig, WithTonicConfig};
+use tracing::{Level, Subscriber};
+use tracing_log::{AsLog, LogTracer};
+use tracing_loki::{BackgroundTask, BackgroundTaskController};
+use tracing_opentelemetry::OpenTelemetryLayer;
 use tracing_loki::url::Url;
 use tracing_subscriber::{fmt, EnvFilter};
-use tracing_subscriber::layer::SubscriberExt;
-use tracing_subscriber::util::SubscriberInitExt;
-use crate::config::{LogConfig, LokiConfig};
-use crate::otel::resource::get_resource;
+use tracing_subscriber::Layer;
+use tracing_subscriber::filter::{FilterExt, LevelFilter};
+use tracing_subscriber::fmt::{FormatFields, MakeWriter};
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
+use tracing_subscriber::fmt::format::{DefaultFields, Format, Full};
+use tracing_subscriber::fmt::time::FormatTime;
+use tracing_subscriber::layer::{Filter, Identity, SubscriberExt};
+use tracing_subscriber::registry::{LookupSpan, Registry};
+use crate::auth::AuthContext;
+use tracing_appender::non_blocking::{NonBlocking, WorkerGuard};
+use crate::config::{http_logs_endpoint, AuthConfig, ExportCompression, ExportPolicyConfig, FileLogConfig, CloudLogFormatConfig, GrpcChannelConfig, LogBatchConfig, LogConfig, LogLineFields, LogTransport, LokiConfig, OTLPLogConfig, RedactionConfig, StdoutLogConfig, StdoutLogFormat, TraceConfig};
+use crate::otel::log_limits::LogLimitsProcessor;
+use crate::otel::binary_encoding::BinaryEncodingProcessor;
+use crate::otel::error_flush::ErrorFlushLayer;
+use crate::otel::rate_limit::RateLimitLayer;
+use crate::otel::span_log::SpanLogLayer;
+use crate::otel::event_rewrite::RewriteLayer;
+use crate::otel::log_redaction::LogRedaction;
+use crate::otel::trace_context::TraceContext;
+use crate::tracer::redaction::Redactor;
+use crate::otel::file_log::{file_writer, non_blocking};
+use crate::otel::json_log::{JsonLogFormat, JsonLogSchema};
+use crate::otel::log_time::LogTimer;
+use crate::otel::syslog_log::{syslog_writer, SyslogMakeWriter};
+use crate::otel::shutdown::LogHandles;
+use crate::resource::get_resource;
+use crate::tracer::{http_protocol, otlp_compression};
+use crate::tracer::grpc::{get_grpc_channel, get_insecure_grpc_channel};
+use crate::tracer::reqwest::ReqwestTracerClient;
+
+
+/// Default endpoint of the gRPC log exporter.
+const DEFAULT_GRPC_ENDPOINT: &str = "http://localhost:4317";
+
 
 /// Enum representing the possible log layers.
+#[allow(clippy::large_enum_variant, clippy::upper_case_acronyms)]
 pub enum LogLayer {
     /// Loki log layer.
-    Loki(tracing_loki::Layer, BackgroundTask),
+    Loki(tracing_loki::Layer, BackgroundTaskController, BackgroundTask),
     /// OTLP log layer.
-    OTLP,
-    /// Standard output log layer.
-    Stdout,
+    OTLP(OTLPLogConfig, LogExporter),
+    /// Standard output log layer, with its configuration and, if buffered, its non-blocking writer and the guard flushing it.
+    Stdout(StdoutLogConfig, Option<(NonBlocking, WorkerGuard)>),
+    /// File log layer, with its non-blocking writer, the guard flushing it and its configuration.
+    File(NonBlocking, WorkerGuard, FileLogConfig),
+    /// Syslog log layer, with the writer queuing each event for the syslog server and the guard sending the queued events.
+    Syslog(SyslogMakeWriter, WorkerGuard),
+    /// systemd-journald log layer.
+    #[cfg(feature = "journald")]
+    Journald(tracing_journald::Layer),
+    /// Several log layers, each with its minimum level and its level filter with the `RUST_LOG` syntax, if any.
+    Multi(Vec<(LogLayer, Option<Level>, Option<String>)>),
+}
+
+
+/// The `RUST_LOG` filter, built once and shared by the layers filtered by it.
+type SharedFilter = Arc<dyn Filter<Registry> + Send + Sync>;
+
+/// Returns whether the headers of an authentication configuration never change, so they can be retrieved once.
+fn has_static_headers(config: &AuthConfig) -> bool {
+    match config {
+        AuthConfig::StaticHeaders(_) | AuthConfig::Unauthenticated => true,
+        AuthConfig::Chain(configs) => configs.iter().all(has_static_headers),
+        AuthConfig::GCPAuth(_) | AuthConfig::HMACAuth(_) | AuthConfig::STSAuth(_) => false,
+    }
 }
 
-/// Initializes the Loki log provider.
-fn init_loki_log_provider(config: &LokiConfig, service_name: &String) -> Result<LogLayer> {
-    let (layer, task) = tracing_loki::layer(
-        Url::parse(config.url.as_str())?,
-        [("service".into(), service_name.into())].into_iter().collect(),
-        [].into_iter().collect(),
-    )?;
-    Ok(LogLayer::Loki(layer, task))
+
+/// Initializes the Loki log provider, with the labels and extra fields of its configuration, sending the
+/// headers of its authentication configuration.
+///
+/// The headers of the Loki layer cannot be changed once it is built, so authentication configurations whose
+/// tokens expire or whose headers depend on the request are rejected, as are headers setting the tenant
+/// when the tenant ID is set too.
+async fn init_loki_log_provider(config: &LokiConfig, service_name: &str, auth_context: &AuthContext) -> Result<LogLayer> {
+    if !has_static_headers(&config.auth_config) {
+        return Err(anyhow!("The Loki log provider only supports static headers, as its headers are never refreshed"));
+    }
+    let headers = auth_context.token_provider(&config.auth_config).get_auth_headers().await?;
+    let mut builder = tracing_loki::builder().label("service", service_name)?;
+    for (key, value) in &config.labels {
+        builder = builder.label(key.as_str(), value)?;
+    }
+    for (key, value) in &config.extra_fields {
+        builder = builder.extra_field(key.as_str(), value)?;
+    }
+    if config.tenant_id.is_some() && headers.iter().any(|(key, _)| key.eq_ignore_ascii_case("X-Scope-OrgID")) {
+        return Err(anyhow!("The Loki tenant is set both by the tenant ID and by an X-Scope-OrgID header"));
+    }
+    for (key, value) in headers {
+        builder = builder.http_header(key, value)?;
+    }
+    if let Some(tenant_id) = &config.tenant_id {
+        builder = builder.http_header("X-Scope-OrgID", tenant_id)?;
+    }
+    let (layer, controller, task) = builder.build_controller_url(Url::parse(config.url.as_str())?)?;
+    Ok(LogLayer::Loki(layer, controller, task))
+}
+
+
+/// Builds the OTLP log exporter.
+///
+/// With `LogTransport::Inherit`, the transport, endpoint, authentication, export policy and HTTP encoding of the
+/// OTLP trace exporter are used, so logs reach the same collector as traces, as well as its gRPC metadata,
+/// certificate verification mode, channel keep-alive and connect timeout, and payload compression. The endpoint and
+/// compression of the log configuration, if any, take precedence over the inherited ones. The gRPC transport
+/// rejects the authentication configurations signing each request, e.g. HMAC.
+fn get_otlp_log_exporter(config: &OTLPLogConfig, trace_config: &TraceConfig, auth_context: &AuthContext) -> Result<LogExporter> {
+    let (transport, auth_config, export_policy, inherited_endpoint, encoding) = match (config.transport, trace_config) {
+        (LogTransport::Inherit, TraceConfig::HTTP(trace) | TraceConfig::REQWEST(trace)) =>
+            (LogTransport::HTTP, &trace.auth_config, trace.export_policy.clone(), Some(http_logs_endpoint(&trace.endpoint)), trace.encoding),
+        (LogTransport::Inherit, TraceConfig::GRPC(trace)) =>
+            (LogTransport::GRPC, &trace.auth_config, trace.export_policy.clone(), Some(trace.endpoint.clone()), config.encoding),
+        (LogTransport::Inherit, TraceConfig::StdOut(_) | TraceConfig::Zipkin(_) | TraceConfig::DatadogAgent(_) | TraceConfig::XRay(_) | TraceConfig::File(_) | TraceConfig::InMemory(_) | TraceConfig::Noop(_)) | (LogTransport::HTTP, _) =>
+            (LogTransport::HTTP, &config.auth_config, ExportPolicyConfig::default(), None, config.encoding),
+        (LogTransport::GRPC, _) =>
+            (LogTransport::GRPC, &config.auth_config, ExportPolicyConfig::default(), None, config.encoding),
+    };
+    let (metadata, insecure_skip_verify, channel_config) = match (config.transport, trace_config) {
+        (LogTransport::Inherit, TraceConfig::GRPC(trace)) => (trace.metadata.clone(), trace.insecure_skip_verify, trace.channel.clone()),
+        _ => (Vec::new(), false, GrpcChannelConfig::default()),
+    };
+    let inherited_compression = match (config.transport, trace_config) {
+        (LogTransport::Inherit, TraceConfig::GRPC(trace) | TraceConfig::HTTP(trace) | TraceConfig::REQWEST(trace)) => trace.compression,
+        _ => ExportCompression::None,
+    };
+    let compression = otlp_compression(config.compression.unwrap_or(inherited_compression));
+    let endpoint = config.endpoint.clone().or(inherited_endpoint);
+    let token_provider = auth_context.token_provider(auth_config);
+
+    if transport == LogTransport::GRPC {
+        if auth_config.signs_requests() {
+            return Err(anyhow!("HMAC authentication is not supported by the gRPC log exporter, as its headers are not bound to a request"));
+        }
+        let endpoint = endpoint.unwrap_or(DEFAULT_GRPC_ENDPOINT.to_string());
+        let channel = if insecure_skip_verify {
+            get_insecure_grpc_channel(&endpoint, &export_policy, &channel_config)?
+        } else {
+            get_grpc_channel(&endpoint, &export_policy, &channel_config)?
+        };
+        let mut builder = LogExporter::builder()
+            .with_tonic()
+            .with_channel(channel)
+            .with_timeout(export_policy.timeout)
+            .with_interceptor(auth_context.interceptor(auth_config).with_metadata(metadata));
+        if let Some(compression) = compression {
+            builder = builder.with_compression(compression);
+        }
+        return Ok(builder.build()?);
+    }
+    let http_client = Arc::new(reqwest::Client::new()) as Arc<dyn HttpClient>;
+    let mut builder = LogExporter::builder()
+        .with_http()
+        .with_protocol(http_protocol(encoding))
+        .with_http_client(ReqwestTracerClient::new(http_client, token_provider));
+    if let Some(endpoint) = endpoint {
+        builder = builder.with_endpoint(endpoint);
+    }
+    if let Some(compression) = compression {
+        builder = builder.with_compression(compression);
+    }
+    Ok(builder.build()?)
 }
 
 
@@ -37,55 +182,274 @@ fn init_loki_log_provider(config: &LokiConfig, service_name: &String) -> Result<
 /// # Arguments
 ///
 /// * `config` - The logging configuration.
+/// * `trace_config` - The tracing configuration, from which the OTLP log exporter may inherit its transport.
 /// * `service_name` - The name of the service.
-pub fn get_logger(config: &LogConfig, service_name: &String) -> Result<LogLayer> {
+/// * `auth_context` - The shared authentication state used to obtain token providers.
+pub async fn get_logger(config: &LogConfig, trace_config: &TraceConfig, service_name: &str, auth_context: &AuthContext) -> Result<LogLayer> {
     match config { 
-        LogConfig::Loki(loki_config) => init_loki_log_provider(loki_config, service_name),
-        LogConfig::OTLP => Ok(LogLayer::OTLP),
-        LogConfig::Stdout => Ok(LogLayer::Stdout),
+        LogConfig::Loki(loki_config) => init_loki_log_provider(loki_config, service_name, auth_context).await,
+        LogConfig::OTLP(otlp_config) => {
+            let exporter = get_otlp_log_exporter(otlp_config, trace_config, auth_context)?;
+            Ok(LogLayer::OTLP(otlp_config.clone(), exporter))
+        },
+        LogConfig::Stdout(stdout_config) => Ok(LogLayer::Stdout(stdout_config.clone(), stdout_config.buffer.map(|buffer| non_blocking(std::io::stdout(), &buffer)))),
+        LogConfig::File(file_config) => {
+            let (writer, guard) = file_writer(file_config)?;
+            Ok(LogLayer::File(writer, guard, file_config.clone()))
+        },
+        LogConfig::Syslog(syslog_config) => {
+            let (writer, guard) = syslog_writer(syslog_config, service_name)?;
+            Ok(LogLayer::Syslog(writer, guard))
+        },
+        #[cfg(feature = "journald")]
+        LogConfig::Journald => {
+            let layer = tracing_journald::layer()?.with_syslog_identifier(service_name.to_string());
+            Ok(LogLayer::Journald(layer))
+        },
+        LogConfig::Multi(sinks) => {
+            let mut layers = Vec::with_capacity(sinks.len());
+            for sink in sinks {
+                let layer = Box::pin(get_logger(&sink.config, trace_config, service_name, auth_context)).await?;
+                layers.push((layer, sink.min_level, sink.level.clone()));
+            }
+            Ok(LogLayer::Multi(layers))
+        },
     }
 }
 
 
-/// Sets the global logger.
+/// Returns the layer exporting the `tracing` spans with the given tracer.
+///
+/// With `record_exceptions`, ERROR events recorded inside a span, and errors recorded as its fields, add an
+/// `exception` event to it and ERROR events set its status to Error. Without it, neither is recorded.
+fn telemetry_layer<S>(tracer: Tracer, record_exceptions: bool) -> OpenTelemetryLayer<S, Tracer>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    tracing_opentelemetry::layer()
+        .with_tracer(tracer)
+        .with_error_events_to_status(record_exceptions)
+        .with_error_events_to_exceptions(record_exceptions)
+        .with_error_records_to_exceptions(record_exceptions)
+}
+
+
+/// Returns the layer writing the `tracing` events to standard output with the given configuration and writer.
+fn stdout_layer<S>(config: StdoutLogConfig, writer: BoxMakeWriter, service_name: &str) -> Result<Box<dyn Layer<S> + Send + Sync>>
+where
+    S: Subscriber + for<'span> LookupSpan<'span> + 'static,
+{
+    let mut layer = fmt::layer().with_writer(writer);
+    if let Some(ansi) = config.ansi {
+        layer = layer.with_ansi(ansi);
+    }
+    match (config.timestamps, config.timestamp_format) {
+        (false, _) => format_layer(layer.without_time(), config.format, &config.cloud_format, &config.fields, service_name),
+        (true, Some(timestamp_format)) => format_layer(layer.with_timer(LogTimer::new(timestamp_format, config.timezone)), config.format, &config.cloud_format, &config.fields, service_name),
+        (true, None) => format_layer(layer, config.format, &config.cloud_format, &config.fields, service_name),
+    }
+}
+
+
+/// Returns a formatting layer writing the given metadata in the log lines.
+fn with_fields<S, N, L, T, W>(layer: fmt::Layer<S, N, Format<L, T>, W>, fields: &LogLineFields) -> fmt::Layer<S, N, Format<L, T>, W>
+where
+    N: for<'writer> FormatFields<'writer> + 'static,
+{
+    layer
+        .with_file(fields.source_location)
+        .with_line_number(fields.source_location)
+        .with_target(fields.target)
+        .with_thread_names(fields.thread_names)
+        .with_thread_ids(fields.thread_ids)
+}
+
+
+/// Returns the layer writing the `tracing` events with the writer and timer of a formatting layer, in the given format.
+///
+/// An error is returned for the GCP format without the project of the traces.
+fn format_layer<S, T, W>(layer: fmt::Layer<S, DefaultFields, Format<Full, T>, W>, format: StdoutLogFormat, cloud_format: &CloudLogFormatConfig, fields: &LogLineFields, service_name: &str) -> Result<Box<dyn Layer<S> + Send + Sync>>
+where
+    S: Subscriber + for<'span> LookupSpan<'span> + 'static,
+    T: FormatTime + Send + Sync + 'static,
+    W: for<'writer> MakeWriter<'writer> + Send + Sync + 'static,
+{
+    let layer = match format {
+        StdoutLogFormat::Full => with_fields(layer, fields).boxed(),
+        StdoutLogFormat::Pretty => with_fields(layer.pretty(), fields).boxed(),
+        StdoutLogFormat::Compact => with_fields(layer.compact(), fields).boxed(),
+        StdoutLogFormat::Json => with_fields(layer.json(), fields).with_span_list(fields.span_list).boxed(),
+        StdoutLogFormat::Ecs => layer.event_format(JsonLogFormat::new(JsonLogSchema::Ecs, service_name)).boxed(),
+        StdoutLogFormat::GcpJson => {
+            let project_id = cloud_format.gcp_project_id.clone()
+                .ok_or_else(|| anyhow!("The gcp log format requires the GCP project of the traces"))?;
+            layer.event_format(JsonLogFormat::new(JsonLogSchema::Gcp { project_id }, service_name)).boxed()
+        },
+        StdoutLogFormat::CloudWatchJson => {
+            let emf_namespace = cloud_format.emf_namespace.clone();
+            layer.event_format(JsonLogFormat::new(JsonLogSchema::CloudWatch { emf_namespace }, service_name)).boxed()
+        },
+    };
+    Ok(layer)
+}
+
+
+/// Returns the batch processor exporting the log records of an OTLP layer.
+fn batch_processor(exporter: LogExporter, config: &LogBatchConfig) -> BatchLogProcessor {
+    let batch_config = BatchConfigBuilder::default()
+        .with_max_queue_size(config.max_queue_size)
+        .with_max_export_batch_size(config.max_export_batch_size)
+        .with_scheduled_delay(config.scheduled_delay)
+        .build();
+    BatchLogProcessor::builder(exporter).with_batch_config(batch_config).build()
+}
+
+
+/// Adds the `trace_id` and `span_id` fields of the current span to the events passed to a layer.
+///
+/// The OTLP layer does not need it, as the log records carry the trace context.
+fn with_trace_ids<L: Layer<Registry> + Send + Sync + 'static>(layer: L) -> Box<dyn Layer<Registry> + Send + Sync> {
+    RewriteLayer::new(layer, TraceContext).boxed()
+}
+
+
+/// Returns the subscriber layer of a log layer, spawning its background tasks.
+///
+/// Except with OTLP, the events recorded inside a span get its trace and span IDs as fields.
+/// The sinks of a `LogLayer::Multi` layer are filtered by their minimum level or, without it, by the shared `RUST_LOG` filter,
+/// narrowed by their level filter, if any.
+fn sink_layer(log_layer: LogLayer, service_name: &str, env_filter: &SharedFilter, state: &mut LogHandles) -> Result<Box<dyn Layer<Registry> + Send + Sync>> {
+    let layer = match log_layer {
+        LogLayer::Loki(layer, controller, task) => {
+            state.loki_tasks.get_mut().unwrap_or_else(|e| e.into_inner()).push((controller, tokio::spawn(task)));
+            with_trace_ids(layer)
+        },
+        LogLayer::OTLP(otlp_config, exp) => {
+            let prov = SdkLoggerProvider::builder()
+                .with_log_processor(BinaryEncodingProcessor::new(otlp_config.binary_policy))
+                .with_log_processor(LogLimitsProcessor::new(otlp_config.limits))
+                .with_log_processor(batch_processor(exp, &otlp_config.batch))
+                .with_resource(get_resource(service_name))
+                .build();
+            let layer = OpenTelemetryTracingBridge::new(&prov).boxed();
+            state.logger_providers.push(prov);
+            layer
+        },
+        LogLayer::Stdout(config, writer) => {
+            let writer = match writer {
+                Some((writer, guard)) => {
+                    state.guards.push(guard);
+                    BoxMakeWriter::new(writer)
+                },
+                None => BoxMakeWriter::new(std::io::stdout),
+            };
+            with_trace_ids(stdout_layer(config, writer, service_name)?)
+        },
+        LogLayer::File(writer, guard, config) => {
+            state.guards.push(guard);
+            with_trace_ids(format_layer(fmt::layer().with_ansi(false).with_writer(writer), config.format, &config.cloud_format, &config.fields, service_name)?)
+        },
+        LogLayer::Syslog(writer, guard) => {
+            state.guards.push(guard);
+            with_trace_ids(fmt::layer().with_ansi(false).without_time().with_level(false).with_writer(writer))
+        },
+        #[cfg(feature = "journald")]
+        LogLayer::Journald(layer) => with_trace_ids(layer),
+        LogLayer::Multi(sinks) => {
+            let mut layers = Vec::with_capacity(sinks.len());
+            for (log_layer, min_level, level) in sinks {
+                let layer = sink_layer(log_layer, service_name, env_filter, state)?;
+                let filter: Box<dyn Filter<Registry> + Send + Sync> = match min_level {
+                    Some(min_level) => Box::new(LevelFilter::from_level(min_level)),
+                    None => Box::new(env_filter.clone()),
+                };
+                layers.push(match level {
+                    Some(level) => layer.with_filter(filter.and(EnvFilter::try_new(level)?)).boxed(),
+                    None => layer.with_filter(filter).boxed(),
+                });
+            }
+            layers.boxed()
+        },
+    };
+    Ok(layer)
+}
+
+
+/// Composes the log sinks, the span exporting layer and the optional layers into a single layer.
+///
+/// The events of each sink are filtered by its minimum level or, if it has none, by the `RUST_LOG` environment variable,
+/// narrowed by its level filter, if any. The `RUST_LOG` filter is built once and also filters the exported spans and
+/// the ERROR events flushing the telemetry.
 ///
 /// # Arguments
 ///
-/// * `log_layer` - The log layer to set.
+/// * `log_layer` - The log layer to compose.
 /// * `tracer` - The tracer to use.
 /// * `service_name` - The name of the service.
-pub fn set_logger(log_layer: LogLayer, tracer: Tracer, service_name: &String) -> Result<()> {
-    let filter = EnvFilter::from_default_env();
-    match log_layer {
-        LogLayer::Loki(layer, task) =>{
-            let telemetry = tracing_opentelemetry::layer().with_tracer(tracer);
-            tokio::spawn(task);
-            tracing_subscriber::registry()
-                .with(filter)
-                .with(layer)
-                .with(telemetry)
-                .init();
-        },
-        LogLayer::OTLP => {
-            let telemetry = tracing_opentelemetry::layer().with_tracer(tracer);
-            let exp = LogExporter::builder().with_http().build().expect("Failed to create OTLP log exporter");
-            let prov = SdkLoggerProvider::builder().with_batch_exporter(exp).with_resource(get_resource(service_name)).build();
-            let log_layer = OpenTelemetryTracingBridge::new(&prov);
-            tracing_subscriber::registry()
-                .with(filter)
-                .with(telemetry)
-                .with(log_layer)
-                .init();
-        },
-        _ => {
-            let telemetry = tracing_opentelemetry::layer().with_tracer(tracer);
-            tracing_subscriber::registry()
-                .with(filter)
-                .with(fmt::layer())
-                .with(telemetry)
-                .init();
-        }
+/// * `error_flush` - The layer flushing the telemetry on ERROR events, if enabled.
+/// * `rate_limit` - The layer suppressing identical events past their rate limit, if enabled.
+/// * `span_log` - The layer recording an event when a span starts and ends, if enabled.
+/// * `redaction` - The redaction configuration applied to the events before the log sinks and the layers of the application, if any.
+/// * `extra_layers` - The layers of the application, added unfiltered.
+/// * `record_exceptions` - Whether ERROR events record an exception and set the status of their span.
+///
+/// # Returns
+///
+/// The composed layer, with the logger providers of the OTLP sinks and the guards flushing the non-blocking log writers when dropped.
+#[allow(clippy::too_many_arguments)]
+pub fn compose_layer(log_layer: LogLayer, tracer: Tracer, service_name: &str, error_flush: Option<ErrorFlushLayer>, rate_limit: Option<RateLimitLayer>, span_log: Option<SpanLogLayer>, redaction: Option<&RedactionConfig>, extra_layers: Vec<Box<dyn Layer<Registry> + Send + Sync>>, record_exceptions: bool) -> Result<(Box<dyn Layer<Registry> + Send + Sync>, LogHandles)> {
+    let mut state = LogHandles::default();
+    let env_filter: SharedFilter = Arc::new(EnvFilter::from_default_env());
+    let layer = match log_layer {
+        LogLayer::Multi(_) => sink_layer(log_layer, service_name, &env_filter, &mut state)?,
+        _ => sink_layer(log_layer, service_name, &env_filter, &mut state)?.with_filter(env_filter.clone()).boxed(),
+    };
+    let mut layers = vec![layer];
+    layers.extend(extra_layers);
+    let layer = match redaction {
+        Some(config) => RewriteLayer::new(layers, LogRedaction::new(Redactor::new(config)?)).boxed(),
+        None => layers.boxed(),
+    };
+    let error_flush = error_flush.map(|error_flush| state.logger_providers.iter()
+        .fold(error_flush, |error_flush, prov| error_flush.with_logger_provider(prov.clone())));
+    // Boxed rather than optional, since optional layers do not forward the subscriber the summaries are recorded through.
+    let rate_limit = match rate_limit {
+        Some(rate_limit) => rate_limit.boxed(),
+        None => Identity::new().boxed(),
     };
+    let layer = layer
+        .and_then(rate_limit)
+        .and_then(span_log)
+        .and_then(telemetry_layer(tracer, record_exceptions).with_filter(env_filter.clone()))
+        .and_then(error_flush.with_filter(env_filter))
+        .boxed();
 
+    Ok((layer, state))
+}
+
+
+/// Sets the global logger.
+///
+/// # Arguments
+///
+/// * `layer` - The composed layer, see `compose_layer`.
+/// * `log_bridge` - Whether the records of the `log` crate are converted to events of the global logger.
+///
+/// # Returns
+///
+/// An error if a global logger, or a `log` logger with the bridge, is already set, in which case neither is set.
+pub fn set_logger(layer: Box<dyn Layer<Registry> + Send + Sync>, log_bridge: bool) -> Result<()> {
+    let subscriber = tracing_subscriber::registry().with(layer);
+    if tracing::dispatcher::has_been_set() {
+        return Err(anyhow!("a global logger is already set"));
+    }
+    if log_bridge {
+        // The maximum level of the logger skips the records no sink would keep.
+        let max_level = subscriber.max_level_hint().unwrap_or(LevelFilter::TRACE);
+        LogTracer::builder()
+            .with_max_level(max_level.as_log())
+            .init()?;
+    }
+    tracing::subscriber::set_global_default(subscriber)?;
     Ok(())
 }
@@ -1,29 +1,576 @@
 //! # OpenTelemetry Module
 //!
 //! This module provides the main entry point for configuring OpenTelemetry.
+pub mod baggage;
+#[cfg(not(feature = "noop"))]
 mod logger;
-mod resource;
+#[cfg(not(feature = "noop"))]
+mod log_limits;
+#[cfg(not(feature = "noop"))]
+mod log_record;
+#[cfg(not(feature = "noop"))]
+mod log_redaction;
+#[cfg(not(feature = "noop"))]
+mod log_time;
+#[cfg(not(feature = "noop"))]
+mod binary_encoding;
+#[cfg(not(feature = "noop"))]
+mod error_flush;
+#[cfg(not(feature = "noop"))]
+mod event_rewrite;
+#[cfg(not(feature = "noop"))]
+mod file_log;
+#[cfg(not(feature = "noop"))]
+mod json_log;
+mod propagation;
+#[cfg(not(feature = "noop"))]
+mod rate_limit;
+mod startup;
+#[cfg(not(feature = "noop"))]
+mod trace_context;
+#[cfg(not(feature = "noop"))]
+mod syslog_log;
+mod shutdown;
+#[cfg(not(feature = "noop"))]
+mod span_log;
 
+pub use startup::DEFAULT_STARTUP_BUDGET;
+pub use shutdown::{ExporterError, ShutdownReport, DEFAULT_SHUTDOWN_TIMEOUT};
+
+use opentelemetry::InstrumentationScope;
+#[cfg(not(feature = "noop"))]
+use opentelemetry::global;
+use opentelemetry::propagation::TextMapCompositePropagator;
+#[cfg(not(feature = "noop"))]
+use opentelemetry::metrics::MeterProvider;
+#[cfg(not(feature = "noop"))]
 use opentelemetry::trace::TracerProvider;
-use crate::otel::logger::{get_logger, set_logger};
-use anyhow::Result;
-use crate::tracer::get_tracer_provider;
+#[cfg(not(feature = "noop"))]
+use crate::otel::logger::{compose_layer, get_logger, set_logger};
+use anyhow::{anyhow, Result};
+use crate::tracer::{ExportErrorCallbackHandle, ExportErrorEvent};
+#[cfg(not(feature = "noop"))]
+use crate::tracer::{get_tracer_provider_builder, report_insecure, with_exporter};
+use crate::tracer::export_error::ExportStats;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::summary::TraceSummaryProcessor;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::baggage::BaggageSpanProcessor;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::enrichment::EnrichmentProcessor;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::span_name::SpanNameProcessor;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::boxed::BoxedSpanProcessor;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::redaction::RedactionProcessor;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::connectivity::verify_connectivity;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::sampling::LoggingSampler;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::heartbeat::spawn_heartbeat;
+#[cfg(not(feature = "noop"))]
+use crate::self_observability::{register_process_metrics, METER_NAME};
+#[cfg(not(feature = "noop"))]
+use crate::resource::get_resource;
 
-use opentelemetry_sdk::trace::SdkTracerProvider as SDKTracerProvider;
-use crate::config::{LogConfig, TraceConfig};
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::logs::SdkLoggerProvider;
+use opentelemetry_sdk::metrics::SdkMeterProvider;
+use opentelemetry_sdk::trace::{SpanData, SpanProcessor, SdkTracerProvider as SDKTracerProvider};
+use crate::auth::{AuthContext, CredentialsCallbackHandle, CredentialsEvent};
+use crate::config::{ErrorFlushConfig, HeartbeatConfig, LogConfig, LogRateLimitConfig, ProcessMetricsConfig, PropagationConfig, RedactionConfig, SpanEnrichmentConfig, SpanNameRule, SpanStore, ScopeFilter, TailSamplingConfig, TraceConfig};
+#[cfg(not(feature = "noop"))]
+use crate::config::validate_grpc_metadata;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tracing_subscriber::Layer;
+#[cfg(feature = "noop")]
+use tracing_subscriber::layer::Identity;
+use tracing_subscriber::registry::Registry;
+#[cfg(not(feature = "noop"))]
+use crate::otel::startup::spawn_startup;
+use crate::otel::shutdown::{stop_logger_providers, stop_loki_tasks, stop_provider, LogHandles};
+#[cfg(not(feature = "noop"))]
+use crate::otel::error_flush::ErrorFlushLayer;
+#[cfg(not(feature = "noop"))]
+use crate::otel::rate_limit::RateLimitLayer;
+#[cfg(not(feature = "noop"))]
+use crate::otel::span_log::SpanLogLayer;
+use crate::otel::propagation::build_propagator;
 
 
 /// The main OpenTelemetry object.
 pub struct OpenTelemetryObject {
     /// The tracer provider.
     pub tracer: SDKTracerProvider,
+    /// The heartbeat task, if enabled.
+    heartbeat: Option<JoinHandle<()>>,
+    /// Set to `true` once the background initialization is complete.
+    ready: watch::Receiver<bool>,
+    /// The store of the exported spans, with the in-memory trace configuration.
+    finished_spans: Option<SpanStore>,
+    /// The logger providers of the OTLP log sinks and the guards flushing the non-blocking log writers.
+    logs: LogHandles,
+    /// The counters of the exported and dropped spans of the tracer provider.
+    export_stats: Arc<ExportStats>,
+    /// The propagation formats of the text map propagator, if enabled.
+    propagation: Option<PropagationConfig>,
+    /// The authentication state of the exporters, with their credential rotation callbacks.
+    auth_context: AuthContext,
+}
+
+
+/// Builder for `OpenTelemetryObject`, allowing optional settings to be configured.
+pub struct OpenTelemetryObjectBuilder {
+    #[cfg(not(feature = "noop"))]
+    log_config: LogConfig,
+    #[cfg(not(feature = "noop"))]
+    trace_config: TraceConfig,
+    #[cfg(not(feature = "noop"))]
+    service_name: String,
+    trace_resource: Option<Resource>,
+    instrumentation_scope: Option<InstrumentationScope>,
+    trace_summary: bool,
+    sampling_decision_logging: bool,
+    heartbeat: Option<HeartbeatConfig>,
+    tail_sampling: Option<TailSamplingConfig>,
+    redaction: Option<RedactionConfig>,
+    startup_budget: Duration,
+    verify_connectivity: bool,
+    propagator: bool,
+    propagation: Option<PropagationConfig>,
+    baggage_span_attributes: Vec<String>,
+    span_enrichment: Option<SpanEnrichmentConfig>,
+    span_name_rules: Vec<SpanNameRule>,
+    span_processors: Vec<Box<dyn SpanProcessor>>,
+    scopes: ScopeFilter,
+    process_metrics: Option<ProcessMetricsConfig>,
+    meter_provider: Option<SdkMeterProvider>,
+    error_flush: Option<ErrorFlushConfig>,
+    log_rate_limit: Option<LogRateLimitConfig>,
+    log_redaction: Option<RedactionConfig>,
+    extra_layers: Vec<Box<dyn Layer<Registry> + Send + Sync>>,
+    exception_recording: bool,
+    log_bridge: bool,
+    span_logging: bool,
+    additional_exporters: Vec<(TraceConfig, ScopeFilter)>,
+}
+
+
+impl OpenTelemetryObjectBuilder {
+    /// Creates a new `OpenTelemetryObjectBuilder`.
+    ///
+    /// # Arguments
+    ///
+    /// * `log_config` - The logging configuration.
+    /// * `trace_config` - The tracing configuration.
+    /// * `service_name` - The name of the service.
+    pub fn new(log_config: &LogConfig, trace_config: &TraceConfig, service_name: String) -> Self {
+        // With the `noop` feature, no pipeline is built from the configurations.
+        #[cfg(feature = "noop")]
+        let _ = (log_config, trace_config, service_name);
+        OpenTelemetryObjectBuilder {
+            #[cfg(not(feature = "noop"))]
+            log_config: log_config.clone(),
+            #[cfg(not(feature = "noop"))]
+            trace_config: trace_config.clone(),
+            #[cfg(not(feature = "noop"))]
+            service_name,
+            trace_resource: None,
+            instrumentation_scope: None,
+            trace_summary: false,
+            sampling_decision_logging: false,
+            heartbeat: None,
+            tail_sampling: None,
+            redaction: None,
+            startup_budget: DEFAULT_STARTUP_BUDGET,
+            verify_connectivity: false,
+            propagator: true,
+            propagation: None,
+            baggage_span_attributes: Vec::new(),
+            span_enrichment: None,
+            span_name_rules: Vec::new(),
+            span_processors: Vec::new(),
+            scopes: ScopeFilter::All,
+            process_metrics: None,
+            meter_provider: None,
+            error_flush: None,
+            log_rate_limit: None,
+            log_redaction: None,
+            extra_layers: Vec::new(),
+            exception_recording: false,
+            log_bridge: true,
+            span_logging: false,
+            additional_exporters: Vec::new(),
+        }
+    }
+
+    /// Prints a summary of the traces captured during the run (count, slowest traces and
+    /// error traces) when the `OpenTelemetryObject` is stopped. Meant for development.
+    pub fn with_trace_summary(mut self, enabled: bool) -> Self {
+        self.trace_summary = enabled;
+        self
+    }
+
+    /// Logs the sampling decision of each root span at TRACE level, with the
+    /// `rust_otel_setup::sampling` target. Logging is rate limited.
+    pub fn with_sampling_decision_logging(mut self, enabled: bool) -> Self {
+        self.sampling_decision_logging = enabled;
+        self
+    }
+
+    /// Exports a synthetic `heartbeat` span and increments the `otel.heartbeat` counter at the
+    /// configured interval, so dashboards can distinguish an idle service from one that is down.
+    /// `None` disables the heartbeat, which is the default.
+    pub fn with_heartbeat(mut self, heartbeat: Option<HeartbeatConfig>) -> Self {
+        self.heartbeat = heartbeat;
+        self
+    }
+
+    /// Buffers the spans of each trace and exports whole traces: always when they contain an error
+    /// or a slow span, otherwise with the configured ratio. `None` disables tail sampling, which is the default.
+    pub fn with_tail_sampling(mut self, tail_sampling: Option<TailSamplingConfig>) -> Self {
+        self.tail_sampling = tail_sampling;
+        self
+    }
+
+    /// Drops or hashes the span attributes matching the redaction configuration, e.g. authorization headers
+    /// or email addresses, before the ended spans reach any exporter or span processor, including those of
+    /// `with_span_processor`. `None` disables redaction, which is the default.
+    pub fn with_redaction(mut self, redaction: Option<RedactionConfig>) -> Self {
+        self.redaction = redaction;
+        self
+    }
+
+    /// Sets the time allowed for the background initialization, after which
+    /// `OpenTelemetryObject::ready` resolves even if authentication has not completed.
+    /// Defaults to `DEFAULT_STARTUP_BUDGET`.
+    pub fn with_startup_budget(mut self, budget: Duration) -> Self {
+        self.startup_budget = budget;
+        self
+    }
+
+    /// Sends an empty export to the endpoint of each trace exporter while building, so DNS, TLS and
+    /// authentication failures make `build` return a descriptive error instead of silently dropping
+    /// spans at runtime. Each check is allowed the startup budget. Disabled by default.
+    pub fn with_verify_connectivity(mut self, enabled: bool) -> Self {
+        self.verify_connectivity = enabled;
+        self
+    }
+
+    /// Installs the propagators of the propagation configuration, by default read from `OTEL_PROPAGATORS`,
+    /// as the global text map propagator, so incoming `traceparent`, `tracestate` and `baggage` headers
+    /// are honored by the instrumentation. Enabled by default; disable it to install another propagator.
+    /// `build_layer` does not install it, see `OpenTelemetryObject::propagator`.
+    pub fn with_propagator(mut self, enabled: bool) -> Self {
+        self.propagator = enabled;
+        self
+    }
+
+    /// Sets the propagation formats of the global text map propagator, e.g. to accept the Jaeger
+    /// `uber-trace-id` header alongside W3C TraceContext. Defaults to `PropagationConfig::from_env`.
+    pub fn with_propagation(mut self, propagation: PropagationConfig) -> Self {
+        self.propagation = Some(propagation);
+        self
+    }
+
+    /// Records the `process.uptime` gauge and the `process.starts` counter, plus the `process.restart.count`
+    /// gauge if a restart marker file is configured, through the meter provider of `with_meter_provider`,
+    /// or else the global one. `build_layer` only records them through the meter provider of `with_meter_provider`.
+    /// `None` disables the process metrics, which is the default.
+    pub fn with_process_metrics(mut self, process_metrics: Option<ProcessMetricsConfig>) -> Self {
+        self.process_metrics = process_metrics;
+        self
+    }
+
+    /// Sets the meter provider of the crate's metrics, e.g. `otel.exporter.dropped`, the queue and heartbeat
+    /// metrics, and the process metrics. `build` installs it as the global meter provider, since these metrics
+    /// are recorded through it. Not set by default, which leaves the global meter provider, a no-op one unless
+    /// the application installs another, so the metrics are only exported with a meter provider.
+    pub fn with_meter_provider(mut self, meter_provider: SdkMeterProvider) -> Self {
+        self.meter_provider = Some(meter_provider);
+        self
+    }
+
+    /// Flushes the trace and log batches in the background whenever an ERROR event is recorded,
+    /// at most once per configured interval. `None` disables the flushes, which is the default.
+    pub fn with_flush_on_error(mut self, error_flush: Option<ErrorFlushConfig>) -> Self {
+        self.error_flush = error_flush;
+        self
+    }
+
+    /// Rate limits identical log events, i.e. with the same target and message template, to the configured
+    /// number per window, recording a "suppressed N similar messages" event for the suppressed ones.
+    /// `None` disables the rate limiting, which is the default.
+    pub fn with_log_rate_limit(mut self, log_rate_limit: Option<LogRateLimitConfig>) -> Self {
+        self.log_rate_limit = log_rate_limit;
+        self
+    }
+
+    /// Redacts the fields of the log events before they reach the log sinks: fields named after a key of the
+    /// configuration are dropped or hashed, and the parts of messages and values matching a pattern are replaced.
+    /// See `RedactionConfig::pii` for common personal data. `None` disables the redaction, which is the default.
+    pub fn with_log_redaction(mut self, log_redaction: Option<RedactionConfig>) -> Self {
+        self.log_redaction = log_redaction;
+        self
+    }
+
+    /// Adds a `tracing-subscriber` layer of the application, e.g. a Sentry or metrics layer, to the registry
+    /// initialized by `build`. Can be called several times; the layers are not filtered by `RUST_LOG`,
+    /// so they can apply their own filters with `Layer::with_filter`. Like the log sinks, they receive the events
+    /// redacted by `with_log_redaction`.
+    pub fn with_extra_layer<L>(mut self, layer: L) -> Self
+    where
+        L: Layer<Registry> + Send + Sync + 'static,
+    {
+        self.extra_layers.push(layer.boxed());
+        self
+    }
+
+    /// Records an `exception` event and sets the span status to Error whenever an ERROR event is
+    /// recorded inside a span, so error traces are flagged without manual instrumentation. Disabled by default.
+    pub fn with_exception_recording(mut self, enabled: bool) -> Self {
+        self.exception_recording = enabled;
+        self
+    }
+
+    /// Converts the records of the `log` crate, e.g. of dependencies using `log::info!`, to `tracing` events,
+    /// so they reach the log sinks with the same filtering and enrichment. Fails the build if another `log`
+    /// logger is already set. Enabled by default, like the `init` of `tracing-subscriber`.
+    pub fn with_log_bridge(mut self, enabled: bool) -> Self {
+        self.log_bridge = enabled;
+        self
+    }
+
+    /// Records an INFO event with the `rust_otel_setup::span` target when a span starts and ends, with its name,
+    /// duration and status, so services with instrumented spans but no access logs get basic request logs
+    /// in the log sinks. Disabled by default.
+    pub fn with_span_logging(mut self, enabled: bool) -> Self {
+        self.span_logging = enabled;
+        self
+    }
+
+    /// Sets the resource of the traces, e.g. to give them a different `service.namespace` than the logs.
+    /// Defaults to a resource with the service name only.
+    pub fn with_trace_resource(mut self, resource: Resource) -> Self {
+        self.trace_resource = Some(resource);
+        self
+    }
+
+    /// Sets the instrumentation scope (name, version, schema URL and attributes) of the tracer bridging
+    /// `tracing` spans to OpenTelemetry. Defaults to a scope named after the service.
+    pub fn with_instrumentation_scope(mut self, scope: InstrumentationScope) -> Self {
+        self.instrumentation_scope = Some(scope);
+        self
+    }
+
+    /// Restricts the exporter of the tracing configuration to the spans of the given instrumentation scopes.
+    /// Defaults to `ScopeFilter::All`.
+    pub fn with_scope_filter(mut self, scopes: ScopeFilter) -> Self {
+        self.scopes = scopes;
+        self
+    }
+
+    /// Adds an exporter receiving the spans of the given instrumentation scopes, e.g. to send `sqlx`
+    /// spans only to an internal collector, or every span with `ScopeFilter::All`, e.g. to export to
+    /// standard output alongside OTLP. The sampler, ID generator and span limits of the main tracing
+    /// configuration apply to every exporter. Can be called several times.
+    pub fn with_additional_exporter(mut self, trace_config: TraceConfig, scopes: ScopeFilter) -> Self {
+        self.additional_exporters.push((trace_config, scopes));
+        self
+    }
+
+    /// Records the baggage entries with the given keys, e.g. `tenant.id`, as attributes of each started span.
+    /// No entry is recorded by default.
+    pub fn with_baggage_span_attributes(mut self, keys: Vec<String>) -> Self {
+        self.baggage_span_attributes = keys;
+        self
+    }
+
+    /// Adds the attributes of the configuration, e.g. `deployment.environment` or `k8s.pod.name`, to each
+    /// ended span without them, before it reaches any exporter or span processor. `None` disables the enrichment,
+    /// which is the default.
+    pub fn with_span_enrichment(mut self, span_enrichment: Option<SpanEnrichmentConfig>) -> Self {
+        self.span_enrichment = span_enrichment;
+        self
+    }
+
+    /// Rewrites the name of each ended span with the given rules, in order, e.g. `SpanNameRule::path_ids`
+    /// to replace `GET /users/12345` with `GET /users/{id}`, before it reaches any exporter or span processor.
+    /// No rule is applied by default.
+    pub fn with_span_name_rules(mut self, rules: Vec<SpanNameRule>) -> Self {
+        self.span_name_rules = rules;
+        self
+    }
+
+    /// Registers an application span processor, e.g. for enrichment or redaction, on the tracer provider.
+    /// Processors are called in registration order, after the processors of the exporters.
+    ///
+    /// Attributes set in `on_start` are seen by the exporters, while `on_end` receives a copy of the
+    /// span, redacted with the configuration of `with_redaction`, if any. Can be called several times.
+    pub fn with_span_processor(mut self, processor: Box<dyn SpanProcessor>) -> Self {
+        self.span_processors.push(processor);
+        self
+    }
+
+    /// Builds the `OpenTelemetryObject`.
+    ///
+    /// This function initializes the tracer and logger providers based on the provided configuration.
+    /// A single `AuthContext` is shared by all pipelines, so each authentication provider is created once.
+    /// Authentication is bootstrapped concurrently in the background, so this function returns without
+    /// waiting for the network; use `OpenTelemetryObject::ready` to wait for it.
+    /// With `with_verify_connectivity`, it first checks that each trace exporter reaches its endpoint.
+    #[cfg(not(feature = "noop"))]
+    pub async fn build(self) -> Result<OpenTelemetryObject> {
+        let log_bridge = self.log_bridge;
+        // Installed first, so the instruments created while building record through it.
+        if let Some(meter_provider) = &self.meter_provider {
+            global::set_meter_provider(meter_provider.clone());
+        }
+        let global_process_metrics = self.process_metrics.clone().filter(|_| self.meter_provider.is_none());
+        let (object, layer) = self.build_pipelines().await?;
+        set_logger(layer, log_bridge)?;
+        // The warnings raised while building are logged once the subscriber receiving them is installed.
+        report_insecure();
+        if let Some(propagator) = object.propagator() {
+            global::set_text_map_propagator(propagator);
+        }
+        if let Some(config) = global_process_metrics {
+            register_process_metrics(&global::meter(METER_NAME), config.restart_marker).await;
+        }
+        Ok(object)
+    }
+
+    /// Stub of `build` with the `noop` feature: no exporters, subscriber layers or background tasks are set up,
+    /// and the returned tracer provider discards every span.
+    #[cfg(feature = "noop")]
+    pub async fn build(self) -> Result<OpenTelemetryObject> {
+        Ok(noop_object().0)
+    }
+
+    /// Builds the `OpenTelemetryObject` like `build`, but returns the composed subscriber layer (log sinks,
+    /// span exporting layer and optional layers) instead of initializing the global subscriber with it,
+    /// so libraries and applications with several subscribers can attach it themselves, e.g. with
+    /// `tracing::subscriber::set_default(tracing_subscriber::registry().with(layer))`.
+    ///
+    /// No global state is set: the text map propagator is returned by `OpenTelemetryObject::propagator`
+    /// instead of being installed, and the process metrics are only recorded through the meter provider
+    /// of `with_meter_provider`. The warnings raised while building, e.g. about disabled certificate
+    /// verification, are logged through the subscriber in effect when it returns.
+    ///
+    /// With the `noop` feature, the returned layer does nothing.
+    pub async fn build_layer(self) -> Result<(OpenTelemetryObject, Box<dyn Layer<Registry> + Send + Sync>)> {
+        #[cfg(feature = "noop")]
+        return Ok(noop_object());
+        #[cfg(not(feature = "noop"))]
+        return self.build_pipelines().await.inspect(|_| report_insecure());
+    }
+
+    /// Builds the exporters, subscriber layers and background tasks of `build_layer`.
+    #[cfg(not(feature = "noop"))]
+    async fn build_pipelines(mut self) -> Result<(OpenTelemetryObject, Box<dyn Layer<Registry> + Send + Sync>)> {
+        // Configurations built in code bypass the validation of `OTLPTraceConfig::from_env`.
+        for trace_config in [&self.trace_config].into_iter().chain(self.additional_exporters.iter().map(|(config, _)| config)) {
+            if let TraceConfig::GRPC(config) = trace_config {
+                validate_grpc_metadata(&config.metadata)?;
+            }
+        }
+        let auth_context = AuthContext::new();
+        let providers = [self.trace_config.auth_config()]
+            .into_iter()
+            .chain(self.additional_exporters.iter().map(|(config, _)| config.auth_config()))
+            .flatten()
+            .chain(self.log_config.auth_configs())
+            .map(|config| (auth_context.token_provider(config), auth_context.header_cache(config)))
+            .collect();
+        let ready = spawn_startup(providers, self.startup_budget);
+
+        if self.verify_connectivity {
+            verify_connectivity(&self.trace_config, &auth_context, self.startup_budget).await?;
+            for (trace_config, _) in &self.additional_exporters {
+                verify_connectivity(trace_config, &auth_context, self.startup_budget).await?;
+            }
+        }
+
+        let resource = self.trace_resource.clone().unwrap_or_else(|| get_resource(&self.service_name));
+        let export_stats = Arc::new(ExportStats::default());
+        let mut builder = get_tracer_provider_builder(&self.trace_config, &resource, &auth_context, self.tail_sampling.as_ref(), self.redaction.as_ref(), self.span_enrichment.as_ref(), &self.span_name_rules, &self.scopes, &export_stats).await?;
+        for (trace_config, scopes) in &self.additional_exporters {
+            // Each exporter has its own processor: batch for OTLP, simple for standard output.
+            builder = with_exporter(builder, trace_config, &auth_context, self.tail_sampling.as_ref(), self.redaction.as_ref(), self.span_enrichment.as_ref(), &self.span_name_rules, scopes, &export_stats).await?;
+        }
+        // Every processor receiving the ended spans gets them renamed, enriched and redacted, not only those of the exporters.
+        let (redaction, enrichment, span_names) = (self.redaction.as_ref(), self.span_enrichment.as_ref(), &self.span_name_rules);
+        for processor in self.span_processors.drain(..) {
+            let processor = SpanNameProcessor::new(BoxedSpanProcessor::new(processor), span_names)?;
+            builder = builder.with_span_processor(RedactionProcessor::new(EnrichmentProcessor::new(processor, enrichment), redaction)?);
+        }
+        if !self.baggage_span_attributes.is_empty() {
+            builder = builder.with_span_processor(BaggageSpanProcessor::new(self.baggage_span_attributes.clone()));
+        }
+        if self.trace_summary {
+            builder = builder.with_span_processor(RedactionProcessor::new(SpanNameProcessor::new(TraceSummaryProcessor::new(), span_names)?, redaction)?);
+        }
+        if self.sampling_decision_logging {
+            builder = builder.with_sampler(LoggingSampler::new(self.trace_config.sampler()));
+        }
+        let exporter = builder.build();
+
+        let log_layer = get_logger(&self.log_config, &self.trace_config, &self.service_name, &auth_context).await?;
+
+        let tracer = match &self.instrumentation_scope {
+            Some(scope) => exporter.tracer_with_scope(scope.clone()),
+            None => exporter.tracer(self.service_name.clone()),
+        };
+
+        let error_flush = self.error_flush.as_ref().map(|config| ErrorFlushLayer::new(exporter.clone(), config.min_interval));
+        let rate_limit = self.log_rate_limit.as_ref().map(|config| RateLimitLayer::new(config.max_events, config.window));
+        let span_log = self.span_logging.then_some(SpanLogLayer);
+        let (layer, logs) = compose_layer(log_layer, tracer, &self.service_name, error_flush, rate_limit, span_log, self.log_redaction.as_ref(), std::mem::take(&mut self.extra_layers), self.exception_recording)?;
+
+        let propagation = match (&self.propagation, &self.trace_config) {
+            _ if !self.propagator => None,
+            (Some(propagation), _) => Some(propagation.clone()),
+            (None, TraceConfig::XRay(_)) => Some(PropagationConfig::from_env_or(PropagationConfig::xray())?),
+            (None, _) => Some(PropagationConfig::from_env()?),
+        };
+
+        if let Some(config) = &self.process_metrics && let Some(meter_provider) = &self.meter_provider {
+            register_process_metrics(&meter_provider.meter(METER_NAME), config.restart_marker.clone()).await;
+        }
+
+        let heartbeat = self.heartbeat.as_ref().map(|config| spawn_heartbeat(config, &exporter));
+
+        let finished_spans = match &self.trace_config {
+            TraceConfig::InMemory(config) => Some(config.store.clone()),
+            _ => None,
+        };
+
+        Ok((OpenTelemetryObject { tracer: exporter, heartbeat, ready, finished_spans, logs, export_stats, propagation, auth_context }, layer))
+    }
+}
+
+
+/// Returns the `OpenTelemetryObject` of the `noop` feature, whose tracer provider discards every span,
+/// with a subscriber layer doing nothing.
+#[cfg(feature = "noop")]
+fn noop_object() -> (OpenTelemetryObject, Box<dyn Layer<Registry> + Send + Sync>) {
+    let (_, ready) = watch::channel(true);
+    let object = OpenTelemetryObject { tracer: SDKTracerProvider::builder().build(), heartbeat: None, ready, finished_spans: None, logs: LogHandles::default(), export_stats: Arc::default(), propagation: None, auth_context: AuthContext::new() };
+    (object, Identity::new().boxed())
 }
 
 
 impl OpenTelemetryObject {
     /// Creates a new `OpenTelemetryObject`.
     ///
-    /// This function initializes the tracer and logger providers based on the provided configuration.
+    /// This function initializes the tracer and logger providers based on the provided configuration,
+    /// with the tail sampling of the `OTEL_TAIL_SAMPLING_*` environment variables, if set.
+    /// Use `OpenTelemetryObject::builder` to configure optional settings.
     ///
     /// # Arguments
     ///
@@ -31,20 +578,162 @@ impl OpenTelemetryObject {
     /// * `trace_config` - The tracing configuration.
     /// * `service_name` - The name of the service.
     pub async fn new(log_config: &LogConfig, trace_config: &TraceConfig, service_name: String) -> Result<Self> {
-        let exporter = get_tracer_provider(trace_config, &service_name).await?;
+        Self::builder(log_config, trace_config, service_name)
+            .with_tail_sampling(TailSamplingConfig::from_env()?)
+            .build()
+            .await
+    }
+
+    /// Returns an isolated, in-memory telemetry pipeline for unit tests.
+    ///
+    /// See `testing::TestTelemetry`. Only available with the `testing` feature.
+    #[cfg(feature = "testing")]
+    pub fn for_tests() -> crate::testing::TestTelemetry {
+        crate::testing::TestTelemetry::new()
+    }
+
+    /// Returns an `OpenTelemetryObjectBuilder` for the provided configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `log_config` - The logging configuration.
+    /// * `trace_config` - The tracing configuration.
+    /// * `service_name` - The name of the service.
+    pub fn builder(log_config: &LogConfig, trace_config: &TraceConfig, service_name: String) -> OpenTelemetryObjectBuilder {
+        OpenTelemetryObjectBuilder::new(log_config, trace_config, service_name)
+    }
+
+    /// Returns the spans exported so far with the in-memory trace configuration, in the order they ended,
+    /// so integration tests can assert on their names, attributes and parent spans.
+    /// Returns an empty list with the other configurations.
+    pub fn finished_spans(&self) -> Vec<SpanData> {
+        self.finished_spans
+            .as_ref()
+            .map(|store| store.0.lock().unwrap_or_else(|e| e.into_inner()).clone())
+            .unwrap_or_default()
+    }
+
+    /// Returns the logger providers of the OTLP log sinks, e.g. to flush the pending log records.
+    pub fn logger_providers(&self) -> &[SdkLoggerProvider] {
+        &self.logs.logger_providers
+    }
+
+    /// Waits until the background initialization is complete, or the startup budget has elapsed.
+    pub async fn ready(&self) {
+        let mut ready = self.ready.clone();
+        let _ = ready.wait_for(|ready| *ready).await;
+    }
 
-        let log_layer = get_logger(log_config, &service_name)?;
+    /// Registers a callback notified whenever an authentication provider of the exporters refreshes its token or
+    /// its credentials are invalidated, e.g. when the collector rejects an export with HTTP 401 or 403.
+    ///
+    /// See `AuthContext::on_credentials_rotated`.
+    pub fn on_credentials_rotated<F>(&self, callback: F) -> CredentialsCallbackHandle
+    where
+        F: Fn(&CredentialsEvent) + Send + Sync + 'static,
+    {
+        self.auth_context.on_credentials_rotated(callback)
+    }
+
+    /// Registers a callback notified whenever a batch of spans of this object is dropped because its export failed,
+    /// so services can alert when telemetry is being lost.
+    ///
+    /// Callbacks are invoked synchronously by the exporting thread, so they should return quickly.
+    /// # Arguments
+    /// * `callback` - The function called with each `ExportErrorEvent`.
+    /// # Returns
+    /// The handle unregistering the callback when dropped.
+    pub fn on_export_error<F>(&self, callback: F) -> ExportErrorCallbackHandle
+    where
+        F: Fn(&ExportErrorEvent) + Send + Sync + 'static,
+    {
+        self.export_stats.on_export_error(Arc::new(callback))
+    }
+
+    /// Returns the text map propagator of the propagation configuration, e.g. to install it with
+    /// `global::set_text_map_propagator` after `build_layer`, or `None` if disabled with `with_propagator`.
+    /// `build` installs it as the global text map propagator.
+    pub fn propagator(&self) -> Option<TextMapCompositePropagator> {
+        self.propagation.as_ref().map(build_propagator)
+    }
 
-        let tracer = exporter.tracer(service_name.clone());
+    /// Returns the number of spans of this object dropped so far because their export failed.
+    /// The `otel.exporter.dropped` counter reports those of the process.
+    pub fn dropped_spans(&self) -> u64 {
+        self.export_stats.dropped_spans()
+    }
 
-        set_logger(log_layer, tracer, &service_name)?;
+    /// Returns the estimated number of spans of this object dropped so far because the queue of the batch
+    /// span processor was full. See `tracer::estimated_queue_drops`.
+    pub fn estimated_queue_drops(&self) -> u64 {
+        self.export_stats.estimated_queue_drops()
+    }
 
-        Ok(OpenTelemetryObject { tracer: exporter })
+    /// Returns the number of batches of spans of this object dropped so far because their export failed.
+    pub fn dropped_batches(&self) -> u64 {
+        self.export_stats.dropped_batches()
     }
 
-    /// Shuts down the tracer provider.
+    /// Stops the heartbeat, if enabled, flushes the pending spans and shuts down the tracer provider,
+    /// then flushes the pending log records and shuts down the logger providers of the OTLP log sinks,
+    /// within `DEFAULT_SHUTDOWN_TIMEOUT`.
+    ///
+    /// See `stop_with_report` for a custom timeout and a report of what was lost.
+    ///
+    /// # Returns
+    ///
+    /// An error if the tracer provider was already shut down, or with the first error of an exporter.
     pub fn stop(&self) -> Result<()> {
-        Ok(self.tracer.shutdown()?)
+        let report = self.stop_with_report(None)?;
+        match report.errors.into_iter().next() {
+            Some(error) => Err(anyhow!("{} failed to stop: {}", error.exporter, error.reason)),
+            None => Ok(()),
+        }
+    }
+
+    /// Stops the telemetry pipelines like `stop`, returning a report of the spans flushed and dropped
+    /// meanwhile and of the errors of each exporter, so services can log what was lost during termination.
+    ///
+    /// The background tasks of the Loki log sinks are not awaited, see `stop_async`.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - The time allowed for the whole shutdown, flushes included. Defaults to `DEFAULT_SHUTDOWN_TIMEOUT`.
+    pub fn stop_with_report(&self, timeout: Option<Duration>) -> Result<ShutdownReport> {
+        let deadline = Instant::now() + timeout.unwrap_or(DEFAULT_SHUTDOWN_TIMEOUT);
+        if let Some(heartbeat) = &self.heartbeat {
+            heartbeat.abort();
+        }
+        let mut report = stop_provider(&self.tracer, &self.export_stats, deadline)?;
+        report.errors.extend(stop_logger_providers(&self.logs.logger_providers, deadline));
+        Ok(report)
+    }
+
+    /// Stops the telemetry pipelines like `stop_with_report`, then waits for the background tasks of the Loki log sinks
+    /// to send their buffered log lines, so the last log lines of the process are not lost.
+    ///
+    /// The flushes and shutdowns run on a blocking thread, so the runtime is not blocked meanwhile.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - The time allowed for the whole shutdown. Defaults to `DEFAULT_SHUTDOWN_TIMEOUT`.
+    pub async fn stop_async(&self, timeout: Option<Duration>) -> Result<ShutdownReport> {
+        let timeout = timeout.unwrap_or(DEFAULT_SHUTDOWN_TIMEOUT);
+        let deadline = Instant::now() + timeout;
+        if let Some(heartbeat) = &self.heartbeat {
+            heartbeat.abort();
+        }
+        let tracer = self.tracer.clone();
+        let export_stats = self.export_stats.clone();
+        let logger_providers = self.logs.logger_providers.clone();
+        let mut report = tokio::task::spawn_blocking(move || -> Result<ShutdownReport> {
+            let mut report = stop_provider(&tracer, &export_stats, deadline)?;
+            report.errors.extend(stop_logger_providers(&logger_providers, deadline));
+            Ok(report)
+        }).await??;
+        let loki_tasks = std::mem::take(&mut *self.logs.loki_tasks.lock().unwrap_or_else(|e| e.into_inner()));
+        report.errors.extend(stop_loki_tasks(loki_tasks, deadline).await);
+        Ok(report)
     }
 }
 
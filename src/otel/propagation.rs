@@ -0,0 +1,24 @@
+use opentelemetry::propagation::{TextMapCompositePropagator, TextMapPropagator};
+use opentelemetry_aws::trace::XrayPropagator;
+use opentelemetry_jaeger_propagator::Propagator as JaegerPropagator;
+use opentelemetry_sdk::propagation::{BaggagePropagator, TraceContextPropagator};
+use opentelemetry_zipkin::{B3Encoding, Propagator as B3Propagator};
+use crate::config::{PropagationConfig, Propagator};
+
+
+/// Returns the composite propagator of the given configuration.
+///
+/// Contexts are injected in every format and extracted from the formats in order, the later ones
+/// overriding the earlier ones when several are present.
+pub fn build_propagator(config: &PropagationConfig) -> TextMapCompositePropagator {
+    TextMapCompositePropagator::new(config.propagators.iter().map(|propagator| -> Box<dyn TextMapPropagator + Send + Sync> {
+        match propagator {
+            Propagator::TraceContext => Box::new(TraceContextPropagator::new()),
+            Propagator::Baggage => Box::new(BaggagePropagator::new()),
+            Propagator::B3 => Box::new(B3Propagator::with_encoding(B3Encoding::SingleHeader)),
+            Propagator::B3Multi => Box::new(B3Propagator::with_encoding(B3Encoding::MultipleHeader)),
+            Propagator::Jaeger => Box::new(JaegerPropagator::new()),
+            Propagator::XRay => Box::new(XrayPropagator::new()),
+        }
+    }).collect())
+}
@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::callsite::Identifier;
+use tracing::{Event, Metadata, Subscriber};
+use tracing::dispatcher::{Dispatch, WeakDispatch};
+use tracing_subscriber::layer::{Context, Layer};
+
+
+/// Target of the summaries of the suppressed events, which are never rate limited.
+const SUMMARY_TARGET: &str = "rust_otel_setup::rate_limit";
+
+/// Minimum interval between two checks for ended windows, so a tiny window does not keep a thread busy.
+const MIN_SUMMARY_INTERVAL: Duration = Duration::from_millis(100);
+
+
+/// The events recorded at one callsite in the current window.
+struct Window {
+    metadata: &'static Metadata<'static>,
+    started: Instant,
+    count: u32,
+    suppressed: u64,
+}
+
+
+/// The windows of the callsites, with the suppressed events of the ended windows not summarized yet.
+#[derive(Default)]
+struct Windows {
+    current: HashMap<Identifier, Window>,
+    ended: Vec<(&'static Metadata<'static>, u64)>,
+}
+
+
+/// A subscriber layer that rate limits identical events, so a hot error loop cannot flood the log backends.
+///
+/// Events are identical when they come from the same callsite, i.e. the same target and message template.
+/// Past the maximum number of events per window, the events of a callsite are suppressed for every sink.
+/// Once the window ends, a background thread records a "suppressed N similar messages" WARN event through
+/// the subscriber of the layer, outside the dispatch of any other event, so the summary goes through the
+/// filters of each sink like any other event.
+pub struct RateLimitLayer {
+    max_events: u32,
+    window: Duration,
+    windows: Arc<Mutex<Windows>>,
+    summaries: OnceLock<()>,
+}
+
+
+impl RateLimitLayer {
+    /// Creates a new instance of `RateLimitLayer`.
+    /// # Arguments
+    /// * `max_events` - The maximum number of identical events per window.
+    /// * `window` - The duration of the window.
+    /// # Returns
+    /// A new `RateLimitLayer` instance.
+    pub fn new(max_events: u32, window: Duration) -> Self {
+        Self { max_events, window, windows: Arc::new(Mutex::new(Windows::default())), summaries: OnceLock::new() }
+    }
+
+    /// Records an event of the given callsite, returning whether it is kept.
+    fn record(&self, metadata: &'static Metadata<'static>) -> bool {
+        let mut windows = self.windows.lock().unwrap_or_else(|e| e.into_inner());
+        let windows = &mut *windows;
+        let now = Instant::now();
+        let window = windows.current.entry(metadata.callsite())
+            .or_insert(Window { metadata, started: now, count: 0, suppressed: 0 });
+        if now.duration_since(window.started) >= self.window {
+            if window.suppressed > 0 {
+                windows.ended.push((metadata, window.suppressed));
+            }
+            *window = Window { metadata, started: now, count: 0, suppressed: 0 };
+        }
+        if window.count >= self.max_events {
+            window.suppressed += 1;
+            return false;
+        }
+        window.count += 1;
+        true
+    }
+}
+
+
+/// Takes the suppressed events of the ended windows, forgetting the ended windows.
+fn take_ended(windows: &Mutex<Windows>, window: Duration) -> Vec<(&'static Metadata<'static>, u64)> {
+    let mut windows = windows.lock().unwrap_or_else(|e| e.into_inner());
+    let windows = &mut *windows;
+    let mut ended = std::mem::take(&mut windows.ended);
+    windows.current.retain(|_, current| {
+        if current.started.elapsed() < window {
+            return true;
+        }
+        if current.suppressed > 0 {
+            ended.push((current.metadata, current.suppressed));
+        }
+        false
+    });
+    ended
+}
+
+
+/// Records the summaries of the ended windows through the subscriber of the layer, once per window,
+/// until the subscriber is dropped.
+fn record_summaries(windows: Arc<Mutex<Windows>>, window: Duration, subscriber: WeakDispatch) {
+    loop {
+        std::thread::sleep(window.max(MIN_SUMMARY_INTERVAL));
+        let Some(subscriber) = subscriber.upgrade() else {
+            return;
+        };
+        let ended = take_ended(&windows, window);
+        tracing::dispatcher::with_default(&subscriber, || {
+            for (metadata, suppressed) in ended {
+                tracing::warn!(
+                    target: SUMMARY_TARGET,
+                    suppressed_target = metadata.target(),
+                    suppressed_callsite = metadata.name(),
+                    suppressed,
+                    "suppressed {} similar messages",
+                    suppressed,
+                );
+            }
+        });
+    }
+}
+
+
+/// Implementation of the Layer trait for RateLimitLayer
+impl<S: Subscriber> Layer<S> for RateLimitLayer {
+    fn on_register_dispatch(&self, subscriber: &Dispatch) {
+        self.summaries.get_or_init(|| {
+            let (windows, window, subscriber) = (self.windows.clone(), self.window, subscriber.downgrade());
+            // Without the thread, the events are still rate limited, only not summarized.
+            let _ = std::thread::Builder::new()
+                .name("otel-rate-limit".to_string())
+                .spawn(move || record_summaries(windows, window, subscriber));
+        });
+    }
+
+    fn event_enabled(&self, event: &Event<'_>, _ctx: Context<'_, S>) -> bool {
+        let metadata = event.metadata();
+        if metadata.target() == SUMMARY_TARGET {
+            return true;
+        }
+        self.record(metadata)
+    }
+}
@@ -0,0 +1,174 @@
+use std::sync::Mutex;
+use std::sync::mpsc::RecvTimeoutError;
+use std::time::{Duration, Instant};
+use anyhow::Result;
+use opentelemetry_sdk::error::{OTelSdkError, OTelSdkResult};
+use opentelemetry_sdk::logs::SdkLoggerProvider;
+use opentelemetry_sdk::trace::SdkTracerProvider as SDKTracerProvider;
+use tokio::task::JoinHandle;
+use tracing_loki::BackgroundTaskController;
+#[cfg(not(feature = "noop"))]
+use tracing_appender::non_blocking::WorkerGuard;
+use crate::tracer::ExportErrorEvent;
+use crate::tracer::export_error::ExportStats;
+
+
+/// Default time allowed for the shutdown of the tracer provider.
+pub const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+
+/// Error of an exporter during shutdown.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExporterError {
+    /// The name of the exporter, e.g. `grpc` or `zipkin`, or `tracer_provider` and `logger_provider` for flush
+    /// and shutdown failures.
+    pub exporter: String,
+    /// A description of the failure.
+    pub reason: String,
+}
+
+
+/// Report of what was flushed and lost while stopping the telemetry pipelines.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShutdownReport {
+    /// The number of spans exported while stopping.
+    pub spans_flushed: u64,
+    /// The number of spans dropped while stopping.
+    pub spans_dropped: u64,
+    /// The errors of the exporters while stopping, in the order they happened.
+    pub errors: Vec<ExporterError>,
+}
+
+
+/// Resources of the log layers that must outlive their construction.
+#[derive(Default)]
+pub(crate) struct LogHandles {
+    /// The logger providers of the OTLP layers, flushed on ERROR events and when stopping.
+    pub(crate) logger_providers: Vec<SdkLoggerProvider>,
+    /// The guards flushing the non-blocking writers of the standard output, file and syslog layers when dropped.
+    #[cfg(not(feature = "noop"))]
+    pub(crate) guards: Vec<WorkerGuard>,
+    /// The background tasks sending the log lines of the Loki layers, with their shutdown signal.
+    /// Taken when stopping.
+    pub(crate) loki_tasks: Mutex<Vec<(BackgroundTaskController, JoinHandle<()>)>>,
+}
+
+
+impl From<ExportErrorEvent> for ExporterError {
+    fn from(event: ExportErrorEvent) -> Self {
+        ExporterError { exporter: event.exporter, reason: event.reason }
+    }
+}
+
+
+/// Runs the flush of a provider on its own thread and stops waiting for it at the deadline, since the
+/// `force_flush` of the SDK providers takes no timeout.
+fn flush_until(flush: impl FnOnce() -> OTelSdkResult + Send + 'static, deadline: Instant) -> OTelSdkResult {
+    let (sender, receiver) = std::sync::mpsc::channel();
+    std::thread::Builder::new()
+        .name("otel-flush".to_string())
+        .spawn(move || {
+            let _ = sender.send(flush());
+        })
+        .map_err(|err| OTelSdkError::InternalFailure(err.to_string()))?;
+    let timeout = deadline.saturating_duration_since(Instant::now());
+    match receiver.recv_timeout(timeout) {
+        Ok(result) => result,
+        Err(RecvTimeoutError::Timeout) => Err(OTelSdkError::Timeout(timeout)),
+        Err(RecvTimeoutError::Disconnected) => Err(OTelSdkError::InternalFailure("the flush panicked".to_string())),
+    }
+}
+
+
+/// Flushes the pending spans of a tracer provider, then shuts it down, reporting the spans flushed
+/// and dropped meanwhile and the errors of each exporter.
+///
+/// # Arguments
+///
+/// * `provider` - The tracer provider.
+/// * `stats` - The counters of the exported and dropped spans of the tracer provider.
+/// * `deadline` - The instant by which the flush and the shutdown must have completed.
+///
+/// # Returns
+///
+/// The shutdown report, or an error if the provider was already shut down.
+pub(crate) fn stop_provider(provider: &SDKTracerProvider, stats: &ExportStats, deadline: Instant) -> Result<ShutdownReport> {
+    let flushed_before = stats.exported_spans();
+    let dropped_before = stats.dropped_spans();
+    stats.start_capture();
+
+    let flushed = provider.clone();
+    let flush = flush_until(move || flushed.force_flush(), deadline);
+    let shutdown = provider.shutdown_with_timeout(deadline.saturating_duration_since(Instant::now()));
+
+    let mut errors: Vec<ExporterError> = stats.take_captured().into_iter().map(ExporterError::from).collect();
+    if let Err(OTelSdkError::AlreadyShutdown) = shutdown {
+        return Err(OTelSdkError::AlreadyShutdown.into());
+    }
+    for result in [flush, shutdown] {
+        if let Err(err) = result {
+            errors.push(ExporterError { exporter: "tracer_provider".to_string(), reason: err.to_string() });
+        }
+    }
+    Ok(ShutdownReport {
+        spans_flushed: stats.exported_spans() - flushed_before,
+        spans_dropped: stats.dropped_spans() - dropped_before,
+        errors,
+    })
+}
+
+
+/// Flushes the pending log records of the logger providers, then shuts them down.
+///
+/// # Arguments
+///
+/// * `providers` - The logger providers.
+/// * `deadline` - The instant by which every flush and shutdown must have completed, shared by all the providers.
+///
+/// # Returns
+///
+/// The errors of the flushes and shutdowns. Providers already shut down are skipped.
+pub(crate) fn stop_logger_providers(providers: &[SdkLoggerProvider], deadline: Instant) -> Vec<ExporterError> {
+    let mut errors = Vec::new();
+    for provider in providers {
+        let flushed = provider.clone();
+        let flush = flush_until(move || flushed.force_flush(), deadline);
+        let shutdown = provider.shutdown_with_timeout(deadline.saturating_duration_since(Instant::now()));
+        for result in [flush, shutdown] {
+            match result {
+                Ok(()) | Err(OTelSdkError::AlreadyShutdown) => {},
+                Err(err) => errors.push(ExporterError { exporter: "logger_provider".to_string(), reason: err.to_string() }),
+            }
+        }
+    }
+    errors
+}
+
+
+/// Signals the background tasks of the Loki log sinks to send their buffered log lines and stop,
+/// then waits for them to complete.
+///
+/// # Arguments
+///
+/// * `tasks` - The background tasks, with their shutdown signal.
+/// * `deadline` - The instant by which every task must have completed, shared by all the tasks.
+///
+/// # Returns
+///
+/// The errors of the tasks which failed or did not complete in time.
+pub(crate) async fn stop_loki_tasks(tasks: Vec<(BackgroundTaskController, JoinHandle<()>)>, deadline: Instant) -> Vec<ExporterError> {
+    let mut errors = Vec::new();
+    for (controller, task) in tasks {
+        let stop = async {
+            controller.shutdown().await;
+            task.await
+        };
+        let reason = match tokio::time::timeout_at(deadline.into(), stop).await {
+            Ok(Ok(())) => continue,
+            Ok(Err(err)) => err.to_string(),
+            Err(_) => "the background task did not complete in time".to_string(),
+        };
+        errors.push(ExporterError { exporter: "loki".to_string(), reason });
+    }
+    errors
+}
@@ -0,0 +1,81 @@
+use std::time::Instant;
+use tracing::span::{Attributes, Id};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+
+/// Target of the events recording the start and end of the spans.
+const SPAN_LOG_TARGET: &str = "rust_otel_setup::span";
+
+
+/// The timing and outcome of a span, stored in its extensions.
+struct SpanTiming {
+    started: Instant,
+    entered: bool,
+    error: bool,
+}
+
+
+/// A subscriber layer that records an INFO event when a span starts and ends, with its name and,
+/// at its end, its duration and status, giving basic request logs to services instrumented with spans only.
+///
+/// The status of a span is `error` if an ERROR event was recorded in it, `ok` otherwise. The events have
+/// the `rust_otel_setup::span` target and the span as parent, so they are filtered like other events
+/// and carry its trace context.
+///
+/// The start of a span is recorded when it is first entered, and its end when it closes, rather than while
+/// the span is being created, whose per-layer filter state a nested event would reset. The layer must run
+/// before the span exporting layer, which ends the OpenTelemetry span of a closed span.
+pub struct SpanLogLayer;
+
+
+/// Implementation of the Layer trait for SpanLogLayer
+impl<S> Layer<S> for SpanLogLayer
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    fn on_new_span(&self, _attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        span.extensions_mut().insert(SpanTiming { started: Instant::now(), entered: false, error: false });
+    }
+
+    fn on_enter(&self, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let first = match span.extensions_mut().get_mut::<SpanTiming>() {
+            Some(timing) => !std::mem::replace(&mut timing.entered, true),
+            None => false,
+        };
+        if first {
+            tracing::info!(target: SPAN_LOG_TARGET, parent: id, span_name = span.name(), "span started");
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        if *event.metadata().level() != Level::ERROR {
+            return;
+        }
+        let Some(span) = ctx.event_span(event) else {
+            return;
+        };
+        if let Some(timing) = span.extensions_mut().get_mut::<SpanTiming>() {
+            timing.error = true;
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let Some(timing) = span.extensions_mut().remove::<SpanTiming>() else {
+            return;
+        };
+        let duration_ms = timing.started.elapsed().as_secs_f64() * 1000.0;
+        let status = if timing.error { "error" } else { "ok" };
+        tracing::info!(target: SPAN_LOG_TARGET, parent: &id, span_name = span.name(), duration_ms, status, "span ended");
+    }
+}
@@ -0,0 +1,50 @@
+#[cfg(not(feature = "noop"))]
+use std::sync::Arc;
+use std::time::Duration;
+#[cfg(not(feature = "noop"))]
+use tokio::sync::watch;
+#[cfg(not(feature = "noop"))]
+use crate::auth::GetToken;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::grpc::interceptor::HeaderCache;
+
+
+/// Default time allowed for the background initialization before the pipelines are reported ready.
+pub const DEFAULT_STARTUP_BUDGET: Duration = Duration::from_secs(5);
+
+
+/// Retrieves the first authentication headers of each token provider concurrently, in the background.
+///
+/// Providers cache their tokens, and the headers are stored in the cache of the gRPC interceptors of their
+/// configuration, so the exporters find them ready on their first export. Retrieval failures are recorded
+/// by the providers themselves, and retried by the interceptors.
+///
+/// # Arguments
+///
+/// * `providers` - The token providers to warm up, with the header cache of their configuration.
+/// * `budget` - The time after which the initialization is reported complete, even if some
+///   providers have not finished.
+///
+/// # Returns
+///
+/// A receiver whose value becomes `true` once the initialization is complete or the budget has elapsed.
+#[cfg(not(feature = "noop"))]
+pub fn spawn_startup(providers: Vec<(Arc<dyn GetToken>, Arc<HeaderCache>)>, budget: Duration) -> watch::Receiver<bool> {
+    let (ready_tx, ready_rx) = watch::channel(false);
+    tokio::spawn(async move {
+        let tasks: Vec<_> = providers
+            .into_iter()
+            .map(|(provider, cache)| tokio::spawn(async move { cache.store(provider.get_auth_headers().await) }))
+            .collect();
+        let warm_up = async {
+            for task in tasks {
+                let _ = task.await;
+            }
+        };
+        if tokio::time::timeout(budget, warm_up).await.is_err() {
+            tracing::warn!(?budget, "OpenTelemetry startup budget exceeded, authentication continues in the background");
+        }
+        let _ = ready_tx.send(true);
+    });
+    ready_rx
+}
@@ -0,0 +1,171 @@
+use std::io::Write;
+use std::str::FromStr;
+use anyhow::{anyhow, Result};
+use syslog::{Facility, Formatter3164, Formatter5424, LogFormat, LoggerBackend, Severity};
+use tracing::{Level, Metadata};
+use tracing_appender::non_blocking::{NonBlocking, WorkerGuard};
+use tracing_subscriber::fmt::MakeWriter;
+use crate::config::{SyslogFormat, SyslogLogConfig};
+use crate::otel::file_log::non_blocking;
+
+
+/// The formatter of the syslog messages.
+enum SyslogFormatter {
+    RFC3164(Formatter3164),
+    RFC5424(Formatter5424),
+}
+
+
+/// The connection to the syslog server, with the formatter of its messages, written by the background
+/// thread of the non-blocking writer.
+///
+/// Each write is one message, prefixed with the byte of its severity.
+struct SyslogSender {
+    backend: LoggerBackend,
+    formatter: SyslogFormatter,
+}
+
+
+/// Implementation of the Write trait for SyslogSender
+impl Write for SyslogSender {
+    /// Sends a message, ignoring failures so a syslog server going away does not stop the background thread.
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let Some((&severity, message)) = buf.split_first() else {
+            return Ok(0);
+        };
+        let severity = severity_from_byte(severity);
+        let message = String::from_utf8_lossy(message);
+        let _ = match &self.formatter {
+            SyslogFormatter::RFC3164(formatter) => formatter.format(&mut self.backend, severity, message.as_ref()),
+            SyslogFormatter::RFC5424(formatter) => formatter.format(&mut self.backend, severity, (0, Default::default(), message.as_ref())),
+        };
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        let _ = self.backend.flush();
+        Ok(())
+    }
+}
+
+
+/// A writer factory sending each formatted `tracing` event as one syslog message, with the severity of its level.
+///
+/// The messages are queued for a background thread sending them to the syslog server, so a slow or
+/// unreachable server cannot stall the threads recording the events.
+#[derive(Clone)]
+pub struct SyslogMakeWriter {
+    writer: NonBlocking,
+}
+
+
+/// A writer buffering one formatted event, queued as a syslog message when dropped.
+pub struct SyslogWriter {
+    writer: NonBlocking,
+    severity: Severity,
+    buffer: Vec<u8>,
+}
+
+
+/// Implementation of the Write trait for SyslogWriter
+impl Write for SyslogWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+
+/// Implementation of the Drop trait for SyslogWriter
+impl Drop for SyslogWriter {
+    fn drop(&mut self) {
+        let message = self.buffer.trim_ascii_end();
+        if !message.is_empty() {
+            let mut line = Vec::with_capacity(message.len() + 1);
+            line.push(self.severity as u8);
+            line.extend_from_slice(message);
+            // Queued as a single write, so the background thread receives the whole message at once.
+            let _ = self.writer.write(&line);
+        }
+    }
+}
+
+
+/// Returns the syslog severity of a `tracing` level.
+fn severity(level: &Level) -> Severity {
+    match *level {
+        Level::ERROR => Severity::LOG_ERR,
+        Level::WARN => Severity::LOG_WARNING,
+        Level::INFO => Severity::LOG_INFO,
+        Level::DEBUG | Level::TRACE => Severity::LOG_DEBUG,
+    }
+}
+
+
+/// Returns the syslog severity of the byte prefixing a queued message.
+fn severity_from_byte(byte: u8) -> Severity {
+    match byte {
+        b if b == Severity::LOG_ERR as u8 => Severity::LOG_ERR,
+        b if b == Severity::LOG_WARNING as u8 => Severity::LOG_WARNING,
+        b if b == Severity::LOG_DEBUG as u8 => Severity::LOG_DEBUG,
+        _ => Severity::LOG_INFO,
+    }
+}
+
+
+/// Implementation of the MakeWriter trait for SyslogMakeWriter
+impl<'a> MakeWriter<'a> for SyslogMakeWriter {
+    type Writer = SyslogWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        SyslogWriter { writer: self.writer.clone(), severity: Severity::LOG_INFO, buffer: Vec::new() }
+    }
+
+    fn make_writer_for(&'a self, meta: &Metadata<'_>) -> Self::Writer {
+        SyslogWriter { writer: self.writer.clone(), severity: severity(meta.level()), buffer: Vec::new() }
+    }
+}
+
+
+/// Connects to the syslog server of the configuration.
+fn connect(endpoint: Option<&str>) -> syslog::Result<LoggerBackend> {
+    let logger = match endpoint.and_then(|endpoint| endpoint.split_once("://")) {
+        Some(("udp", address)) => syslog::udp((), "0.0.0.0:0", address)?,
+        Some(("tcp", address)) => syslog::tcp((), address)?,
+        Some((_, path)) => syslog::unix_custom((), path)?,
+        None => syslog::unix(())?,
+    };
+    Ok(logger.backend)
+}
+
+
+/// Returns the writer factory of the syslog sink, with the guard sending the queued messages when dropped.
+///
+/// The service name is used as the application name of the messages.
+/// # Arguments
+/// * `config` - The syslog configuration.
+/// * `service_name` - The name of the service.
+/// # Returns
+/// The writer factory and its guard, or an error if the syslog server cannot be reached.
+pub(crate) fn syslog_writer(config: &SyslogLogConfig, service_name: &str) -> Result<(SyslogMakeWriter, WorkerGuard)> {
+    let facility = Facility::from_str(&config.facility).map_err(|_| anyhow!("Unsupported syslog facility: {}", config.facility))?;
+    let formatter = match config.format {
+        SyslogFormat::RFC3164 => SyslogFormatter::RFC3164(Formatter3164 {
+            facility,
+            process: service_name.to_string(),
+            ..Default::default()
+        }),
+        SyslogFormat::RFC5424 => SyslogFormatter::RFC5424(Formatter5424 {
+            facility,
+            process: service_name.to_string(),
+            ..Default::default()
+        }),
+    };
+    let backend = connect(config.endpoint.as_deref()).map_err(|err| anyhow!("Failed to connect to syslog: {}", err))?;
+    let (writer, guard) = non_blocking(SyslogSender { backend, formatter }, &config.buffer);
+    Ok((SyslogMakeWriter { writer }, guard))
+}
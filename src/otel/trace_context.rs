@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex, OnceLock};
+use tracing::callsite::{Callsite, Identifier};
+use tracing::field::FieldSet;
+use tracing::metadata::Kind;
+use tracing::subscriber::Interest;
+use tracing::{Event, Metadata, Subscriber};
+use tracing_opentelemetry::OtelData;
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+use crate::otel::event_rewrite::{collect_fields, FieldValue, RewriteEvent};
+
+
+/// Name of the field holding the trace ID.
+const TRACE_ID: &str = "trace_id";
+
+
+/// Name of the field holding the span ID.
+const SPAN_ID: &str = "span_id";
+
+
+/// Maximum number of callsites whose events get the trace context, bounding the memory of their metadata.
+const MAX_CALLSITES: usize = 4096;
+
+
+/// The metadata of the events with the trace context, by callsite of the original events.
+static CALLSITES: LazyLock<Mutex<HashMap<Identifier, &'static Metadata<'static>>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+
+/// The callsite of the events of another callsite with the trace context.
+struct TraceContextCallsite {
+    metadata: OnceLock<Metadata<'static>>,
+}
+
+
+/// Implementation of the Callsite trait for TraceContextCallsite
+impl Callsite for TraceContextCallsite {
+    fn set_interest(&self, _interest: Interest) {}
+
+    fn metadata(&self) -> &Metadata<'_> {
+        self.metadata.get().expect("the metadata is set when the callsite is created")
+    }
+}
+
+
+/// Returns the metadata of the events of a callsite with the `trace_id` and `span_id` fields added.
+///
+/// The metadata is created once per callsite, as the metadata of events must be static, and never freed.
+/// Past `MAX_CALLSITES` callsites, `None` is returned for the new ones, so dynamically created callsites
+/// cannot grow the memory without bound.
+fn with_trace_context(metadata: &'static Metadata<'static>) -> Option<&'static Metadata<'static>> {
+    let mut callsites = CALLSITES.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(metadata) = callsites.get(&metadata.callsite()) {
+        return Some(*metadata);
+    }
+    if callsites.len() >= MAX_CALLSITES {
+        return None;
+    }
+    Some(*callsites.entry(metadata.callsite()).or_insert_with(|| {
+        let names: Vec<&'static str> = metadata.fields().iter().map(|field| field.name()).chain([TRACE_ID, SPAN_ID]).collect();
+        let callsite: &'static TraceContextCallsite = Box::leak(Box::new(TraceContextCallsite { metadata: OnceLock::new() }));
+        let fields = FieldSet::new(Box::leak(names.into_boxed_slice()), Identifier(callsite));
+        callsite.metadata.get_or_init(|| Metadata::new(
+            metadata.name(),
+            metadata.target(),
+            *metadata.level(),
+            metadata.file(),
+            metadata.line(),
+            metadata.module_path(),
+            fields,
+            Kind::EVENT,
+        ))
+    }))
+}
+
+
+/// The addition of the `trace_id` and `span_id` fields to the events recorded inside a span, so operators
+/// can pivot from a log line to its trace.
+///
+/// The IDs are those of the OpenTelemetry span of the current `tracing` span. Events outside of a span,
+/// or with a field named `trace_id` already, are passed unchanged, as are the events of the callsites past
+/// the first 4096.
+///
+/// The IDs are fields of the events, so the Loki sink sends them in the JSON log line, where the `json`
+/// parser of LogQL extracts them, rather than as structured metadata, which its push client does not support.
+pub(crate) struct TraceContext;
+
+
+/// Implementation of the RewriteEvent trait for TraceContext
+impl RewriteEvent for TraceContext {
+    fn rewrite<S>(&self, event: &Event<'_>, ctx: &Context<'_, S>) -> Option<(&'static Metadata<'static>, Vec<(&'static str, FieldValue)>)>
+    where
+        S: Subscriber + for<'span> LookupSpan<'span>,
+    {
+        if event.metadata().fields().field(TRACE_ID).is_some() {
+            return None;
+        }
+        let (trace_id, span_id) = {
+            let span = ctx.event_span(event)?;
+            let extensions = span.extensions();
+            let data = extensions.get::<OtelData>()?;
+            (data.trace_id()?, data.span_id()?)
+        };
+        let metadata = with_trace_context(event.metadata())?;
+        let mut fields = collect_fields(event);
+        fields.push((TRACE_ID, FieldValue::Str(trace_id.to_string())));
+        fields.push((SPAN_ID, FieldValue::Str(span_id.to_string())));
+        Some((metadata, fields))
+    }
+}
@@ -0,0 +1,124 @@
+//! # Process Module
+//!
+//! This module provides helpers to trace the execution of subprocesses.
+use std::collections::HashMap;
+use std::io;
+use std::process::{Command, ExitStatus, Output};
+use opentelemetry::propagation::TextMapPropagator;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use tracing::field::Empty;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+
+/// Replacement of redacted arguments.
+const REDACTED: &str = "[REDACTED]";
+
+
+/// Enum representing the possible policies for recording the arguments of a command.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub enum ArgumentPolicy {
+    /// Record the arguments unchanged.
+    Record,
+    /// Record the arguments, redacting the values of the listed flags, e.g. `--password`.
+    /// Both `--flag value` and `--flag=value` forms are redacted.
+    RedactFlags(Vec<String>),
+    /// Record only the number of arguments.
+    #[default]
+    RedactAll,
+}
+
+
+impl ArgumentPolicy {
+    /// Returns the arguments as recorded in the span.
+    fn apply(&self, args: &[String]) -> String {
+        match self {
+            ArgumentPolicy::Record => args.join(" "),
+            ArgumentPolicy::RedactFlags(flags) => {
+                let mut redacted = Vec::with_capacity(args.len());
+                let mut redact_next = false;
+                for arg in args {
+                    if redact_next {
+                        redacted.push(REDACTED.to_string());
+                        redact_next = false;
+                    } else if let Some((flag, _)) = arg.split_once('=').filter(|(flag, _)| flags.iter().any(|f| f == flag)) {
+                        redacted.push(format!("{}={}", flag, REDACTED));
+                    } else {
+                        redact_next = flags.iter().any(|f| f == arg);
+                        redacted.push(arg.clone());
+                    }
+                }
+                redacted.join(" ")
+            },
+            ArgumentPolicy::RedactAll => format!("<{} arguments>", args.len()),
+        }
+    }
+}
+
+
+/// Runs a command to completion in a `process.exec` span, collecting its output.
+///
+/// The span records the program, the arguments according to `policy` and the exit code, and
+/// its duration is the duration of the subprocess. The trace context of the span is passed
+/// to the child in the `TRACEPARENT` and `TRACESTATE` environment variables.
+///
+/// # Arguments
+///
+/// * `command` - The command to run.
+/// * `policy` - How the arguments are recorded.
+pub fn output(command: &mut Command, policy: &ArgumentPolicy) -> io::Result<Output> {
+    run(command, policy, |command| command.output().map(|output| (output.status, output)))
+}
+
+
+/// Runs a command to completion in a `process.exec` span, returning its exit status.
+///
+/// See `output` for the recorded attributes.
+///
+/// # Arguments
+///
+/// * `command` - The command to run.
+/// * `policy` - How the arguments are recorded.
+pub fn status(command: &mut Command, policy: &ArgumentPolicy) -> io::Result<ExitStatus> {
+    run(command, policy, |command| command.status().map(|status| (status, status)))
+}
+
+
+/// Runs a command in a `process.exec` span, using `execute` to spawn and wait for it.
+fn run<T>(command: &mut Command, policy: &ArgumentPolicy, execute: impl FnOnce(&mut Command) -> io::Result<(ExitStatus, T)>) -> io::Result<T> {
+    let program = command.get_program().to_string_lossy().into_owned();
+    let args: Vec<String> = command.get_args().map(|arg| arg.to_string_lossy().into_owned()).collect();
+    let span = tracing::info_span!(
+        "process.exec",
+        process.command = %program,
+        process.command_args = %policy.apply(&args),
+        process.exit_code = Empty,
+        error = Empty,
+    );
+    let _entered = span.enter();
+
+    let mut carrier = HashMap::new();
+    TraceContextPropagator::new().inject_context(&span.context(), &mut carrier);
+    if let Some(traceparent) = carrier.get("traceparent") {
+        command.env("TRACEPARENT", traceparent);
+    }
+    if let Some(tracestate) = carrier.get("tracestate").filter(|state| !state.is_empty()) {
+        command.env("TRACESTATE", tracestate);
+    }
+
+    match execute(command) {
+        Ok((status, result)) => {
+            if let Some(code) = status.code() {
+                span.record("process.exit_code", code);
+            }
+            if !status.success() {
+                span.record("error", true);
+            }
+            Ok(result)
+        },
+        Err(err) => {
+            span.record("error", true);
+            tracing::error!(error = %err, "Failed to execute {}", program);
+            Err(err)
+        },
+    }
+}
@@ -1,21 +1,26 @@
 use std::sync::OnceLock;
+use opentelemetry::KeyValue;
 use opentelemetry_sdk::Resource;
+use crate::config::read_env_var;
 
 
 /// Returns a singleton `Resource` instance.
 ///
-/// The resource is initialized with the service name.
+/// The resource is initialized with the service name, and the service version of the `OTEL_SERVICE_VERSION`
+/// environment variable, if set.
 ///
 /// # Arguments
 ///
 /// * `service_name` - The name of the service.
-pub fn get_resource(service_name: &String) -> Resource {
+pub fn get_resource(service_name: &str) -> Resource {
     static RESOURCE: OnceLock<Resource> = OnceLock::new();
     RESOURCE
         .get_or_init(|| {
-            Resource::builder()
-                .with_service_name(service_name.clone())
-                .build()
+            let builder = Resource::builder().with_service_name(service_name.to_string());
+            match read_env_var("OTEL_SERVICE_VERSION") {
+                Ok(version) => builder.with_attribute(KeyValue::new("service.version", version)).build(),
+                Err(_) => builder.build(),
+            }
         })
         .clone()
 }
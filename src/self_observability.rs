@@ -0,0 +1,150 @@
+#[cfg(not(feature = "noop"))]
+use std::path::{Path, PathBuf};
+#[cfg(not(feature = "noop"))]
+use std::time::{Duration, Instant};
+use opentelemetry::{global, KeyValue};
+#[cfg(not(feature = "noop"))]
+use opentelemetry::metrics::{Counter, Gauge, Meter};
+
+
+/// Name of the meter used for the crate's self-observability metrics.
+pub const METER_NAME: &str = "rust-otel-setup";
+
+
+/// Records an OTLP partial success response.
+///
+/// A warning is emitted and the `otel.exporter.rejected` counter of the global meter provider is
+/// incremented by the number of rejected items, labelled with the signal.
+///
+/// # Arguments
+///
+/// * `signal` - The signal of the export, e.g. "traces" or "logs".
+/// * `rejected` - The number of rejected spans or log records.
+/// * `message` - The error message returned by the collector.
+pub fn record_partial_success(signal: &'static str, rejected: i64, message: &str) {
+    tracing::warn!(signal, rejected, message, "OTLP export partially rejected by the collector");
+    global::meter(METER_NAME)
+        .u64_counter("otel.exporter.rejected")
+        .with_description("Number of items rejected by the collector in partially successful exports")
+        .build()
+        .add(rejected.max(0) as u64, &[KeyValue::new("signal", signal)]);
+}
+
+
+/// Records dropped spans.
+///
+/// The `otel.exporter.dropped` counter of the global meter provider is incremented by the number
+/// of dropped spans, labelled with the reason.
+///
+/// # Arguments
+///
+/// * `count` - The number of dropped spans.
+/// * `reason` - Why the spans were dropped, e.g. "export_failed", or "queue_full_estimated" for the
+///   drops of a full queue, which are estimated.
+#[cfg(not(feature = "noop"))]
+pub fn record_dropped_spans(count: u64, reason: &'static str) {
+    global::meter(METER_NAME)
+        .u64_counter("otel.exporter.dropped")
+        .with_description("Number of spans dropped before reaching the collector")
+        .build()
+        .add(count, &[KeyValue::new("reason", reason)]);
+}
+
+
+/// Records a heartbeat.
+///
+/// The `otel.heartbeat` counter of the global meter provider is incremented by one.
+#[cfg(not(feature = "noop"))]
+pub fn record_heartbeat() {
+    global::meter(METER_NAME)
+        .u64_counter("otel.heartbeat")
+        .with_description("Number of heartbeats emitted by the service")
+        .build()
+        .add(1, &[]);
+}
+
+
+/// The instruments of the batch span processor queue, created once per processor.
+#[cfg(not(feature = "noop"))]
+#[derive(Debug)]
+pub struct QueueMetrics {
+    high_water_mark: Gauge<u64>,
+    time_at_capacity: Counter<f64>,
+}
+
+
+#[cfg(not(feature = "noop"))]
+impl QueueMetrics {
+    /// Creates the `otel.exporter.queue.high_water_mark` gauge and the `otel.exporter.queue.time_at_capacity`
+    /// counter of the global meter provider.
+    pub fn new() -> Self {
+        let meter = global::meter(METER_NAME);
+        let high_water_mark = meter.u64_gauge("otel.exporter.queue.high_water_mark")
+            .with_description("Highest number of spans queued by the batch span processor")
+            .build();
+        let time_at_capacity = meter.f64_counter("otel.exporter.queue.time_at_capacity")
+            .with_description("Time the batch span processor queue spent full")
+            .with_unit("s")
+            .build();
+        Self { high_water_mark, time_at_capacity }
+    }
+
+    /// Records the state of the batch span processor queue.
+    ///
+    /// # Arguments
+    ///
+    /// * `high_water_mark` - The highest number of spans queued so far.
+    /// * `time_at_capacity` - The time the queue spent full since the last call.
+    pub fn record(&self, high_water_mark: u64, time_at_capacity: Duration) {
+        self.high_water_mark.record(high_water_mark, &[]);
+        self.time_at_capacity.add(time_at_capacity.as_secs_f64(), &[]);
+    }
+}
+
+
+/// Reads the number of previous starts from the restart marker file and persists the current start.
+///
+/// A missing or unreadable marker counts as no previous start.
+#[cfg(not(feature = "noop"))]
+fn persist_start(marker: &Path) -> u64 {
+    let previous = std::fs::read_to_string(marker).ok()
+        .and_then(|content| content.trim().parse().ok())
+        .unwrap_or(0u64);
+    if let Err(err) = std::fs::write(marker, (previous + 1).to_string()) {
+        tracing::warn!(error = %err, marker = %marker.display(), "Failed to persist the restart marker");
+    }
+    previous
+}
+
+
+/// Registers the process metrics.
+///
+/// The `process.uptime` gauge of the meter reports the time since this call, and the `process.starts`
+/// counter is incremented by one. If a restart marker file is given, the `process.restart.count` gauge
+/// reports the number of starts persisted in it before this one. The marker file is read and written
+/// on the blocking thread pool of the runtime.
+///
+/// # Arguments
+///
+/// * `meter` - The meter recording the metrics.
+/// * `restart_marker` - The file persisting the number of starts, if any.
+#[cfg(not(feature = "noop"))]
+pub async fn register_process_metrics(meter: &Meter, restart_marker: Option<PathBuf>) {
+    let started = Instant::now();
+    meter.f64_observable_gauge("process.uptime")
+        .with_description("Time since the telemetry of the process was set up")
+        .with_unit("s")
+        .with_callback(move |observer| observer.observe(started.elapsed().as_secs_f64(), &[]))
+        .build();
+    meter.u64_counter("process.starts")
+        .with_description("Number of starts of the process")
+        .build()
+        .add(1, &[]);
+    if let Some(marker) = restart_marker {
+        let restarts = tokio::task::spawn_blocking(move || persist_start(&marker)).await.unwrap_or_default();
+        meter.u64_observable_gauge("process.restart.count")
+            .with_description("Number of previous starts persisted in the restart marker file")
+            .with_callback(move |observer| observer.observe(restarts, &[]))
+            .build();
+    }
+}
@@ -0,0 +1,199 @@
+//! # Testing Module
+//!
+//! This module provides a load generator to validate the capacity of a telemetry pipeline, and an
+//! isolated in-memory pipeline to test instrumented code. It is only available with the `testing` feature.
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use anyhow::Result;
+use opentelemetry::trace::{Span, SpanId, TraceId, Tracer, TracerProvider};
+use opentelemetry_appender_tracing::layer::OpenTelemetryTracingBridge;
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::logs::{InMemoryLogExporter, SdkLoggerProvider};
+use opentelemetry_sdk::logs::in_memory_exporter::LogDataWithResource;
+use opentelemetry_sdk::trace::{IdGenerator, InMemorySpanExporter, SdkTracerProvider as SDKTracerProvider, SpanData};
+use tracing::Dispatch;
+use tracing_subscriber::layer::SubscriberExt;
+use crate::auth::AuthContext;
+use crate::config::{RedactionConfig, ScopeFilter, SpanEnrichmentConfig, TailSamplingConfig, TraceConfig};
+use crate::resource::get_resource;
+use crate::tracer::get_tracer_provider_builder;
+use crate::tracer::export_error::ExportStats;
+
+
+/// Report produced by a soak run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SoakReport {
+    /// Number of spans created.
+    pub spans_created: u64,
+    /// Number of spans exported successfully.
+    pub spans_exported: u64,
+    /// Number of spans handed to the exporter whose export failed.
+    pub spans_failed: u64,
+    /// Number of spans that never reached the exporter, e.g. because the queue was full or the spans were
+    /// not sampled.
+    pub spans_dropped: u64,
+    /// Achieved span creation rate, in spans per second.
+    pub achieved_spans_per_sec: f64,
+    /// Median export latency per batch.
+    pub export_latency_p50: Duration,
+    /// 90th percentile export latency per batch.
+    pub export_latency_p90: Duration,
+    /// 99th percentile export latency per batch.
+    pub export_latency_p99: Duration,
+}
+
+
+/// Returns the given percentile of a sorted list of durations.
+fn percentile(sorted: &[Duration], p: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let index = ((sorted.len() - 1) as f64 * p).round() as usize;
+    sorted[index]
+}
+
+
+/// Drives the configured trace pipeline at a fixed rate and reports its behavior.
+///
+/// Spans are created at `spans_per_sec` for `duration` through the tracer provider of the configuration,
+/// with its sampler, span limits, ID generator, export mode and queue, and the tail sampling, redaction and
+/// enrichment of the environment variables, like `tracer::get_tracer_provider`. The pipeline is then flushed
+/// and shut down. Spans whose export failed are reported as failed, and spans that never reached the exporter
+/// as dropped.
+///
+/// # Arguments
+///
+/// * `trace_config` - The tracing configuration to validate.
+/// * `spans_per_sec` - The target span creation rate.
+/// * `duration` - How long to generate load for.
+pub async fn soak(trace_config: &TraceConfig, spans_per_sec: u64, duration: Duration) -> Result<SoakReport> {
+    let stats = Arc::new(ExportStats::default());
+    stats.start_latency_recording();
+    let (tail_sampling, redaction, enrichment) = (TailSamplingConfig::from_env()?, RedactionConfig::from_env()?, SpanEnrichmentConfig::from_env()?);
+    let provider = get_tracer_provider_builder(trace_config, &get_resource("soak"), &AuthContext::new(), tail_sampling.as_ref(), redaction.as_ref(), enrichment.as_ref(), &[], &ScopeFilter::All, &stats)
+        .await?
+        .build();
+    let tracer = provider.tracer("soak");
+
+    let start = Instant::now();
+    let mut spans_created = 0u64;
+    while start.elapsed() < duration {
+        let expected = (start.elapsed().as_secs_f64() * spans_per_sec as f64) as u64;
+        while spans_created < expected {
+            tracer.start("soak-span").end();
+            spans_created += 1;
+        }
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+    let elapsed = start.elapsed();
+
+    // Flush errors are reported through the export statistics.
+    let _ = provider.force_flush();
+    let _ = provider.shutdown();
+
+    let (spans_exported, spans_failed) = (stats.exported_spans(), stats.dropped_spans());
+    let mut latencies = stats.take_latencies();
+    latencies.sort();
+
+    Ok(SoakReport {
+        spans_created,
+        spans_exported,
+        spans_failed,
+        spans_dropped: spans_created.saturating_sub(spans_exported + spans_failed),
+        achieved_spans_per_sec: spans_created as f64 / elapsed.as_secs_f64(),
+        export_latency_p50: percentile(&latencies, 0.50),
+        export_latency_p90: percentile(&latencies, 0.90),
+        export_latency_p99: percentile(&latencies, 0.99),
+    })
+}
+
+
+/// An ID generator producing sequential trace and span IDs, so test output is deterministic.
+#[derive(Debug, Default)]
+pub struct SequentialIdGenerator {
+    trace_ids: AtomicU64,
+    span_ids: AtomicU64,
+}
+
+
+/// Implementation of the IdGenerator trait for SequentialIdGenerator
+impl IdGenerator for SequentialIdGenerator {
+    fn new_trace_id(&self) -> TraceId {
+        let id = self.trace_ids.fetch_add(1, Ordering::Relaxed) + 1;
+        TraceId::from_bytes((id as u128).to_be_bytes())
+    }
+
+    fn new_span_id(&self) -> SpanId {
+        let id = self.span_ids.fetch_add(1, Ordering::Relaxed) + 1;
+        SpanId::from_bytes(id.to_be_bytes())
+    }
+}
+
+
+/// An isolated telemetry pipeline for unit tests.
+///
+/// Spans and logs are exported synchronously to in-memory exporters, with sequential IDs. The
+/// subscriber is not installed globally and no global propagator is set, so tests can run in
+/// parallel; wrap the code under test in `in_scope`.
+#[derive(Debug)]
+pub struct TestTelemetry {
+    /// The tracer provider.
+    pub tracer_provider: SDKTracerProvider,
+    /// The logger provider.
+    pub logger_provider: SdkLoggerProvider,
+    spans: InMemorySpanExporter,
+    logs: InMemoryLogExporter,
+    dispatch: Dispatch,
+}
+
+
+impl TestTelemetry {
+    /// Creates a new `TestTelemetry`.
+    pub fn new() -> Self {
+        let spans = InMemorySpanExporter::default();
+        let logs = InMemoryLogExporter::default();
+        let resource = Resource::builder().with_service_name("test").build();
+        let tracer_provider = SDKTracerProvider::builder()
+            .with_id_generator(SequentialIdGenerator::default())
+            .with_resource(resource.clone())
+            .with_simple_exporter(spans.clone())
+            .build();
+        let logger_provider = SdkLoggerProvider::builder()
+            .with_resource(resource)
+            .with_simple_exporter(logs.clone())
+            .build();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(tracer_provider.tracer("test")))
+            .with(OpenTelemetryTracingBridge::new(&logger_provider));
+        Self { tracer_provider, logger_provider, spans, logs, dispatch: Dispatch::new(subscriber) }
+    }
+
+    /// Runs `f` with the test subscriber as the default subscriber of the current thread.
+    pub fn in_scope<T>(&self, f: impl FnOnce() -> T) -> T {
+        tracing::dispatcher::with_default(&self.dispatch, f)
+    }
+
+    /// Returns the spans finished so far.
+    pub fn finished_spans(&self) -> Vec<SpanData> {
+        self.spans.get_finished_spans().unwrap_or_default()
+    }
+
+    /// Returns the log records emitted so far.
+    pub fn emitted_logs(&self) -> Vec<LogDataWithResource> {
+        self.logs.get_emitted_logs().unwrap_or_default()
+    }
+
+    /// Clears the captured spans and log records.
+    pub fn reset(&self) {
+        self.spans.reset();
+        self.logs.reset();
+    }
+}
+
+
+impl Default for TestTelemetry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,51 @@
+use std::time::Duration;
+use opentelemetry::{Context, KeyValue};
+use opentelemetry::baggage::BaggageExt;
+use opentelemetry::trace::Span as _;
+use opentelemetry_sdk::error::OTelSdkResult;
+use opentelemetry_sdk::trace::{Span, SpanData, SpanProcessor};
+
+
+/// A span processor that records the listed baggage entries of the parent context as attributes
+/// of each started span.
+///
+/// Only listed keys are recorded, so baggage set by other services cannot add arbitrary attributes.
+#[derive(Debug)]
+pub struct BaggageSpanProcessor {
+    keys: Vec<String>,
+}
+
+
+impl BaggageSpanProcessor {
+    /// Creates a new instance of `BaggageSpanProcessor`.
+    /// # Arguments
+    /// * `keys` - The keys of the baggage entries recorded as span attributes.
+    /// # Returns
+    /// A new `BaggageSpanProcessor` instance.
+    pub fn new(keys: Vec<String>) -> Self {
+        Self { keys }
+    }
+}
+
+
+/// Implementation of the SpanProcessor trait for BaggageSpanProcessor
+impl SpanProcessor for BaggageSpanProcessor {
+    fn on_start(&self, span: &mut Span, cx: &Context) {
+        let baggage = cx.baggage();
+        for key in &self.keys {
+            if let Some(value) = baggage.get(key.as_str()) {
+                span.set_attribute(KeyValue::new(key.clone(), value.clone()));
+            }
+        }
+    }
+
+    fn on_end(&self, _span: SpanData) {}
+
+    fn force_flush(&self) -> OTelSdkResult {
+        Ok(())
+    }
+
+    fn shutdown_with_timeout(&self, _timeout: Duration) -> OTelSdkResult {
+        Ok(())
+    }
+}
@@ -0,0 +1,75 @@
+use std::time::Duration;
+use opentelemetry::KeyValue;
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::error::OTelSdkResult;
+use opentelemetry_sdk::trace::{SpanData, SpanExporter};
+use crate::config::BinaryAttributePolicy;
+use crate::encoding::{encode_value, value_has_binary};
+
+
+/// A span exporter that applies a `BinaryAttributePolicy` to span and event attributes
+/// before delegating to the wrapped exporter.
+#[derive(Debug)]
+pub struct BinaryEncodingExporter<E: SpanExporter> {
+    inner: E,
+    policy: BinaryAttributePolicy,
+}
+
+
+/// Applies the policy to a list of attributes.
+fn encode_attributes(attributes: &mut Vec<KeyValue>, policy: BinaryAttributePolicy) {
+    if !attributes.iter().any(|kv| value_has_binary(&kv.value)) {
+        return;
+    }
+    *attributes = attributes
+        .iter()
+        .filter_map(|kv| encode_value(&kv.value, policy).map(|v| KeyValue::new(kv.key.clone(), v)))
+        .collect();
+}
+
+
+impl<E: SpanExporter> BinaryEncodingExporter<E> {
+    /// Creates a new instance of `BinaryEncodingExporter`.
+    /// # Arguments
+    /// * `inner` - The exporter to delegate to.
+    /// * `policy` - The encoding policy to apply.
+    /// # Returns
+    /// A new `BinaryEncodingExporter` instance.
+    pub fn new(inner: E, policy: BinaryAttributePolicy) -> Self {
+        Self { inner, policy }
+    }
+
+    /// Returns the wrapped exporter.
+    pub(crate) fn inner(&self) -> &E {
+        &self.inner
+    }
+}
+
+
+/// Implementation of the SpanExporter trait for BinaryEncodingExporter
+impl<E: SpanExporter> SpanExporter for BinaryEncodingExporter<E> {
+    async fn export(&self, mut batch: Vec<SpanData>) -> OTelSdkResult {
+        if self.policy == BinaryAttributePolicy::Keep {
+            return self.inner.export(batch).await;
+        }
+        for span in batch.iter_mut() {
+            encode_attributes(&mut span.attributes, self.policy);
+            for event in span.events.events.iter_mut() {
+                encode_attributes(&mut event.attributes, self.policy);
+            }
+        }
+        self.inner.export(batch).await
+    }
+
+    fn shutdown_with_timeout(&mut self, timeout: Duration) -> OTelSdkResult {
+        self.inner.shutdown_with_timeout(timeout)
+    }
+
+    fn force_flush(&mut self) -> OTelSdkResult {
+        self.inner.force_flush()
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        self.inner.set_resource(resource)
+    }
+}
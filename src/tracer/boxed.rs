@@ -0,0 +1,46 @@
+use std::time::Duration;
+use opentelemetry::Context;
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::error::OTelSdkResult;
+use opentelemetry_sdk::trace::{Span, SpanData, SpanProcessor};
+
+
+/// A span processor delegating to a boxed, user-provided span processor.
+#[derive(Debug)]
+pub struct BoxedSpanProcessor(Box<dyn SpanProcessor>);
+
+
+impl BoxedSpanProcessor {
+    /// Creates a new instance of `BoxedSpanProcessor`.
+    /// # Arguments
+    /// * `inner` - The user-provided span processor.
+    /// # Returns
+    /// A new `BoxedSpanProcessor` instance.
+    pub fn new(inner: Box<dyn SpanProcessor>) -> Self {
+        Self(inner)
+    }
+}
+
+
+/// Implementation of the SpanProcessor trait for BoxedSpanProcessor
+impl SpanProcessor for BoxedSpanProcessor {
+    fn on_start(&self, span: &mut Span, cx: &Context) {
+        self.0.on_start(span, cx)
+    }
+
+    fn on_end(&self, span: SpanData) {
+        self.0.on_end(span)
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        self.0.force_flush()
+    }
+
+    fn shutdown_with_timeout(&self, timeout: Duration) -> OTelSdkResult {
+        self.0.shutdown_with_timeout(timeout)
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        self.0.set_resource(resource)
+    }
+}
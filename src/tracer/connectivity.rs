@@ -0,0 +1,82 @@
+use std::net::ToSocketAddrs;
+use std::time::Duration;
+use anyhow::{anyhow, Result};
+use opentelemetry_sdk::trace::SpanExporter;
+use crate::auth::AuthContext;
+use crate::config::TraceConfig;
+use crate::tracer::{datadog, grpc, http, reqwest, zipkin, OtlpSpanExporter};
+
+
+/// Returns the remote endpoint of the exporter of a trace configuration, if any.
+fn endpoint(trace_config: &TraceConfig) -> Option<&str> {
+    match trace_config {
+        TraceConfig::HTTP(config) | TraceConfig::GRPC(config) | TraceConfig::REQWEST(config) => Some(&config.endpoint),
+        TraceConfig::Zipkin(config) => Some(&config.endpoint),
+        TraceConfig::DatadogAgent(config) => Some(&config.agent_url),
+        TraceConfig::XRay(config) => config.daemon_address.as_deref(),
+        TraceConfig::StdOut(_) | TraceConfig::File(_) | TraceConfig::InMemory(_) | TraceConfig::Noop(_) => None,
+    }
+}
+
+
+/// Sends an empty export with the OTLP exporter, bypassing the disk buffer and the retries,
+/// so failures are reported instead of buffered or retried. The exporter authenticates with the token provider
+/// shared through the `AuthContext`, so the probe reuses the tokens of the pipelines.
+async fn probe_otlp(exporter: OtlpSpanExporter) -> Result<(), String> {
+    exporter.raw_exporter().export(Vec::new()).await.map_err(|err| err.to_string())
+}
+
+
+/// Sends an empty export with the exporter of a trace configuration.
+async fn probe(trace_config: &TraceConfig, auth_context: &AuthContext) -> Result<(), String> {
+    match trace_config {
+        TraceConfig::HTTP(config) => {
+            let token_provider = auth_context.token_provider(&config.auth_config);
+            probe_otlp(http::get_http_span_exporter(config, token_provider).await.map_err(|err| err.to_string())?).await
+        },
+        TraceConfig::GRPC(config) => {
+            probe_otlp(grpc::get_grpc_span_exporter(config, auth_context).await.map_err(|err| err.to_string())?).await
+        },
+        TraceConfig::REQWEST(config) => {
+            let token_provider = auth_context.token_provider(&config.auth_config);
+            probe_otlp(reqwest::get_reqwest_span_exporter(config, token_provider).map_err(|err| err.to_string())?).await
+        },
+        TraceConfig::Zipkin(config) => {
+            let token_provider = auth_context.token_provider(&config.auth_config);
+            let exporter = zipkin::get_zipkin_span_exporter(config, token_provider).map_err(|err| err.to_string())?;
+            exporter.export(Vec::new()).await.map_err(|err| err.to_string())
+        },
+        TraceConfig::DatadogAgent(config) => {
+            let token_provider = auth_context.token_provider(&config.auth_config);
+            datadog::get_datadog_span_exporter(config, token_provider).export(Vec::new()).await.map_err(|err| err.to_string())
+        },
+        // The daemon does not acknowledge segments, so only its address is resolved.
+        TraceConfig::XRay(config) => match &config.daemon_address {
+            Some(daemon_address) => daemon_address.to_socket_addrs().map(|_| ()).map_err(|err| err.to_string()),
+            None => Ok(()),
+        },
+        TraceConfig::StdOut(_) | TraceConfig::File(_) | TraceConfig::InMemory(_) | TraceConfig::Noop(_) => Ok(()),
+    }
+}
+
+
+/// Verifies that the exporter of a trace configuration reaches its endpoint by sending it an empty export,
+/// so DNS, TLS and authentication failures are reported at startup instead of dropping spans at runtime.
+///
+/// The address of the X-Ray daemon is only resolved, and exporters without a remote endpoint are not checked.
+/// # Arguments
+/// * `trace_config` - The trace configuration of the exporter.
+/// * `auth_context` - The shared authentication state used to obtain token providers.
+/// * `timeout` - The time allowed for the check.
+/// # Returns
+/// An error describing the failure if the endpoint cannot be reached.
+pub(crate) async fn verify_connectivity(trace_config: &TraceConfig, auth_context: &AuthContext, timeout: Duration) -> Result<()> {
+    let Some(endpoint) = endpoint(trace_config) else {
+        return Ok(());
+    };
+    match tokio::time::timeout(timeout, probe(trace_config, auth_context)).await {
+        Ok(Ok(())) => Ok(()),
+        Ok(Err(reason)) => Err(anyhow!("The trace exporter cannot reach {}: {}", endpoint, reason)),
+        Err(_) => Err(anyhow!("The trace exporter did not reach {} within {:?}", endpoint, timeout)),
+    }
+}
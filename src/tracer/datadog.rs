@@ -0,0 +1,216 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use opentelemetry::trace::{SpanId, SpanKind, Status, TraceId};
+use opentelemetry::{Key, Value};
+use opentelemetry_http::{Bytes, HttpClient, Request};
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::error::{OTelSdkError, OTelSdkResult};
+use opentelemetry_sdk::trace::{SpanData, SpanExporter, TraceError};
+use opentelemetry_sdk::trace::SdkTracerProvider as SDKTracerProvider;
+use serde::Serialize;
+use crate::auth::GetToken;
+use crate::config::DatadogAgentConfig;
+#[cfg(not(feature = "noop"))]
+use crate::config::ExportPolicyConfig;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::id_generator::build_id_generator;
+use crate::tracer::json::{pairs, unix_nanos};
+#[cfg(not(feature = "noop"))]
+use crate::tracer::reqwest::ReqwestTracerClient;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::sampling::build_sampler;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::{with_export_mode, with_span_limits};
+
+
+/// Path of the trace intake of the Datadog agent.
+const TRACES_PATH: &str = "/v0.4/traces";
+
+
+/// A span in the v0.4 format of the Datadog trace agent.
+#[derive(Debug, Serialize)]
+struct DatadogSpan {
+    trace_id: u64,
+    span_id: u64,
+    parent_id: u64,
+    name: String,
+    resource: String,
+    service: String,
+    r#type: &'static str,
+    start: i64,
+    duration: i64,
+    error: i32,
+    meta: BTreeMap<String, String>,
+    metrics: BTreeMap<String, f64>,
+}
+
+
+/// A span exporter that sends spans to a Datadog trace agent, in the JSON encoding of the v0.4 intake,
+/// for agents without OTLP ingest.
+///
+/// Numeric attributes are sent as metrics and the other attributes as tags. Span events are not sent,
+/// since the v0.4 format has no equivalent.
+#[derive(Debug)]
+pub struct DatadogSpanExporter {
+    endpoint: String,
+    client: Arc<dyn HttpClient>,
+    resource: Resource,
+}
+
+
+/// Returns the lower 64 bits of a trace ID, used by Datadog as the trace ID, and the upper 64 bits.
+fn split_trace_id(trace_id: TraceId) -> (u64, u64) {
+    let id = u128::from_be_bytes(trace_id.to_bytes());
+    (id as u64, (id >> 64) as u64)
+}
+
+
+/// Returns the numeric value of a span ID.
+fn span_id(span_id: SpanId) -> u64 {
+    u64::from_be_bytes(span_id.to_bytes())
+}
+
+
+/// Adds attributes to the tags or the metrics of a Datadog span, depending on their type.
+fn add_attributes<'a>(attributes: impl Iterator<Item = (&'a Key, &'a Value)>, meta: &mut BTreeMap<String, String>, metrics: &mut BTreeMap<String, f64>) {
+    for (key, value) in attributes {
+        match value {
+            Value::I64(i) => {
+                metrics.insert(key.to_string(), *i as f64);
+            },
+            Value::F64(f) => {
+                metrics.insert(key.to_string(), *f);
+            },
+            _ => {
+                meta.insert(key.to_string(), value.as_str().into_owned());
+            },
+        }
+    }
+}
+
+
+impl DatadogSpanExporter {
+    /// Creates a new instance of `DatadogSpanExporter`.
+    /// # Arguments
+    /// * `config` - The Datadog agent trace configuration.
+    /// * `client` - The HTTP client sending the traces to the agent.
+    /// # Returns
+    /// A new `DatadogSpanExporter` instance.
+    pub fn new(config: &DatadogAgentConfig, client: Arc<dyn HttpClient>) -> Self {
+        Self {
+            endpoint: format!("{}{}", config.agent_url.trim_end_matches('/'), TRACES_PATH),
+            client,
+            resource: Resource::builder().build(),
+        }
+    }
+
+    /// Converts a span to the Datadog format.
+    fn convert(&self, span: &SpanData, service: &str) -> DatadogSpan {
+        let (trace_id, trace_id_high) = split_trace_id(span.span_context.trace_id());
+        let mut meta = BTreeMap::new();
+        let mut metrics = BTreeMap::new();
+        add_attributes(self.resource.iter().filter(|(key, _)| key.as_str() != "service.name"), &mut meta, &mut metrics);
+        add_attributes(pairs(&span.attributes), &mut meta, &mut metrics);
+        if trace_id_high != 0 {
+            meta.insert("_dd.p.tid".to_string(), format!("{:016x}", trace_id_high));
+        }
+        meta.insert("span.kind".to_string(), format!("{:?}", span.span_kind).to_lowercase());
+        let error = match &span.status {
+            Status::Error { description } => {
+                meta.insert("error.message".to_string(), description.to_string());
+                1
+            },
+            _ => 0,
+        };
+        let start = unix_nanos(span.start_time);
+        DatadogSpan {
+            trace_id,
+            span_id: span_id(span.span_context.span_id()),
+            parent_id: span_id(span.parent_span_id),
+            name: span.instrumentation_scope.name().to_string(),
+            resource: span.name.to_string(),
+            service: service.to_string(),
+            r#type: match span.span_kind {
+                SpanKind::Server => "web",
+                SpanKind::Client => "http",
+                _ => "custom",
+            },
+            start: start as i64,
+            duration: unix_nanos(span.end_time).saturating_sub(start) as i64,
+            error,
+            meta,
+            metrics,
+        }
+    }
+}
+
+
+/// Implementation of the SpanExporter trait for DatadogSpanExporter
+impl SpanExporter for DatadogSpanExporter {
+    async fn export(&self, batch: Vec<SpanData>) -> OTelSdkResult {
+        let service = self.resource.get(&Key::from_static_str("service.name"))
+            .map(|value| value.as_str().into_owned())
+            .unwrap_or_else(|| "unknown_service".to_string());
+        let mut traces: HashMap<TraceId, Vec<DatadogSpan>> = HashMap::new();
+        for span in &batch {
+            traces.entry(span.span_context.trace_id()).or_default().push(self.convert(span, &service));
+        }
+        let traces: Vec<Vec<DatadogSpan>> = traces.into_values().collect();
+        let body = serde_json::to_vec(&traces).map_err(|e| OTelSdkError::InternalFailure(e.to_string()))?;
+
+        let request = Request::builder()
+            .method("PUT")
+            .uri(&self.endpoint)
+            .header("Content-Type", "application/json")
+            .header("X-Datadog-Trace-Count", traces.len().to_string())
+            .header("Datadog-Meta-Lang", "rust")
+            .body(Bytes::from(body))
+            .map_err(|e| OTelSdkError::InternalFailure(e.to_string()))?;
+        let response = self.client.send_bytes(request).await.map_err(|e| OTelSdkError::InternalFailure(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(OTelSdkError::InternalFailure(format!("Datadog agent export failed with status code: {}", response.status().as_u16())));
+        }
+        Ok(())
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        self.resource = resource.clone();
+    }
+}
+
+
+/// Builds the Datadog agent span exporter, with authentication for agents behind an authenticating proxy.
+/// # Arguments
+/// * `config` - The Datadog agent trace configuration.
+/// * `token_provider` - An `Arc<dyn GetToken>` to provide access tokens.
+/// # Returns
+/// The span exporter.
+#[cfg(not(feature = "noop"))]
+pub(crate) fn get_datadog_span_exporter(config: &DatadogAgentConfig, token_provider: Arc<dyn GetToken>) -> DatadogSpanExporter {
+    let http_client = Arc::new(reqwest::Client::new()) as Arc<dyn HttpClient>;
+    DatadogSpanExporter::new(config, Arc::new(ReqwestTracerClient::new(http_client, token_provider)))
+}
+
+
+/// Initializes the Datadog agent tracer provider.
+/// # Arguments
+/// * `config` - The Datadog agent trace configuration.
+/// * `resource` - The resource of the traces.
+/// * `token_provider` - An `Arc<dyn GetToken>` to provide access tokens.
+/// # Returns
+/// A `Result` containing the initialized `SDKTracerProvider` or a `TraceError`.
+#[cfg(not(feature = "noop"))]
+pub async fn get_datadog_tracer_provider(config: &DatadogAgentConfig, resource: &Resource, token_provider: Arc<dyn GetToken>) -> Result<SDKTracerProvider, TraceError> {
+    let builder = with_span_limits(SDKTracerProvider::builder(), &config.span_limits)
+        .with_resource(resource.clone())
+        .with_sampler(build_sampler(&config.sampler))
+        .with_id_generator(build_id_generator(&config.id_generator));
+    Ok(with_export_mode(builder, get_datadog_span_exporter(config, token_provider), config.export_mode, &ExportPolicyConfig::default(), "datadog").build())
+}
+
+
+/// Stub of `get_datadog_tracer_provider` with the `noop` feature, returning a tracer provider without exporter.
+#[cfg(feature = "noop")]
+pub async fn get_datadog_tracer_provider(_config: &DatadogAgentConfig, _resource: &Resource, _token_provider: Arc<dyn GetToken>) -> Result<SDKTracerProvider, TraceError> {
+    Ok(SDKTracerProvider::builder().build())
+}
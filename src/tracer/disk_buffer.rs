@@ -0,0 +1,445 @@
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use opentelemetry::{Array, InstrumentationScope, KeyValue, Value};
+use opentelemetry::trace::{Event, Link, SpanContext, SpanId, SpanKind, Status, TraceFlags, TraceId, TraceState};
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::error::OTelSdkResult;
+use opentelemetry_sdk::trace::{SpanData, SpanEvents, SpanExporter, SpanLinks};
+use serde::{Deserialize, Serialize};
+use crate::config::{DiskBufferConfig, ExportPolicyConfig};
+use crate::tracer::retry::is_retryable;
+use crate::tracer::run_blocking;
+
+
+/// Extension of the files holding buffered batches.
+const BATCH_EXTENSION: &str = "batch";
+
+/// Extension of the files holding buffered batches the collector rejected permanently, kept for inspection.
+const FAILED_EXTENSION: &str = "failed";
+
+
+/// An attribute value, as stored on disk.
+#[derive(Debug, Serialize, Deserialize)]
+enum StoredValue {
+    Bool(bool),
+    I64(i64),
+    F64(f64),
+    String(String),
+    Bools(Vec<bool>),
+    I64s(Vec<i64>),
+    F64s(Vec<f64>),
+    Strings(Vec<String>),
+}
+
+
+/// An attribute, as stored on disk.
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredKeyValue {
+    key: String,
+    value: StoredValue,
+}
+
+
+/// A span context, as stored on disk.
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredSpanContext {
+    trace_id: String,
+    span_id: String,
+    trace_flags: u8,
+    is_remote: bool,
+    trace_state: String,
+}
+
+
+/// A span event, as stored on disk.
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredEvent {
+    name: String,
+    time_unix_nano: u64,
+    attributes: Vec<StoredKeyValue>,
+    dropped_attributes_count: u32,
+}
+
+
+/// A span link, as stored on disk.
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredLink {
+    span_context: StoredSpanContext,
+    attributes: Vec<StoredKeyValue>,
+    dropped_attributes_count: u32,
+}
+
+
+/// A span, as stored on disk.
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredSpan {
+    span_context: StoredSpanContext,
+    parent_span_id: String,
+    parent_span_is_remote: bool,
+    kind: u8,
+    name: String,
+    start_time_unix_nano: u64,
+    end_time_unix_nano: u64,
+    attributes: Vec<StoredKeyValue>,
+    dropped_attributes_count: u32,
+    events: Vec<StoredEvent>,
+    dropped_events_count: u32,
+    links: Vec<StoredLink>,
+    dropped_links_count: u32,
+    /// `None` for an unset status, `Some(None)` for Ok and `Some(Some(description))` for Error.
+    status: Option<Option<String>>,
+    scope_name: String,
+    scope_version: Option<String>,
+    scope_schema_url: Option<String>,
+}
+
+
+/// Returns the nanoseconds since the UNIX epoch of a timestamp.
+fn to_nanos(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map(|d| d.as_nanos() as u64).unwrap_or_default()
+}
+
+
+/// Returns the timestamp of nanoseconds since the UNIX epoch.
+fn from_nanos(nanos: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_nanos(nanos)
+}
+
+
+impl From<&Value> for StoredValue {
+    fn from(value: &Value) -> Self {
+        match value {
+            Value::Bool(b) => StoredValue::Bool(*b),
+            Value::I64(i) => StoredValue::I64(*i),
+            Value::F64(f) => StoredValue::F64(*f),
+            Value::String(s) => StoredValue::String(s.as_str().to_string()),
+            Value::Array(Array::Bool(l)) => StoredValue::Bools(l.clone()),
+            Value::Array(Array::I64(l)) => StoredValue::I64s(l.clone()),
+            Value::Array(Array::F64(l)) => StoredValue::F64s(l.clone()),
+            Value::Array(Array::String(l)) => StoredValue::Strings(l.iter().map(|s| s.as_str().to_string()).collect()),
+            _ => StoredValue::String(value.to_string()),
+        }
+    }
+}
+
+
+impl From<StoredValue> for Value {
+    fn from(value: StoredValue) -> Self {
+        match value {
+            StoredValue::Bool(b) => Value::Bool(b),
+            StoredValue::I64(i) => Value::I64(i),
+            StoredValue::F64(f) => Value::F64(f),
+            StoredValue::String(s) => Value::String(s.into()),
+            StoredValue::Bools(l) => Value::Array(Array::Bool(l)),
+            StoredValue::I64s(l) => Value::Array(Array::I64(l)),
+            StoredValue::F64s(l) => Value::Array(Array::F64(l)),
+            StoredValue::Strings(l) => Value::Array(Array::String(l.into_iter().map(Into::into).collect())),
+        }
+    }
+}
+
+
+/// Converts attributes to their stored form.
+fn store_attributes(attributes: &[KeyValue]) -> Vec<StoredKeyValue> {
+    attributes.iter().map(|kv| StoredKeyValue { key: kv.key.to_string(), value: (&kv.value).into() }).collect()
+}
+
+
+/// Converts stored attributes back to attributes.
+fn load_attributes(attributes: Vec<StoredKeyValue>) -> Vec<KeyValue> {
+    attributes.into_iter().map(|kv| KeyValue::new(kv.key, Value::from(kv.value))).collect()
+}
+
+
+impl From<&SpanContext> for StoredSpanContext {
+    fn from(context: &SpanContext) -> Self {
+        StoredSpanContext {
+            trace_id: context.trace_id().to_string(),
+            span_id: context.span_id().to_string(),
+            trace_flags: context.trace_flags().to_u8(),
+            is_remote: context.is_remote(),
+            trace_state: context.trace_state().header(),
+        }
+    }
+}
+
+
+impl StoredSpanContext {
+    /// Converts the stored span context back to a span context, or `None` if an ID is malformed.
+    fn load(self) -> Option<SpanContext> {
+        Some(SpanContext::new(
+            TraceId::from_hex(&self.trace_id).ok()?,
+            SpanId::from_hex(&self.span_id).ok()?,
+            TraceFlags::new(self.trace_flags),
+            self.is_remote,
+            TraceState::from_str(&self.trace_state).unwrap_or_default(),
+        ))
+    }
+}
+
+
+impl From<&SpanData> for StoredSpan {
+    fn from(span: &SpanData) -> Self {
+        StoredSpan {
+            span_context: (&span.span_context).into(),
+            parent_span_id: span.parent_span_id.to_string(),
+            parent_span_is_remote: span.parent_span_is_remote,
+            kind: match span.span_kind {
+                SpanKind::Client => 1,
+                SpanKind::Server => 2,
+                SpanKind::Producer => 3,
+                SpanKind::Consumer => 4,
+                SpanKind::Internal => 0,
+            },
+            name: span.name.to_string(),
+            start_time_unix_nano: to_nanos(span.start_time),
+            end_time_unix_nano: to_nanos(span.end_time),
+            attributes: store_attributes(&span.attributes),
+            dropped_attributes_count: span.dropped_attributes_count,
+            events: span.events.iter().map(|event| StoredEvent {
+                name: event.name.to_string(),
+                time_unix_nano: to_nanos(event.timestamp),
+                attributes: store_attributes(&event.attributes),
+                dropped_attributes_count: event.dropped_attributes_count,
+            }).collect(),
+            dropped_events_count: span.events.dropped_count,
+            links: span.links.iter().map(|link| StoredLink {
+                span_context: (&link.span_context).into(),
+                attributes: store_attributes(&link.attributes),
+                dropped_attributes_count: link.dropped_attributes_count,
+            }).collect(),
+            dropped_links_count: span.links.dropped_count,
+            status: match &span.status {
+                Status::Unset => None,
+                Status::Ok => Some(None),
+                Status::Error { description } => Some(Some(description.to_string())),
+            },
+            scope_name: span.instrumentation_scope.name().to_string(),
+            scope_version: span.instrumentation_scope.version().map(str::to_string),
+            scope_schema_url: span.instrumentation_scope.schema_url().map(str::to_string),
+        }
+    }
+}
+
+
+impl StoredSpan {
+    /// Converts the stored span back to span data, or `None` if an ID is malformed.
+    fn load(self) -> Option<SpanData> {
+        let mut events = SpanEvents::default();
+        events.events = self.events.into_iter()
+            .map(|event| Event::new(event.name, from_nanos(event.time_unix_nano), load_attributes(event.attributes), event.dropped_attributes_count))
+            .collect();
+        events.dropped_count = self.dropped_events_count;
+        let mut links = SpanLinks::default();
+        links.links = self.links.into_iter()
+            .map(|link| Some(Link::new(link.span_context.load()?, load_attributes(link.attributes), link.dropped_attributes_count)))
+            .collect::<Option<_>>()?;
+        links.dropped_count = self.dropped_links_count;
+        let mut scope = InstrumentationScope::builder(self.scope_name);
+        if let Some(version) = self.scope_version {
+            scope = scope.with_version(version);
+        }
+        if let Some(schema_url) = self.scope_schema_url {
+            scope = scope.with_schema_url(schema_url);
+        }
+        Some(SpanData {
+            span_context: self.span_context.load()?,
+            parent_span_id: SpanId::from_hex(&self.parent_span_id).ok()?,
+            parent_span_is_remote: self.parent_span_is_remote,
+            span_kind: match self.kind {
+                1 => SpanKind::Client,
+                2 => SpanKind::Server,
+                3 => SpanKind::Producer,
+                4 => SpanKind::Consumer,
+                _ => SpanKind::Internal,
+            },
+            name: Cow::Owned(self.name),
+            start_time: from_nanos(self.start_time_unix_nano),
+            end_time: from_nanos(self.end_time_unix_nano),
+            attributes: load_attributes(self.attributes),
+            dropped_attributes_count: self.dropped_attributes_count,
+            events,
+            links,
+            status: match self.status {
+                None => Status::Unset,
+                Some(None) => Status::Ok,
+                Some(Some(description)) => Status::error(description),
+            },
+            instrumentation_scope: scope.build(),
+        })
+    }
+}
+
+
+/// Returns the buffered batch files of a directory, oldest first, with their sizes.
+fn buffered(dir: &Path) -> Vec<(PathBuf, u64)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<(PathBuf, u64)> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == BATCH_EXTENSION))
+        .filter_map(|path| std::fs::metadata(&path).ok().map(|metadata| (path, metadata.len())))
+        .collect();
+    // File names start with a zero-padded timestamp, so they sort in creation order.
+    files.sort();
+    files
+}
+
+
+/// A span exporter that appends the batches the wrapped exporter fails to export with a retryable
+/// error to a bounded on-disk queue, and replays them after the next successful export.
+///
+/// Each batch is stored in its own file in the buffer directory, so batches buffered before a
+/// restart are replayed too. When the buffer is full, the oldest batches are deleted. Batches the
+/// collector rejects permanently, e.g. with HTTP 400 or INVALID_ARGUMENT, are not buffered, and
+/// buffered batches it rejects permanently on replay are renamed with the `failed` extension and skipped.
+///
+/// Without a configuration, batches are passed through to the wrapped exporter.
+#[derive(Debug)]
+pub struct DiskBufferExporter<E: SpanExporter> {
+    inner: E,
+    config: Option<DiskBufferConfig>,
+    sequence: AtomicU64,
+    replaying: AtomicBool,
+    lock: Arc<Mutex<()>>,
+}
+
+
+impl<E: SpanExporter> DiskBufferExporter<E> {
+    /// Creates a new instance of `DiskBufferExporter`.
+    /// # Arguments
+    /// * `inner` - The exporter to delegate to.
+    /// * `policy` - The export policy, setting the disk buffer, if any.
+    /// # Returns
+    /// A new `DiskBufferExporter` instance.
+    pub fn new(inner: E, policy: &ExportPolicyConfig) -> Self {
+        Self {
+            inner,
+            config: policy.disk_buffer.clone(),
+            sequence: AtomicU64::new(0),
+            replaying: AtomicBool::new(false),
+            lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Returns the wrapped exporter.
+    pub(crate) fn inner(&self) -> &E {
+        &self.inner
+    }
+
+    /// Appends a batch to the buffer, deleting the oldest batches if the buffer would exceed its maximum size.
+    async fn append(&self, config: &DiskBufferConfig, batch: &[SpanData]) -> std::io::Result<()> {
+        let stored: Vec<StoredSpan> = batch.iter().map(StoredSpan::from).collect();
+        let content = serde_json::to_vec(&stored)?;
+        if content.len() as u64 > config.max_size {
+            return Err(std::io::Error::other("batch larger than the disk buffer"));
+        }
+        let millis = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis()).unwrap_or_default();
+        let sequence = self.sequence.fetch_add(1, Ordering::Relaxed);
+        let name = format!("{:020}-{:010}.{}", millis, sequence, BATCH_EXTENSION);
+        let (dir, max_size, lock) = (config.path.clone(), config.max_size, self.lock.clone());
+
+        run_blocking(move || {
+            let _guard = lock.lock().unwrap_or_else(|e| e.into_inner());
+            std::fs::create_dir_all(&dir)?;
+
+            let files = buffered(&dir);
+            let mut size: u64 = files.iter().map(|(_, len)| len).sum::<u64>() + content.len() as u64;
+            for (path, len) in files {
+                if size <= max_size {
+                    break;
+                }
+                tracing::warn!(file = %path.display(), "Disk buffer full, dropping the oldest buffered spans");
+                std::fs::remove_file(&path)?;
+                size -= len;
+            }
+            std::fs::write(dir.join(name), content)
+        }).await
+    }
+
+    /// Removes a replayed batch from the buffer. Batches the collector rejected permanently are kept
+    /// with the `failed` extension instead, so they are neither replayed again nor lost silently.
+    async fn remove(&self, path: PathBuf, rejected: bool) {
+        let lock = self.lock.clone();
+        let result = run_blocking(move || {
+            let _guard = lock.lock().unwrap_or_else(|e| e.into_inner());
+            if rejected {
+                std::fs::rename(&path, path.with_extension(FAILED_EXTENSION))
+            } else {
+                std::fs::remove_file(&path)
+            }
+        }).await;
+        if let Err(err) = result {
+            tracing::warn!(error = %err, "Failed to remove a replayed batch from the disk buffer");
+        }
+    }
+
+    /// Exports the buffered batches, oldest first, until an export fails with a retryable error.
+    async fn replay(&self, config: &DiskBufferConfig) {
+        if self.replaying.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let dir = config.path.clone();
+        let files = run_blocking(move || Ok(buffered(&dir))).await.unwrap_or_default();
+        for (path, _) in files {
+            let file = path.clone();
+            let batch = run_blocking(move || std::fs::read(file)).await.ok()
+                .and_then(|content| serde_json::from_slice::<Vec<StoredSpan>>(&content).ok())
+                .map(|stored| stored.into_iter().filter_map(StoredSpan::load).collect::<Vec<_>>());
+            // Unreadable batches are deleted too, so they are not retried forever.
+            let mut rejected = false;
+            if let Some(batch) = batch
+                && let Err(err) = self.inner.export(batch).await {
+                if is_retryable(&err) {
+                    break;
+                }
+                tracing::warn!(error = %err, file = %path.display(), "Buffered spans rejected by the collector, skipping them");
+                rejected = true;
+            }
+            self.remove(path, rejected).await;
+        }
+        self.replaying.store(false, Ordering::SeqCst);
+    }
+}
+
+
+/// Implementation of the SpanExporter trait for DiskBufferExporter
+impl<E: SpanExporter> SpanExporter for DiskBufferExporter<E> {
+    async fn export(&self, batch: Vec<SpanData>) -> OTelSdkResult {
+        let Some(config) = &self.config else {
+            return self.inner.export(batch).await;
+        };
+        match self.inner.export(batch.clone()).await {
+            Ok(()) => {
+                self.replay(config).await;
+                Ok(())
+            },
+            Err(err) if !is_retryable(&err) => Err(err),
+            Err(err) => match self.append(config, &batch).await {
+                Ok(()) => Ok(()),
+                Err(io_err) => {
+                    tracing::warn!(error = %io_err, "Failed to buffer spans on disk");
+                    Err(err)
+                },
+            },
+        }
+    }
+
+    fn shutdown_with_timeout(&mut self, timeout: Duration) -> OTelSdkResult {
+        self.inner.shutdown_with_timeout(timeout)
+    }
+
+    fn force_flush(&mut self) -> OTelSdkResult {
+        self.inner.force_flush()
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        self.inner.set_resource(resource)
+    }
+}
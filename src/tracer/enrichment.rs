@@ -0,0 +1,68 @@
+use std::time::Duration;
+use opentelemetry::{Context, KeyValue};
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::error::OTelSdkResult;
+use opentelemetry_sdk::trace::{Span, SpanData, SpanProcessor};
+use crate::config::SpanEnrichmentConfig;
+
+
+/// A span processor that adds fixed deployment attributes, e.g. `deployment.environment` or
+/// `k8s.pod.name`, to each ended span before it is passed to the wrapped processor, so instrumented
+/// libraries do not each need to add them.
+///
+/// The attributes are added at export time, only to the spans without an attribute of the same key,
+/// so attributes set by the application take precedence and no key is recorded twice.
+/// Without a configuration, spans are passed through to the wrapped processor.
+#[derive(Debug)]
+pub struct EnrichmentProcessor<P: SpanProcessor> {
+    inner: P,
+    attributes: Vec<KeyValue>,
+}
+
+
+impl<P: SpanProcessor> EnrichmentProcessor<P> {
+    /// Creates a new instance of `EnrichmentProcessor`.
+    /// # Arguments
+    /// * `inner` - The processor receiving the enriched spans.
+    /// * `config` - The enrichment configuration, or `None` to pass every span through.
+    /// # Returns
+    /// A new `EnrichmentProcessor` instance.
+    pub fn new(inner: P, config: Option<&SpanEnrichmentConfig>) -> Self {
+        let attributes = config
+            .map(|config| config.attributes
+                .iter()
+                .map(|(key, value)| KeyValue::new(key.clone(), value.clone()))
+                .collect())
+            .unwrap_or_default();
+        Self { inner, attributes }
+    }
+}
+
+
+/// Implementation of the SpanProcessor trait for EnrichmentProcessor
+impl<P: SpanProcessor> SpanProcessor for EnrichmentProcessor<P> {
+    fn on_start(&self, span: &mut Span, cx: &Context) {
+        self.inner.on_start(span, cx)
+    }
+
+    fn on_end(&self, mut span: SpanData) {
+        for attribute in &self.attributes {
+            if !span.attributes.iter().any(|kv| kv.key == attribute.key) {
+                span.attributes.push(attribute.clone());
+            }
+        }
+        self.inner.on_end(span)
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        self.inner.force_flush()
+    }
+
+    fn shutdown_with_timeout(&self, timeout: Duration) -> OTelSdkResult {
+        self.inner.shutdown_with_timeout(timeout)
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        self.inner.set_resource(resource)
+    }
+}
@@ -0,0 +1,76 @@
+use std::time::Duration;
+use opentelemetry::{Context, Value};
+use opentelemetry::trace::{Link, SpanContext, SpanId, TraceFlags, TraceId, TraceState};
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::error::OTelSdkResult;
+use opentelemetry_sdk::trace::{Span, SpanData, SpanProcessor};
+use crate::error::{ORIGIN_SPAN_ID_ATTRIBUTE, ORIGIN_TRACE_ID_ATTRIBUTE};
+
+
+/// A span processor that links spans reporting a `TracedError` to the span in which the error was created.
+///
+/// Events carrying the `error.origin.trace_id` and `error.origin.span_id` attributes, as logged by
+/// `TracedError::report`, add a link to the origin span before the span is passed to the wrapped processor.
+#[derive(Debug)]
+pub struct ErrorLinkProcessor<P: SpanProcessor> {
+    inner: P,
+}
+
+
+/// Returns the origin span context recorded in the attributes of an event, if any.
+fn origin_span_context(attributes: &[opentelemetry::KeyValue]) -> Option<SpanContext> {
+    let attribute = |name: &str| attributes.iter().find(|kv| kv.key.as_str() == name).map(|kv| &kv.value);
+    let (Some(Value::String(trace_id)), Some(Value::String(span_id))) =
+        (attribute(ORIGIN_TRACE_ID_ATTRIBUTE), attribute(ORIGIN_SPAN_ID_ATTRIBUTE)) else {
+        return None;
+    };
+    let trace_id = TraceId::from_hex(trace_id.as_str()).ok()?;
+    let span_id = SpanId::from_hex(span_id.as_str()).ok()?;
+    if trace_id == TraceId::INVALID || span_id == SpanId::INVALID {
+        return None;
+    }
+    Some(SpanContext::new(trace_id, span_id, TraceFlags::SAMPLED, false, TraceState::default()))
+}
+
+
+impl<P: SpanProcessor> ErrorLinkProcessor<P> {
+    /// Creates a new instance of `ErrorLinkProcessor`.
+    /// # Arguments
+    /// * `inner` - The processor receiving the linked spans.
+    /// # Returns
+    /// A new `ErrorLinkProcessor` instance.
+    pub fn new(inner: P) -> Self {
+        Self { inner }
+    }
+}
+
+
+/// Implementation of the SpanProcessor trait for ErrorLinkProcessor
+impl<P: SpanProcessor> SpanProcessor for ErrorLinkProcessor<P> {
+    fn on_start(&self, span: &mut Span, cx: &Context) {
+        self.inner.on_start(span, cx)
+    }
+
+    fn on_end(&self, mut span: SpanData) {
+        let origins: Vec<SpanContext> = span.events.events.iter()
+            .filter_map(|event| origin_span_context(&event.attributes))
+            .filter(|origin| *origin != span.span_context)
+            .collect();
+        for origin in origins {
+            span.links.links.push(Link::with_context(origin));
+        }
+        self.inner.on_end(span)
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        self.inner.force_flush()
+    }
+
+    fn shutdown_with_timeout(&self, timeout: Duration) -> OTelSdkResult {
+        self.inner.shutdown_with_timeout(timeout)
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        self.inner.set_resource(resource)
+    }
+}
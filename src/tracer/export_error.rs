@@ -0,0 +1,277 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::fmt::{Debug, Formatter};
+use std::sync::{Arc, Mutex, Weak};
+#[cfg(any(feature = "testing", not(feature = "noop")))]
+use std::time::Duration;
+#[cfg(not(feature = "noop"))]
+use std::time::Instant;
+#[cfg(not(feature = "noop"))]
+use opentelemetry_sdk::Resource;
+#[cfg(not(feature = "noop"))]
+use opentelemetry_sdk::error::OTelSdkResult;
+#[cfg(not(feature = "noop"))]
+use opentelemetry_sdk::trace::{SpanData, SpanExporter};
+#[cfg(not(feature = "noop"))]
+use crate::self_observability::record_dropped_spans as record_dropped_metrics;
+
+
+/// Event describing a batch of spans lost because its export failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportErrorEvent {
+    /// The name of the exporter, e.g. `grpc` or `zipkin`.
+    pub exporter: String,
+    /// The number of spans in the failed batch.
+    pub spans: usize,
+    /// A description of the failure, after retries if any.
+    pub reason: String,
+}
+
+
+/// Callback notified of failed exports.
+type ExportErrorCallback = Arc<dyn Fn(&ExportErrorEvent) + Send + Sync>;
+
+
+/// The registered export error callbacks, with the ID of the next one.
+#[derive(Default)]
+struct ExportErrorCallbacks {
+    next_id: u64,
+    callbacks: Vec<(u64, ExportErrorCallback)>,
+}
+
+
+/// Implementation of the Debug trait for ExportErrorCallbacks
+impl Debug for ExportErrorCallbacks {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ExportErrorCallbacks").field("callbacks", &self.callbacks.len()).finish()
+    }
+}
+
+
+/// Number of spans dropped so far.
+static DROPPED_SPANS: AtomicU64 = AtomicU64::new(0);
+
+/// Number of batches dropped so far.
+static DROPPED_BATCHES: AtomicU64 = AtomicU64::new(0);
+
+/// Estimated number of spans dropped so far by a full queue.
+static ESTIMATED_QUEUE_DROPS: AtomicU64 = AtomicU64::new(0);
+
+
+/// Counters, captured failures and export error callbacks of the exports of one tracer provider,
+/// so each `OpenTelemetryObject` reports its own spans rather than those of the whole process.
+#[derive(Debug, Default)]
+pub(crate) struct ExportStats {
+    exported_spans: AtomicU64,
+    dropped_spans: AtomicU64,
+    dropped_batches: AtomicU64,
+    estimated_queue_drops: AtomicU64,
+    captured: Mutex<Option<Vec<ExportErrorEvent>>>,
+    #[cfg(any(feature = "testing", not(feature = "noop")))]
+    latencies: Mutex<Option<Vec<Duration>>>,
+    callbacks: Arc<Mutex<ExportErrorCallbacks>>,
+}
+
+
+impl ExportStats {
+    /// Returns the number of spans exported so far.
+    pub(crate) fn exported_spans(&self) -> u64 {
+        self.exported_spans.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of spans dropped so far because their export failed.
+    pub(crate) fn dropped_spans(&self) -> u64 {
+        self.dropped_spans.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of batches of spans dropped so far because their export failed.
+    pub(crate) fn dropped_batches(&self) -> u64 {
+        self.dropped_batches.load(Ordering::Relaxed)
+    }
+
+    /// Returns the estimated number of spans dropped so far by a full queue.
+    pub(crate) fn estimated_queue_drops(&self) -> u64 {
+        self.estimated_queue_drops.load(Ordering::Relaxed)
+    }
+
+    /// Starts capturing the failed exports, e.g. during shutdown, until `take_captured` is called.
+    pub(crate) fn start_capture(&self) {
+        *self.captured.lock().unwrap_or_else(|e| e.into_inner()) = Some(Vec::new());
+    }
+
+    /// Stops capturing the failed exports and returns those captured since `start_capture`.
+    pub(crate) fn take_captured(&self) -> Vec<ExportErrorEvent> {
+        self.captured.lock().unwrap_or_else(|e| e.into_inner()).take().unwrap_or_default()
+    }
+
+    /// Starts recording the latency of each export, e.g. during a soak run, until `take_latencies` is called.
+    #[cfg(feature = "testing")]
+    pub(crate) fn start_latency_recording(&self) {
+        *self.latencies.lock().unwrap_or_else(|e| e.into_inner()) = Some(Vec::new());
+    }
+
+    /// Stops recording the export latencies and returns those recorded since `start_latency_recording`.
+    #[cfg(feature = "testing")]
+    pub(crate) fn take_latencies(&self) -> Vec<Duration> {
+        self.latencies.lock().unwrap_or_else(|e| e.into_inner()).take().unwrap_or_default()
+    }
+
+    /// Registers a callback notified whenever a batch of spans is dropped because its export failed.
+    /// # Arguments
+    /// * `callback` - The function called with each `ExportErrorEvent`.
+    /// # Returns
+    /// The handle unregistering the callback when dropped.
+    pub(crate) fn on_export_error(&self, callback: ExportErrorCallback) -> ExportErrorCallbackHandle {
+        let mut callbacks = self.callbacks.lock().unwrap_or_else(|e| e.into_inner());
+        let id = callbacks.next_id;
+        callbacks.next_id += 1;
+        callbacks.callbacks.push((id, callback));
+        ExportErrorCallbackHandle { callbacks: Arc::downgrade(&self.callbacks), id }
+    }
+
+    /// Notifies the registered callbacks of a failed export.
+    /// # Arguments
+    /// * `event` - The failed export.
+    #[cfg(not(feature = "noop"))]
+    fn notify(&self, event: &ExportErrorEvent) {
+        let callbacks: Vec<ExportErrorCallback> = self.callbacks.lock().unwrap_or_else(|e| e.into_inner())
+            .callbacks
+            .iter()
+            .map(|(_, callback)| callback.clone())
+            .collect();
+        for callback in callbacks {
+            callback(event);
+        }
+    }
+}
+
+
+/// Handle of an export error callback, returned by `OpenTelemetryObject::on_export_error`.
+///
+/// Dropping the handle unregisters the callback, so it must be kept for as long as the callback is needed.
+#[derive(Debug)]
+#[must_use = "dropping the handle unregisters the export error callback"]
+pub struct ExportErrorCallbackHandle {
+    callbacks: Weak<Mutex<ExportErrorCallbacks>>,
+    id: u64,
+}
+
+
+/// Implementation of the Drop trait for ExportErrorCallbackHandle
+impl Drop for ExportErrorCallbackHandle {
+    fn drop(&mut self) {
+        if let Some(callbacks) = self.callbacks.upgrade() {
+            let mut callbacks = callbacks.lock().unwrap_or_else(|e| e.into_inner());
+            callbacks.callbacks.retain(|(id, _)| *id != self.id);
+        }
+    }
+}
+
+
+/// Returns the number of spans dropped so far by every tracer provider of the process because their
+/// export failed. See `estimated_queue_drops` for the spans dropped by a full queue.
+pub fn dropped_spans() -> u64 {
+    DROPPED_SPANS.load(Ordering::Relaxed)
+}
+
+
+/// Returns the estimated number of spans dropped so far by every tracer provider of the process because
+/// the queue of the batch span processor was full.
+///
+/// The batch span processor does not report its drops, so they are estimated from the spans entering and
+/// leaving the queue, and may be off, e.g. while an export is in flight.
+pub fn estimated_queue_drops() -> u64 {
+    ESTIMATED_QUEUE_DROPS.load(Ordering::Relaxed)
+}
+
+
+/// Returns the number of batches of spans dropped so far by every tracer provider of the process,
+/// because their export failed.
+pub fn dropped_batches() -> u64 {
+    DROPPED_BATCHES.load(Ordering::Relaxed)
+}
+
+
+/// Records spans estimated to be dropped by a full queue, apart from the spans whose export failed.
+/// # Arguments
+/// * `stats` - The counters of the tracer provider of the queue.
+/// * `count` - The estimated number of dropped spans.
+#[cfg(not(feature = "noop"))]
+pub(crate) fn record_queue_drop(stats: &ExportStats, count: u64) {
+    ESTIMATED_QUEUE_DROPS.fetch_add(count, Ordering::Relaxed);
+    stats.estimated_queue_drops.fetch_add(count, Ordering::Relaxed);
+    record_dropped_metrics(count, "queue_full_estimated");
+}
+
+
+/// Records a failed export and notifies the callbacks registered on the tracer provider.
+/// # Arguments
+/// * `stats` - The counters of the tracer provider of the exporter.
+/// * `event` - The failed export.
+#[cfg(not(feature = "noop"))]
+fn record_export_failure(stats: &ExportStats, event: ExportErrorEvent) {
+    DROPPED_SPANS.fetch_add(event.spans as u64, Ordering::Relaxed);
+    DROPPED_BATCHES.fetch_add(1, Ordering::Relaxed);
+    stats.dropped_spans.fetch_add(event.spans as u64, Ordering::Relaxed);
+    stats.dropped_batches.fetch_add(1, Ordering::Relaxed);
+    record_dropped_metrics(event.spans as u64, "export_failed");
+    if let Some(captured) = stats.captured.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+        captured.push(event.clone());
+    }
+    stats.notify(&event);
+}
+
+
+/// A span exporter that records the failed exports of the wrapped exporter as dropped batches.
+#[cfg(not(feature = "noop"))]
+#[derive(Debug)]
+pub struct ExportErrorExporter<E: SpanExporter> {
+    inner: E,
+    name: String,
+    stats: Arc<ExportStats>,
+}
+
+
+#[cfg(not(feature = "noop"))]
+impl<E: SpanExporter> ExportErrorExporter<E> {
+    /// Creates a new instance of `ExportErrorExporter`.
+    /// # Arguments
+    /// * `inner` - The exporter to delegate to.
+    /// * `name` - The name of the exporter, reported with its failed exports.
+    /// * `stats` - The counters of the tracer provider of the exporter.
+    /// # Returns
+    /// A new `ExportErrorExporter` instance.
+    pub(crate) fn new(inner: E, name: &str, stats: Arc<ExportStats>) -> Self {
+        Self { inner, name: name.to_string(), stats }
+    }
+}
+
+
+/// Implementation of the SpanExporter trait for ExportErrorExporter
+#[cfg(not(feature = "noop"))]
+impl<E: SpanExporter> SpanExporter for ExportErrorExporter<E> {
+    async fn export(&self, batch: Vec<SpanData>) -> OTelSdkResult {
+        let spans = batch.len();
+        let start = Instant::now();
+        let result = self.inner.export(batch).await;
+        if let Some(latencies) = self.stats.latencies.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+            latencies.push(start.elapsed());
+        }
+        result
+            .inspect(|_| {
+                self.stats.exported_spans.fetch_add(spans as u64, Ordering::Relaxed);
+            })
+            .inspect_err(|e| record_export_failure(&self.stats, ExportErrorEvent { exporter: self.name.clone(), spans, reason: e.to_string() }))
+    }
+
+    fn shutdown_with_timeout(&mut self, timeout: Duration) -> OTelSdkResult {
+        self.inner.shutdown_with_timeout(timeout)
+    }
+
+    fn force_flush(&mut self) -> OTelSdkResult {
+        self.inner.force_flush()
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        self.inner.set_resource(resource)
+    }
+}
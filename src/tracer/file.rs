@@ -0,0 +1,174 @@
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use opentelemetry::trace::SpanId;
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::error::{OTelSdkError, OTelSdkResult};
+use opentelemetry_sdk::trace::{SpanData, SpanExporter, TraceError};
+use opentelemetry_sdk::trace::SdkTracerProvider as SDKTracerProvider;
+use serde_json::json;
+use crate::config::{FileRotation, FileTraceConfig};
+#[cfg(not(feature = "noop"))]
+use crate::config::ExportPolicyConfig;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::id_generator::build_id_generator;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::sampling::build_sampler;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::{with_export_mode, with_span_limits};
+use crate::tracer::json::{json_attributes, pairs, unix_nanos};
+
+
+/// The file currently written by `FileSpanExporter`.
+#[derive(Debug)]
+struct CurrentFile {
+    file: File,
+    size: u64,
+    opened: Instant,
+}
+
+
+/// A span exporter that writes each span as one JSON line to a local file, rotating the file by size or age.
+///
+/// Rotated files are renamed to `<path>.<epoch millis>`, and a new file is created at the configured path,
+/// so an agent tailing the path follows the rotations.
+#[derive(Debug)]
+pub struct FileSpanExporter {
+    path: PathBuf,
+    rotation: FileRotation,
+    resource: Resource,
+    current: Mutex<Option<CurrentFile>>,
+}
+
+
+/// Opens the file at the given path for appending.
+fn open(path: &Path) -> std::io::Result<CurrentFile> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    let size = file.metadata()?.len();
+    Ok(CurrentFile { file, size, opened: Instant::now() })
+}
+
+
+impl FileSpanExporter {
+    /// Creates a new instance of `FileSpanExporter`.
+    ///
+    /// The file is opened on the first export.
+    /// # Arguments
+    /// * `config` - The file trace configuration.
+    /// # Returns
+    /// A new `FileSpanExporter` instance.
+    pub fn new(config: &FileTraceConfig) -> Self {
+        Self {
+            path: config.path.clone(),
+            rotation: config.rotation.clone(),
+            resource: Resource::builder().build(),
+            current: Mutex::new(None),
+        }
+    }
+
+    /// Renders a span as a JSON line.
+    fn render(&self, span: &SpanData) -> String {
+        let parent_span_id = (span.parent_span_id != SpanId::INVALID).then(|| span.parent_span_id.to_string());
+        let line = json!({
+            "trace_id": span.span_context.trace_id().to_string(),
+            "span_id": span.span_context.span_id().to_string(),
+            "parent_span_id": parent_span_id,
+            "name": span.name,
+            "kind": format!("{:?}", span.span_kind),
+            "start_time_unix_nano": unix_nanos(span.start_time).to_string(),
+            "end_time_unix_nano": unix_nanos(span.end_time).to_string(),
+            "status": format!("{:?}", span.status),
+            "scope": span.instrumentation_scope.name(),
+            "attributes": json_attributes(pairs(&span.attributes)),
+            "events": span.events.iter().map(|event| json!({
+                "name": event.name,
+                "time_unix_nano": unix_nanos(event.timestamp).to_string(),
+                "attributes": json_attributes(pairs(&event.attributes)),
+            })).collect::<Vec<_>>(),
+            "links": span.links.iter().map(|link| json!({
+                "trace_id": link.span_context.trace_id().to_string(),
+                "span_id": link.span_context.span_id().to_string(),
+                "attributes": json_attributes(pairs(&link.attributes)),
+            })).collect::<Vec<_>>(),
+            "resource": json_attributes(self.resource.iter()),
+        });
+        format!("{}\n", line)
+    }
+
+    /// Returns whether the current file must be rotated before writing the given number of bytes.
+    fn must_rotate(&self, current: &CurrentFile, len: u64) -> bool {
+        let too_large = self.rotation.max_size.is_some_and(|max| current.size > 0 && current.size + len > max);
+        let too_old = self.rotation.max_age.is_some_and(|max| current.opened.elapsed() >= max);
+        too_large || too_old
+    }
+
+    /// Renames the current file and opens a new one.
+    fn rotate(&self) -> std::io::Result<CurrentFile> {
+        let millis = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis()).unwrap_or_default();
+        let mut rotated = self.path.clone().into_os_string();
+        rotated.push(format!(".{}", millis));
+        std::fs::rename(&self.path, rotated)?;
+        open(&self.path)
+    }
+
+    /// Writes rendered spans to the current file, rotating it if needed.
+    fn write(&self, lines: &str) -> std::io::Result<()> {
+        let mut current = self.current.lock().unwrap_or_else(|e| e.into_inner());
+        let len = lines.len() as u64;
+        let file = match current.take() {
+            Some(file) if self.must_rotate(&file, len) => {
+                drop(file);
+                self.rotate()?
+            },
+            Some(file) => file,
+            None => open(&self.path)?,
+        };
+        let file = current.insert(file);
+        file.file.write_all(lines.as_bytes())?;
+        file.size += len;
+        Ok(())
+    }
+}
+
+
+/// Implementation of the SpanExporter trait for FileSpanExporter
+impl SpanExporter for FileSpanExporter {
+    async fn export(&self, batch: Vec<SpanData>) -> OTelSdkResult {
+        let lines: String = batch.iter().map(|span| self.render(span)).collect();
+        self.write(&lines).map_err(|e| OTelSdkError::InternalFailure(e.to_string()))
+    }
+
+    fn force_flush(&mut self) -> OTelSdkResult {
+        match self.current.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+            Some(current) => current.file.flush().map_err(|e| OTelSdkError::InternalFailure(e.to_string())),
+            None => Ok(()),
+        }
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        self.resource = resource.clone();
+    }
+}
+
+
+/// Returns a tracer provider that writes spans to a local file as JSON lines.
+/// # Arguments
+/// * `config` - The file trace configuration.
+/// * `resource` - The resource of the traces.
+#[cfg(not(feature = "noop"))]
+pub async fn get_file_tracer_provider(config: &FileTraceConfig, resource: &Resource) -> Result<SDKTracerProvider, TraceError> {
+    let builder = with_span_limits(SDKTracerProvider::builder(), &config.span_limits)
+        .with_resource(resource.clone())
+        .with_sampler(build_sampler(&config.sampler))
+        .with_id_generator(build_id_generator(&config.id_generator));
+    Ok(with_export_mode(builder, FileSpanExporter::new(config), config.export_mode, &ExportPolicyConfig::default(), "file").build())
+}
+
+
+/// Stub of `get_file_tracer_provider` with the `noop` feature, returning a tracer provider without exporter.
+#[cfg(feature = "noop")]
+pub async fn get_file_tracer_provider(_config: &FileTraceConfig, _resource: &Resource) -> Result<SDKTracerProvider, TraceError> {
+    Ok(SDKTracerProvider::builder().build())
+}
@@ -1,26 +1,156 @@
+use std::fmt::{Debug, Formatter};
 use std::str::FromStr;
-use std::sync::Arc;
-use tokio::runtime::Runtime;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
 use tonic::metadata::{Ascii, MetadataKey};
 use crate::auth::GetToken;
 
 
+/// Interval at which the cached authorization headers are refreshed.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Delay before retrying a failed retrieval of the authorization headers, doubled after each
+/// consecutive failure up to `REFRESH_INTERVAL`.
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+
+/// The last retrieved authorization headers, with the error of the last retrieval if it failed.
+#[derive(Default)]
+struct CachedHeaders {
+    headers: Option<Vec<(String, String)>>,
+    error: Option<String>,
+}
+
+
+/// The authorization headers of a token provider, cached for the synchronous gRPC interceptors.
+///
+/// The headers are retrieved by a background task, started by the first interceptor using the cache,
+/// and by the startup warm-up awaited by `OpenTelemetryObject::ready`. A failed retrieval keeps the
+/// last headers and is retried after a short delay.
+#[derive(Default)]
+pub(crate) struct HeaderCache {
+    cached: Mutex<CachedHeaders>,
+    refreshing: OnceLock<()>,
+}
+
+
+impl HeaderCache {
+    /// Stores the result of a retrieval of the authorization headers.
+    /// # Arguments
+    /// * `retrieved` - The retrieved headers, or the error of the retrieval.
+    /// # Returns
+    /// Whether the retrieval succeeded.
+    pub(crate) fn store(&self, retrieved: anyhow::Result<Vec<(String, String)>>) -> bool {
+        let mut cached = self.cached.lock().unwrap_or_else(|e| e.into_inner());
+        match retrieved {
+            Ok(headers) => {
+                *cached = CachedHeaders { headers: Some(headers), error: None };
+                true
+            },
+            Err(err) => {
+                cached.error = Some(err.to_string());
+                false
+            },
+        }
+    }
+
+    /// Returns the cached headers, or the reason why none were retrieved yet.
+    fn headers(&self) -> Result<Vec<(String, String)>, String> {
+        let cached = self.cached.lock().unwrap_or_else(|e| e.into_inner());
+        match (&cached.headers, &cached.error) {
+            (Some(headers), _) => Ok(headers.clone()),
+            (None, Some(error)) => Err(format!("authorization headers not retrieved: {}", error)),
+            (None, None) => Err("authorization headers not retrieved yet".to_string()),
+        }
+    }
+
+    /// Starts the background refresh of the headers, once per cache, until the cache is dropped.
+    /// Must be called from within a Tokio runtime.
+    fn start_refresh(self: &Arc<Self>, token_provider: Arc<dyn GetToken>) {
+        let cache = Arc::downgrade(self);
+        self.refreshing.get_or_init(move || {
+            tokio::spawn(async move {
+                let mut retry_delay = RETRY_DELAY;
+                loop {
+                    let retrieved = token_provider.get_auth_headers().await;
+                    let Some(cache) = cache.upgrade() else { break };
+                    let delay = if cache.store(retrieved) {
+                        retry_delay = RETRY_DELAY;
+                        REFRESH_INTERVAL
+                    } else {
+                        let delay = retry_delay;
+                        retry_delay = (retry_delay * 2).min(REFRESH_INTERVAL);
+                        delay
+                    };
+                    drop(cache);
+                    tokio::time::sleep(delay).await;
+                }
+            });
+        });
+    }
+}
+
+
+/// Implementation of the Debug trait for HeaderCache, which does not print the headers
+impl Debug for HeaderCache {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let cached = self.cached.lock().unwrap_or_else(|e| e.into_inner());
+        f.debug_struct("HeaderCache")
+            .field("retrieved", &cached.headers.is_some())
+            .field("error", &cached.error)
+            .finish()
+    }
+}
+
+
 /// A gRPC interceptor that adds authorization metadata to requests.
+///
+/// Tonic interceptors are synchronous, so the headers are retrieved asynchronously by a background
+/// task on the Tokio runtime and cached; `call` only reads the cache and never blocks. Requests sent
+/// before the first successful retrieval fail as unavailable, which the exporters retry.
 #[derive(Clone)]
 pub struct TonicInterceptor {
-    token_provider: Arc<dyn GetToken>,
+    headers: Arc<HeaderCache>,
+    metadata: Vec<(String, String)>,
 }
 
 
 /// Implementation of TonicInterceptor
 impl TonicInterceptor {
     /// Creates a new instance of `TonicInterceptor`.
+    ///
+    /// The headers are retrieved in the background, without delaying startup, and refreshed until
+    /// the interceptor is dropped. Must be called from within a Tokio runtime.
     /// # Arguments
     /// * `token_provider` - An `Arc<dyn GetToken>` to provide access tokens.
     /// # Returns
     /// A new `TonicInterceptor` instance.
     pub fn new(token_provider: Arc<dyn GetToken>) -> Self {
-        Self { token_provider }
+        Self::with_cache(token_provider, Arc::default())
+    }
+
+    /// Creates a new instance of `TonicInterceptor` reading a shared header cache, e.g. the one of an
+    /// `AuthContext` filled by the startup warm-up, and refreshing it in the background if no other
+    /// interceptor does. Must be called from within a Tokio runtime.
+    /// # Arguments
+    /// * `token_provider` - The token provider refreshing the cache.
+    /// * `headers` - The header cache.
+    /// # Returns
+    /// A new `TonicInterceptor` instance.
+    pub(crate) fn with_cache(token_provider: Arc<dyn GetToken>, headers: Arc<HeaderCache>) -> Self {
+        headers.start_refresh(token_provider);
+        Self { headers, metadata: Vec::new() }
+    }
+
+    /// Adds fixed metadata to every request, e.g. tenant identifiers or routing hints.
+    /// Authorization headers with the same key take precedence.
+    /// # Arguments
+    /// * `metadata` - The metadata key-value pairs.
+    /// # Returns
+    /// The `TonicInterceptor` instance, with the metadata.
+    pub fn with_metadata(mut self, metadata: Vec<(String, String)>) -> Self {
+        self.metadata = metadata;
+        self
     }
 }
 
@@ -28,21 +158,13 @@ impl TonicInterceptor {
 /// Implementation of the gRPC interceptor trait for TonicInterceptor
 impl tonic::service::Interceptor for TonicInterceptor {
     fn call(&mut self, mut req: tonic::Request<()>) -> anyhow::Result<tonic::Request<()>, tonic::Status> {
-        let rt = Runtime::new()?;
-        let headers = rt.block_on(async {self.token_provider.get_auth_headers().await}).map_err(|err| {tonic::Status::unauthenticated(format!("{}", err))})?;
+        let headers = self.headers.headers().map_err(tonic::Status::unavailable)?;
 
-        for (key, value) in headers {
+        for (key, value) in self.metadata.iter().cloned().chain(headers) {
             let k: MetadataKey<Ascii> = MetadataKey::from_str(key.as_str()).map_err(|err| tonic::Status::unauthenticated(format!("{}", err)))?;
             req.metadata_mut().insert(k, value.parse().map_err(|e| tonic::Status::internal(format!("Failed to parse metadata value: {}", e)))?);
         }
 
-        match std::env::var("GOOGLE_PROJECT_ID") {
-            Ok(val) => {
-                req.metadata_mut().insert("x-goog-user-project", val.parse().map_err(|e| tonic::Status::internal(format!("Failed to parse metadata value: {}", e)))?);
-            },
-            Err(_) => {},
-        };
-
         Ok(req)
     }
 }
@@ -1,28 +1,177 @@
 pub mod interceptor;
 
 use std::sync::Arc;
+#[cfg(not(feature = "noop"))]
 use opentelemetry_otlp::{SpanExporter, WithExportConfig, WithTonicConfig};
+use opentelemetry_sdk::Resource;
 use opentelemetry_sdk::trace::TraceError;
-use tonic::transport::ClientTlsConfig;
-use crate::config::OTLPTraceConfig;use opentelemetry_sdk::trace::SdkTracerProvider as SDKTracerProvider;
+#[cfg(not(feature = "noop"))]
+use tonic::codegen::http::Uri;
+#[cfg(not(feature = "noop"))]
+use tonic::codegen::http::uri::Scheme;
+#[cfg(not(feature = "noop"))]
+use tonic::transport::{Channel, ClientTlsConfig, Endpoint};
+#[cfg(not(feature = "noop"))]
+use tower::Service;
+#[cfg(not(feature = "noop"))]
+use crate::config::{ExportPolicyConfig, GrpcChannelConfig};
+use crate::config::OTLPTraceConfig;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::OtlpSpanExporter;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::grpc::interceptor::TonicInterceptor;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::binary_encoding::BinaryEncodingExporter;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::disk_buffer::DiskBufferExporter;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::insecure::{insecure_https_connector, warn_insecure};
+#[cfg(not(feature = "noop"))]
+use crate::tracer::retry::RetryExporter;
+use opentelemetry_sdk::trace::SdkTracerProvider as SDKTracerProvider;
 use crate::auth::GetToken;
-use crate::resource::get_resource;
+#[cfg(not(feature = "noop"))]
+use crate::auth::AuthContext;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::sampling::build_sampler;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::id_generator::build_id_generator;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::{otlp_compression, with_export_mode, with_span_limits};
 
 
-/// Initializes the OTLP tracer provider.
-pub async fn init_grpc_otlp_tracer_provider(otlp_config: &OTLPTraceConfig, service_name: &String, token_provider: Arc<dyn GetToken>) -> Result<SDKTracerProvider, TraceError> {
-    let exporter = SpanExporter::builder()
+/// Applies the timeout and concurrency limit of the export policy to a gRPC endpoint.
+#[cfg(not(feature = "noop"))]
+fn with_policy(endpoint: Endpoint, policy: &ExportPolicyConfig) -> Endpoint {
+    let endpoint = endpoint.timeout(policy.timeout);
+    match policy.concurrency_limit {
+        Some(limit) => endpoint.concurrency_limit(limit),
+        None => endpoint,
+    }
+}
+
+
+/// Applies the keep-alive and connect timeout of the channel configuration to a gRPC endpoint.
+#[cfg(not(feature = "noop"))]
+fn with_channel_config(endpoint: Endpoint, config: &GrpcChannelConfig) -> Endpoint {
+    let mut endpoint = endpoint
+        .tcp_keepalive(config.tcp_keepalive)
+        .keep_alive_while_idle(config.keepalive_while_idle);
+    if let Some(interval) = config.http2_keepalive_interval {
+        endpoint = endpoint.http2_keep_alive_interval(interval);
+    }
+    if let Some(timeout) = config.keepalive_timeout {
+        endpoint = endpoint.keep_alive_timeout(timeout);
+    }
+    match config.connect_timeout {
+        Some(timeout) => endpoint.connect_timeout(timeout),
+        None => endpoint,
+    }
+}
+
+
+/// Builds the gRPC export channel.
+///
+/// The channel applies the timeout and concurrency limit of the export policy as tower middleware,
+/// and the keep-alive and connect timeout of the channel configuration. It connects lazily, on the first export.
+/// # Arguments
+/// * `endpoint` - The endpoint of the OTLP collector.
+/// * `policy` - The resilience policy of the exports.
+/// * `config` - The tuning of the channel.
+#[cfg(not(feature = "noop"))]
+pub(crate) fn get_grpc_channel(endpoint: &str, policy: &ExportPolicyConfig, config: &GrpcChannelConfig) -> Result<Channel, TraceError> {
+    let endpoint = Endpoint::from_shared(endpoint.to_string())
+        .and_then(|endpoint| endpoint.tls_config(ClientTlsConfig::new().with_native_roots()))
+        .map_err(|err| TraceError::from(err.to_string()))?;
+    Ok(with_channel_config(with_policy(endpoint, policy), config).connect_lazy())
+}
+
+
+/// Builds a gRPC export channel which does not verify the certificate of the collector, for development
+/// collectors with self-signed certificates.
+///
+/// Tonic rejects `https` endpoints without its own TLS configuration, which always verifies certificates,
+/// so the channel connects to the `http` endpoint and its connector establishes TLS for the original scheme,
+/// while the requests keep the original endpoint as their origin, i.e. the `https` scheme.
+/// # Arguments
+/// * `endpoint` - The endpoint of the OTLP collector.
+/// * `policy` - The resilience policy of the exports.
+/// * `config` - The tuning of the channel.
+#[cfg(not(feature = "noop"))]
+pub(crate) fn get_insecure_grpc_channel(endpoint: &str, policy: &ExportPolicyConfig, config: &GrpcChannelConfig) -> Result<Channel, TraceError> {
+    warn_insecure("grpc");
+    let https = endpoint.starts_with("https://");
+    let connector = insecure_https_connector(true).map_err(|err| TraceError::from(err.to_string()))?;
+    let connector = tower::service_fn(move |uri: Uri| {
+        let mut connector = connector.clone();
+        let mut parts = uri.into_parts();
+        if https {
+            parts.scheme = Some(Scheme::HTTPS);
+        }
+        let uri = Uri::from_parts(parts);
+        async move { connector.call(uri?).await }
+    });
+    let origin = endpoint.parse::<Uri>().map_err(|err| TraceError::from(err.to_string()))?;
+    let endpoint = Endpoint::from_shared(endpoint.replacen("https://", "http://", 1))
+        .map_err(|err| TraceError::from(err.to_string()))?
+        .origin(origin);
+    Ok(with_channel_config(with_policy(endpoint, policy), config).connect_with_connector_lazy(connector))
+}
+
+
+/// Builds the OTLP gRPC span exporter, authenticating with the token provider and the header cache of the
+/// authentication configuration in the `AuthContext`.
+#[cfg(not(feature = "noop"))]
+pub(crate) async fn get_grpc_span_exporter(otlp_config: &OTLPTraceConfig, auth_context: &AuthContext) -> Result<OtlpSpanExporter, TraceError> {
+    let token_provider = auth_context.token_provider(&otlp_config.auth_config);
+    grpc_span_exporter(otlp_config, token_provider, auth_context.interceptor(&otlp_config.auth_config))
+}
+
+
+/// Builds the OTLP gRPC span exporter with the given interceptor, rejecting the authentication configurations
+/// signing each request, which the interceptor cannot do.
+#[cfg(not(feature = "noop"))]
+fn grpc_span_exporter(otlp_config: &OTLPTraceConfig, token_provider: Arc<dyn GetToken>, interceptor: TonicInterceptor) -> Result<OtlpSpanExporter, TraceError> {
+    if otlp_config.auth_config.signs_requests() {
+        return Err(TraceError::from("HMAC authentication is not supported by the gRPC trace exporter, as its headers are not bound to a request"));
+    }
+    let mut builder = SpanExporter::builder()
         .with_tonic()
-        .with_endpoint(otlp_config.endpoint.clone())
-        .with_tls_config(ClientTlsConfig::new().with_native_roots())
-        .with_interceptor(interceptor::TonicInterceptor::new(token_provider))
+        .with_channel(if otlp_config.insecure_skip_verify {
+            get_insecure_grpc_channel(&otlp_config.endpoint, &otlp_config.export_policy, &otlp_config.channel)?
+        } else {
+            get_grpc_channel(&otlp_config.endpoint, &otlp_config.export_policy, &otlp_config.channel)?
+        })
+        .with_timeout(otlp_config.export_policy.timeout)
+        .with_interceptor(interceptor.with_metadata(otlp_config.metadata.clone()));
+    if let Some(compression) = otlp_compression(otlp_config.compression) {
+        builder = builder.with_compression(compression);
+    }
+    let exporter = builder
         .build()
         .map_err(|err| TraceError::from(err.to_string()))?;
 
-    Ok(SDKTracerProvider::builder()
-        .with_resource(get_resource(service_name))
-        .with_batch_exporter(exporter)
-        .build())
+    let exporter = RetryExporter::new(exporter, &otlp_config.export_policy, otlp_config.export_mode).with_token_provider(token_provider);
+    let exporter = DiskBufferExporter::new(exporter, &otlp_config.export_policy);
+    Ok(BinaryEncodingExporter::new(exporter, otlp_config.binary_policy))
+}
+
+
+/// Initializes the OTLP tracer provider.
+#[cfg(not(feature = "noop"))]
+pub async fn init_grpc_otlp_tracer_provider(otlp_config: &OTLPTraceConfig, resource: &Resource, token_provider: Arc<dyn GetToken>) -> Result<SDKTracerProvider, TraceError> {
+    let builder = with_span_limits(SDKTracerProvider::builder(), &otlp_config.span_limits)
+        .with_resource(resource.clone())
+        .with_sampler(build_sampler(&otlp_config.sampler))
+        .with_id_generator(build_id_generator(&otlp_config.id_generator));
+    Ok(with_export_mode(builder, grpc_span_exporter(otlp_config, token_provider.clone(), TonicInterceptor::new(token_provider))?, otlp_config.export_mode, &otlp_config.export_policy, "grpc").build())
+}
+
+
+/// Stub of `init_grpc_otlp_tracer_provider` with the `noop` feature, returning a tracer provider without exporter.
+#[cfg(feature = "noop")]
+pub async fn init_grpc_otlp_tracer_provider(_otlp_config: &OTLPTraceConfig, _resource: &Resource, _token_provider: Arc<dyn GetToken>) -> Result<SDKTracerProvider, TraceError> {
+    Ok(SDKTracerProvider::builder().build())
 }
 
 
@@ -0,0 +1,33 @@
+use opentelemetry::trace::{Span, Tracer, TracerProvider};
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use tokio::task::JoinHandle;
+use crate::config::HeartbeatConfig;
+use crate::self_observability::record_heartbeat;
+
+
+/// Name of the synthetic span exported on each heartbeat.
+const HEARTBEAT_SPAN_NAME: &str = "heartbeat";
+
+
+/// Spawns a task that exports a `heartbeat` span and increments the `otel.heartbeat` counter
+/// at the configured interval, so a healthy but idle service can be told apart from a service that is down.
+///
+/// The task runs until the returned handle is aborted.
+///
+/// # Arguments
+///
+/// * `config` - The heartbeat configuration.
+/// * `provider` - The tracer provider used to export the heartbeat spans.
+pub fn spawn_heartbeat(config: &HeartbeatConfig, provider: &SdkTracerProvider) -> JoinHandle<()> {
+    let tracer = provider.tracer("rust-otel-setup");
+    let period = config.interval;
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            tracer.start(HEARTBEAT_SPAN_NAME).end();
+            record_heartbeat();
+        }
+    })
+}
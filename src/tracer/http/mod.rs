@@ -1,38 +1,69 @@
+use std::fmt::Debug;
 use std::str::FromStr;
 use std::sync::Arc;
+#[cfg(not(feature = "noop"))]
 use std::time::Duration;
 use async_trait::async_trait;
-use hyper_util::client::legacy::connect::{Connect, HttpConnector};
+#[cfg(not(feature = "noop"))]
+use http_body_util::{BodyExt, Full};
+#[cfg(not(feature = "noop"))]
+use hyper_util::client::legacy::Client;
+use hyper_util::client::legacy::connect::Connect;
+#[cfg(not(feature = "noop"))]
+use hyper_util::client::legacy::connect::HttpConnector;
+#[cfg(not(feature = "noop"))]
+use hyper_util::rt::TokioExecutor;
 use opentelemetry_http::{Bytes, HttpClient, HttpError, Request, Response};
 use opentelemetry_http::hyper::HyperClient;
+#[cfg(not(feature = "noop"))]
 use opentelemetry_otlp::{SpanExporter, WithHttpConfig, WithExportConfig};
+use opentelemetry_sdk::Resource;
 use opentelemetry_sdk::trace::TraceError;
 use opentelemetry_sdk::trace::SdkTracerProvider as SDKTracerProvider;
 use anyhow::Result;
-use tokio::runtime::Runtime;
 use tonic::codegen::http::HeaderName;
 use crate::auth::GetToken;
 use crate::config::OTLPTraceConfig;
-use crate::resource::get_resource;
+#[cfg(not(feature = "noop"))]
+use crate::config::HttpVersion;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::OtlpSpanExporter;
+use crate::tracer::partial_success::handle_export_response;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::binary_encoding::BinaryEncodingExporter;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::disk_buffer::DiskBufferExporter;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::insecure::{insecure_https_connector, warn_insecure};
+use crate::tracer::retry::transport_error;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::retry::RetryExporter;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::sampling::build_sampler;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::id_generator::build_id_generator;
+use crate::tracer::on_tokio;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::{http_protocol, otlp_compression, with_export_mode, with_span_limits};
 
 
 /// A Hyper-based HTTP client that adds authentication tokens to requests.
 #[derive(Debug, Clone)]
-pub struct HyperTracerClient<HttpConnector: Clone+Send+Sync+Connect+'static>{
-    client: HyperClient<HttpConnector>,
+pub struct HyperTracerClient{
+    client: Arc<dyn HttpClient>,
     token_provider: Arc<dyn GetToken>,
 }
 
 /// Implementation of HyperTracerClient
-impl HyperTracerClient<HttpConnector> {
+impl HyperTracerClient {
     /// Creates a new instance of `HyperTracerClient`.
     /// # Arguments
-    /// * `client` - A `HyperClient<HttpConnector>` to send HTTP requests.
+    /// * `client` - A `HyperClient` to send HTTP requests.
     /// * `token_provider` - An `Arc<dyn GetToken>` to provide access tokens.
     /// # Returns
     /// A new `HyperTracerClient` instance.
-    pub async fn new(client: HyperClient<HttpConnector>, token_provider: Arc<dyn GetToken>) -> Result<Self> {
-        Ok( Self{ client, token_provider })
+    pub async fn new<C: Clone+Send+Sync+Connect+Debug+'static>(client: HyperClient<C>, token_provider: Arc<dyn GetToken>) -> Result<Self> {
+        Ok( Self{ client: Arc::new(client), token_provider })
     }
 
     /// Adds an authorization token to the request if available.
@@ -41,9 +72,8 @@ impl HyperTracerClient<HttpConnector> {
     /// # Returns
     /// The modified HTTP request with the authorization header if a token is available.
     async fn get_token(&self, request: Request<Bytes>) -> Result<Request<Bytes>> {
-        let rt = Runtime::new()?;
-        let headers = rt.block_on(async { self.token_provider.get_auth_headers().await})?;
         let (mut parts, bts) = request.into_parts();
+        let headers = on_tokio(self.token_provider.get_request_auth_headers(parts.method.as_str(), parts.uri.path(), &bts)).await?;
         for (key, value) in headers {
             let hn = HeaderName::from_str(key.as_str())?;
             parts.headers.insert(hn, value.parse()?);
@@ -56,39 +86,161 @@ impl HyperTracerClient<HttpConnector> {
 
 /// Implementation of the HttpClient trait for HyperTracerClient
 #[async_trait]
-impl HttpClient for HyperTracerClient<HttpConnector> {
+impl HttpClient for HyperTracerClient {
     async fn send_bytes(&self, request: Request<Bytes>) -> Result<Response<Bytes>, HttpError> {
-        let rt = Runtime::new()?;
         let request = self.get_token(request).await?;
-        rt.block_on(async { self.client.send_bytes(request).await})
+        let path = request.uri().path().to_string();
+        let response = on_tokio(self.client.send_bytes(request)).await.map_err(transport_error)?;
+        handle_export_response(&path, &response);
+        if matches!(response.status().as_u16(), 401 | 403) {
+            self.token_provider.record_rejection(&format!("export rejected with status {}", response.status()));
+        }
+        Ok(response)
     }
 }
 
 
-/// Initializes the OTLP HTTP tracer provider with authentication.
+/// A Hyper-based HTTP client speaking HTTP/2 with prior knowledge, which `HyperClient` cannot be configured for.
+#[cfg(not(feature = "noop"))]
+#[derive(Debug, Clone)]
+struct Http2PriorKnowledgeClient<C: Clone+Send+Sync+Connect+Debug+'static> {
+    client: Client<C, Full<Bytes>>,
+    timeout: Duration,
+}
+
+
+#[cfg(not(feature = "noop"))]
+impl<C: Clone+Send+Sync+Connect+Debug+'static> Http2PriorKnowledgeClient<C> {
+    /// Creates a new instance of `Http2PriorKnowledgeClient`.
+    /// # Arguments
+    /// * `connector` - The connector of the HTTP client.
+    /// * `timeout` - The timeout of each request.
+    /// # Returns
+    /// A new `Http2PriorKnowledgeClient` instance.
+    fn new(connector: C, timeout: Duration) -> Self {
+        let client = Client::builder(TokioExecutor::new())
+            .http2_only(true)
+            .build(connector);
+        Self { client, timeout }
+    }
+}
+
+
+/// Implementation of the HttpClient trait for Http2PriorKnowledgeClient
+#[cfg(not(feature = "noop"))]
+#[async_trait]
+impl<C: Clone+Send+Sync+Connect+Debug+'static> HttpClient for Http2PriorKnowledgeClient<C> {
+    async fn send_bytes(&self, request: Request<Bytes>) -> Result<Response<Bytes>, HttpError> {
+        let (parts, body) = request.into_parts();
+        let request = Request::from_parts(parts, Full::new(body));
+        let response = tokio::time::timeout(self.timeout, self.client.request(request)).await??;
+        let (parts, body) = response.into_parts();
+        Ok(Response::from_parts(parts, body.collect().await?.to_bytes()))
+    }
+}
+
+
+/// Builds the OTLP HTTP span exporter with authentication.
 /// # Arguments
 /// * `otlp_config` - The OTLP trace configuration.
-/// * `service_name` - The name of the service.
 /// * `token_provider` - An `Arc<dyn GetToken>` to provide access tokens.
 /// # Returns
-/// A `Result` containing the initialized `SDKTracerProvider` or a `TraceError`.
-pub async fn get_http_tracer_provider(otlp_config: &OTLPTraceConfig, service_name: &String, token_provider: Arc<dyn GetToken>) -> Result<SDKTracerProvider, TraceError> {
-    let hyper_tracer_client = HyperTracerClient::new(
-        HyperClient::with_default_connector(Duration::from_secs(5), None),
-        token_provider,
-    ).await.map_err(|err| TraceError::from(err.to_string()))?;
+/// A `Result` containing the span exporter or a `TraceError`.
+#[cfg(not(feature = "noop"))]
+pub(crate) async fn get_http_span_exporter(otlp_config: &OTLPTraceConfig, token_provider: Arc<dyn GetToken>) -> Result<OtlpSpanExporter, TraceError> {
+    if otlp_config.insecure_skip_verify {
+        warn_insecure("http");
+        let connector = insecure_https_connector(false).map_err(|err| TraceError::from(err.to_string()))?;
+        return get_http_span_exporter_with_connector(otlp_config, token_provider, connector).await;
+    }
+    get_http_span_exporter_with_connector(otlp_config, token_provider, HttpConnector::new()).await
+}
+
+
+/// Builds the OTLP HTTP span exporter with authentication, sending requests through the given connector.
+/// # Arguments
+/// * `otlp_config` - The OTLP trace configuration. Its export timeout applies to each request, and its HTTP
+///   version to the connections.
+/// * `token_provider` - An `Arc<dyn GetToken>` to provide access tokens.
+/// * `connector` - The connector of the HTTP client, e.g. with a custom DNS resolver or TLS stack.
+/// # Returns
+/// A `Result` containing the span exporter or a `TraceError`.
+#[cfg(not(feature = "noop"))]
+async fn get_http_span_exporter_with_connector<C: Clone+Send+Sync+Connect+Debug+'static>(otlp_config: &OTLPTraceConfig, token_provider: Arc<dyn GetToken>, connector: C) -> Result<OtlpSpanExporter, TraceError> {
+    let timeout = otlp_config.export_policy.timeout;
+    let hyper_tracer_client = match otlp_config.http_version {
+        HttpVersion::Http2 => HyperTracerClient {
+            client: Arc::new(Http2PriorKnowledgeClient::new(connector, timeout)),
+            token_provider,
+        },
+        HttpVersion::Auto | HttpVersion::Http1 => HyperTracerClient::new(HyperClient::new(connector, timeout, None), token_provider)
+            .await
+            .map_err(|err| TraceError::from(err.to_string()))?,
+    };
 
-    let span_exporter = SpanExporter::builder()
+    let mut builder = SpanExporter::builder()
             .with_http()
             .with_endpoint(otlp_config.endpoint.clone())
-            .with_http_client(hyper_tracer_client)
-            .build()
-            .map_err(|err| TraceError::from(err.to_string()))?;
-
-    Ok(
-        SDKTracerProvider::builder()
-            .with_resource(get_resource(service_name))
-            .with_batch_exporter(span_exporter)
-            .build()
-    )
+            .with_protocol(http_protocol(otlp_config.encoding))
+            .with_http_client(hyper_tracer_client);
+    if let Some(compression) = otlp_compression(otlp_config.compression) {
+        builder = builder.with_compression(compression);
+    }
+    let span_exporter = builder
+        .build()
+        .map_err(|err| TraceError::from(err.to_string()))?;
+
+    let exporter = DiskBufferExporter::new(RetryExporter::new(span_exporter, &otlp_config.export_policy, otlp_config.export_mode), &otlp_config.export_policy);
+    Ok(BinaryEncodingExporter::new(exporter, otlp_config.binary_policy))
+}
+
+
+/// Initializes the OTLP HTTP tracer provider with authentication.
+/// # Arguments
+/// * `otlp_config` - The OTLP trace configuration.
+/// * `resource` - The resource of the traces.
+/// * `token_provider` - An `Arc<dyn GetToken>` to provide access tokens.
+/// # Returns
+/// A `Result` containing the initialized `SDKTracerProvider` or a `TraceError`.
+#[cfg(not(feature = "noop"))]
+pub async fn get_http_tracer_provider(otlp_config: &OTLPTraceConfig, resource: &Resource, token_provider: Arc<dyn GetToken>) -> Result<SDKTracerProvider, TraceError> {
+    let builder = with_span_limits(SDKTracerProvider::builder(), &otlp_config.span_limits)
+        .with_resource(resource.clone())
+        .with_sampler(build_sampler(&otlp_config.sampler))
+        .with_id_generator(build_id_generator(&otlp_config.id_generator));
+    Ok(with_export_mode(builder, get_http_span_exporter(otlp_config, token_provider).await?, otlp_config.export_mode, &otlp_config.export_policy, "http").build())
+}
+
+
+/// Stub of `get_http_tracer_provider` with the `noop` feature, returning a tracer provider without exporter.
+#[cfg(feature = "noop")]
+pub async fn get_http_tracer_provider(_otlp_config: &OTLPTraceConfig, _resource: &Resource, _token_provider: Arc<dyn GetToken>) -> Result<SDKTracerProvider, TraceError> {
+    Ok(SDKTracerProvider::builder().build())
+}
+
+
+/// Initializes the OTLP HTTP tracer provider with authentication, sending requests through a pre-built
+/// connector, e.g. with a custom DNS resolver or TLS stack.
+/// # Arguments
+/// * `otlp_config` - The OTLP trace configuration.
+/// * `resource` - The resource of the traces.
+/// * `token_provider` - An `Arc<dyn GetToken>` to provide access tokens.
+/// * `connector` - The connector of the HTTP client. It must not negotiate HTTP/2 when the configured HTTP
+///   version is `HttpVersion::Http1`.
+/// # Returns
+/// A `Result` containing the initialized `SDKTracerProvider` or a `TraceError`.
+#[cfg(not(feature = "noop"))]
+pub async fn get_http_tracer_provider_with_connector<C: Clone+Send+Sync+Connect+Debug+'static>(otlp_config: &OTLPTraceConfig, resource: &Resource, token_provider: Arc<dyn GetToken>, connector: C) -> Result<SDKTracerProvider, TraceError> {
+    let builder = with_span_limits(SDKTracerProvider::builder(), &otlp_config.span_limits)
+        .with_resource(resource.clone())
+        .with_sampler(build_sampler(&otlp_config.sampler))
+        .with_id_generator(build_id_generator(&otlp_config.id_generator));
+    Ok(with_export_mode(builder, get_http_span_exporter_with_connector(otlp_config, token_provider, connector).await?, otlp_config.export_mode, &otlp_config.export_policy, "http").build())
+}
+
+
+/// Stub of `get_http_tracer_provider_with_connector` with the `noop` feature, returning a tracer provider without exporter.
+#[cfg(feature = "noop")]
+pub async fn get_http_tracer_provider_with_connector<C: Clone+Send+Sync+Connect+Debug+'static>(_otlp_config: &OTLPTraceConfig, _resource: &Resource, _token_provider: Arc<dyn GetToken>, _connector: C) -> Result<SDKTracerProvider, TraceError> {
+    Ok(SDKTracerProvider::builder().build())
 }
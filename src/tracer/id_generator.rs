@@ -0,0 +1,73 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+use opentelemetry::trace::{SpanId, TraceId};
+use opentelemetry_sdk::trace::{IdGenerator, RandomIdGenerator};
+use crate::config::CustomIdGenerator;
+#[cfg(not(feature = "noop"))]
+use crate::config::IdGeneratorConfig;
+
+
+/// An ID generator producing AWS X-Ray compatible trace IDs.
+///
+/// The first 4 bytes of each trace ID are the epoch seconds of its creation, in big-endian order,
+/// and the remaining 12 bytes are random. Span IDs are random.
+#[derive(Debug, Default)]
+pub struct XRayIdGenerator {
+    random: RandomIdGenerator,
+}
+
+
+/// Implementation of the IdGenerator trait for XRayIdGenerator
+impl IdGenerator for XRayIdGenerator {
+    fn new_trace_id(&self) -> TraceId {
+        let mut bytes = self.random.new_trace_id().to_bytes();
+        let secs = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() as u32;
+        bytes[..4].copy_from_slice(&secs.to_be_bytes());
+        TraceId::from_bytes(bytes)
+    }
+
+    fn new_span_id(&self) -> SpanId {
+        self.random.new_span_id()
+    }
+}
+
+
+/// The ID generator of an `IdGeneratorConfig`.
+#[cfg(not(feature = "noop"))]
+#[derive(Debug)]
+pub struct ConfiguredIdGenerator(Box<dyn IdGenerator>);
+
+
+/// Returns the ID generator of the given configuration.
+#[cfg(not(feature = "noop"))]
+pub fn build_id_generator(config: &IdGeneratorConfig) -> ConfiguredIdGenerator {
+    ConfiguredIdGenerator(match config {
+        IdGeneratorConfig::Random => Box::new(RandomIdGenerator::default()),
+        IdGeneratorConfig::XRay => Box::new(XRayIdGenerator::default()),
+        IdGeneratorConfig::Custom(custom) => Box::new(custom.clone()),
+    })
+}
+
+
+/// Implementation of the IdGenerator trait for ConfiguredIdGenerator
+#[cfg(not(feature = "noop"))]
+impl IdGenerator for ConfiguredIdGenerator {
+    fn new_trace_id(&self) -> TraceId {
+        self.0.new_trace_id()
+    }
+
+    fn new_span_id(&self) -> SpanId {
+        self.0.new_span_id()
+    }
+}
+
+
+/// Implementation of the IdGenerator trait for CustomIdGenerator, delegating to the user generator.
+impl IdGenerator for CustomIdGenerator {
+    fn new_trace_id(&self) -> TraceId {
+        self.0.new_trace_id()
+    }
+
+    fn new_span_id(&self) -> SpanId {
+        self.0.new_span_id()
+    }
+}
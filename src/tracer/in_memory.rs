@@ -0,0 +1,31 @@
+use opentelemetry_sdk::error::OTelSdkResult;
+use opentelemetry_sdk::trace::{SpanData, SpanExporter};
+use crate::config::SpanStore;
+
+
+/// A span exporter that keeps the exported spans in a `SpanStore`, for integration tests.
+#[derive(Debug)]
+pub struct InMemoryExporter {
+    store: SpanStore,
+}
+
+
+impl InMemoryExporter {
+    /// Creates a new instance of `InMemoryExporter`.
+    /// # Arguments
+    /// * `store` - The store receiving the exported spans.
+    /// # Returns
+    /// A new `InMemoryExporter` instance.
+    pub fn new(store: SpanStore) -> Self {
+        Self { store }
+    }
+}
+
+
+/// Implementation of the SpanExporter trait for InMemoryExporter
+impl SpanExporter for InMemoryExporter {
+    async fn export(&self, batch: Vec<SpanData>) -> OTelSdkResult {
+        self.store.0.lock().unwrap_or_else(|e| e.into_inner()).extend(batch);
+        Ok(())
+    }
+}
@@ -0,0 +1,91 @@
+use std::sync::Mutex;
+#[cfg(not(feature = "noop"))]
+use std::sync::Arc;
+#[cfg(not(feature = "noop"))]
+use hyper_rustls::{HttpsConnector, HttpsConnectorBuilder};
+#[cfg(not(feature = "noop"))]
+use hyper_util::client::legacy::connect::HttpConnector;
+#[cfg(not(feature = "noop"))]
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+#[cfg(not(feature = "noop"))]
+use rustls::crypto::CryptoProvider;
+#[cfg(not(feature = "noop"))]
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+#[cfg(not(feature = "noop"))]
+use rustls::{ClientConfig, DigitallySignedStruct, SignatureScheme};
+
+
+/// Transports of the trace exporters built without certificate verification whose warning is not logged yet.
+static PENDING_WARNINGS: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+
+
+/// A certificate verifier accepting every server certificate.
+#[cfg(not(feature = "noop"))]
+#[derive(Debug)]
+struct SkipServerVerification(Arc<CryptoProvider>);
+
+
+/// Implementation of the ServerCertVerifier trait for SkipServerVerification
+#[cfg(not(feature = "noop"))]
+impl ServerCertVerifier for SkipServerVerification {
+    fn verify_server_cert(&self, _end_entity: &CertificateDer<'_>, _intermediates: &[CertificateDer<'_>], _server_name: &ServerName<'_>, _ocsp_response: &[u8], _now: UnixTime) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(&self, _message: &[u8], _cert: &CertificateDer<'_>, _dss: &DigitallySignedStruct) -> Result<HandshakeSignatureValid, rustls::Error> {
+        Ok(HandshakeSignatureValid::assertion())
+    }
+
+    fn verify_tls13_signature(&self, _message: &[u8], _cert: &CertificateDer<'_>, _dss: &DigitallySignedStruct) -> Result<HandshakeSignatureValid, rustls::Error> {
+        Ok(HandshakeSignatureValid::assertion())
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+
+/// Records that the server certificates of a trace exporter are not verified. The warning is logged by
+/// `report_insecure`, once the subscriber receiving it is installed.
+/// # Arguments
+/// * `transport` - The transport of the exporter.
+#[cfg(not(feature = "noop"))]
+pub(crate) fn warn_insecure(transport: &'static str) {
+    PENDING_WARNINGS.lock().unwrap_or_else(|e| e.into_inner()).push(transport);
+}
+
+
+/// Logs a warning for each transport of the trace exporters built without certificate verification since the last call.
+pub(crate) fn report_insecure() {
+    let mut transports = std::mem::take(&mut *PENDING_WARNINGS.lock().unwrap_or_else(|e| e.into_inner()));
+    transports.sort_unstable();
+    transports.dedup();
+    for transport in transports {
+        tracing::warn!(transport, "TLS certificate verification is DISABLED for the trace exporter; spans can be intercepted. Only use OTEL_EXPORTER_OTLP_INSECURE_SKIP_VERIFY with development collectors");
+    }
+}
+
+
+/// Returns a rustls client configuration accepting every server certificate.
+#[cfg(not(feature = "noop"))]
+pub(crate) fn insecure_tls_config() -> Result<ClientConfig, rustls::Error> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    Ok(ClientConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()?
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(SkipServerVerification(provider)))
+        .with_no_client_auth())
+}
+
+
+/// Returns an HTTP connector establishing TLS for `https` URIs without verifying the server certificate.
+/// # Arguments
+/// * `http2` - Whether the connector negotiates HTTP/2, as required by gRPC, instead of HTTP/1.1.
+#[cfg(not(feature = "noop"))]
+pub(crate) fn insecure_https_connector(http2: bool) -> Result<HttpsConnector<HttpConnector>, rustls::Error> {
+    let builder = HttpsConnectorBuilder::new()
+        .with_tls_config(insecure_tls_config()?)
+        .https_or_http();
+    Ok(if http2 { builder.enable_http2().build() } else { builder.enable_http1().build() })
+}
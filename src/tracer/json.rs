@@ -0,0 +1,37 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+use opentelemetry::{Key, KeyValue, Value};
+use serde_json::{json, Map};
+
+
+/// Returns the nanoseconds since the UNIX epoch of a timestamp.
+pub(crate) fn unix_nanos(time: SystemTime) -> u128 {
+    time.duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or_default()
+}
+
+
+/// Converts an attribute value to JSON.
+fn json_value(value: &Value) -> serde_json::Value {
+    match value {
+        Value::Bool(b) => json!(b),
+        Value::I64(i) => json!(i),
+        Value::F64(f) => json!(f),
+        Value::String(s) => json!(s.as_str()),
+        _ => json!(value.to_string()),
+    }
+}
+
+
+/// Converts attributes to a JSON object.
+pub(crate) fn json_attributes<'a>(attributes: impl IntoIterator<Item = (&'a Key, &'a Value)>) -> serde_json::Value {
+    let map: Map<String, serde_json::Value> = attributes
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), json_value(value)))
+        .collect();
+    serde_json::Value::Object(map)
+}
+
+
+/// Returns the key and value of each attribute of a list.
+pub(crate) fn pairs(attributes: &[KeyValue]) -> impl Iterator<Item = (&Key, &Value)> {
+    attributes.iter().map(|kv| (&kv.key, &kv.value))
+}
@@ -0,0 +1,136 @@
+use std::time::Duration;
+use opentelemetry::{Array, KeyValue, Value};
+use opentelemetry::trace::Status;
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::error::OTelSdkResult;
+use opentelemetry_sdk::trace::{SpanData, SpanExporter};
+
+
+/// Bytes of the maximum message size reserved for the resource, the instrumentation scopes and the
+/// framing of the export request, which are not counted span by span.
+const REQUEST_OVERHEAD: usize = 16 * 1024;
+
+/// Estimated bytes of the fixed-size fields of a span, i.e. its IDs, kind, timestamps, flags and counts.
+const SPAN_OVERHEAD: usize = 80;
+
+/// Estimated bytes of the tag and length prefixes of a field.
+const FIELD_OVERHEAD: usize = 4;
+
+
+/// A span exporter that splits the batches whose encoded size exceeds a maximum message size into
+/// several exports of the wrapped exporter, so collectors rejecting large messages, e.g. 4 MiB by default
+/// over gRPC, do not drop whole batches.
+///
+/// The size of a batch is estimated from the fields of its spans, without encoding them, and may be off
+/// by a few bytes per field. A span larger than the maximum message size on its own is exported alone.
+/// Each chunk is a separate export of the wrapped exporter, so a failed chunk only drops its own spans.
+#[derive(Debug)]
+pub struct MessageSizeExporter<E: SpanExporter> {
+    inner: E,
+    max_message_size: Option<usize>,
+}
+
+
+/// Returns the estimated size of an attribute value in an export request.
+fn value_size(value: &Value) -> usize {
+    match value {
+        Value::Bool(_) => 2,
+        Value::I64(_) | Value::F64(_) => 10,
+        Value::String(value) => value.as_str().len() + FIELD_OVERHEAD,
+        Value::Array(Array::Bool(values)) => values.len() * 4 + FIELD_OVERHEAD,
+        Value::Array(Array::I64(values)) => values.len() * 12 + FIELD_OVERHEAD,
+        Value::Array(Array::F64(values)) => values.len() * 12 + FIELD_OVERHEAD,
+        Value::Array(Array::String(values)) => values.iter().map(|value| value.as_str().len() + 2 * FIELD_OVERHEAD).sum::<usize>() + FIELD_OVERHEAD,
+        value => value.as_str().len() + FIELD_OVERHEAD,
+    }
+}
+
+
+/// Returns the estimated size of attributes in an export request.
+fn attributes_size(attributes: &[KeyValue]) -> usize {
+    attributes.iter()
+        .map(|attribute| attribute.key.as_str().len() + value_size(&attribute.value) + 2 * FIELD_OVERHEAD)
+        .sum()
+}
+
+
+/// Returns the estimated size of a span in an export request.
+fn encoded_size(span: &SpanData) -> usize {
+    let status = match &span.status {
+        Status::Error { description } => description.len() + FIELD_OVERHEAD,
+        Status::Ok | Status::Unset => FIELD_OVERHEAD,
+    };
+    let events: usize = span.events.iter()
+        .map(|event| event.name.len() + attributes_size(&event.attributes) + 2 * FIELD_OVERHEAD + 9)
+        .sum();
+    let links: usize = span.links.iter()
+        .map(|link| link.span_context.trace_state().header().len() + attributes_size(&link.attributes) + 2 * FIELD_OVERHEAD + 30)
+        .sum();
+    let length = SPAN_OVERHEAD + span.name.len() + span.span_context.trace_state().header().len()
+        + attributes_size(&span.attributes) + events + links + status;
+    length + FIELD_OVERHEAD
+}
+
+
+/// Splits a batch into chunks whose estimated encoded size does not exceed a limit.
+fn split_batch(batch: Vec<SpanData>, limit: usize) -> Vec<Vec<SpanData>> {
+    let mut chunks = Vec::new();
+    let mut chunk = Vec::new();
+    let mut chunk_size = 0;
+    for span in batch {
+        let size = encoded_size(&span);
+        if !chunk.is_empty() && chunk_size + size > limit {
+            chunks.push(std::mem::take(&mut chunk));
+            chunk_size = 0;
+        }
+        chunk_size += size;
+        chunk.push(span);
+    }
+    if !chunk.is_empty() {
+        chunks.push(chunk);
+    }
+    chunks
+}
+
+
+impl<E: SpanExporter> MessageSizeExporter<E> {
+    /// Creates a new instance of `MessageSizeExporter`.
+    /// # Arguments
+    /// * `inner` - The exporter to delegate to.
+    /// * `max_message_size` - The maximum size of an export request, in bytes. `None` means batches are not split.
+    /// # Returns
+    /// A new `MessageSizeExporter` instance.
+    pub fn new(inner: E, max_message_size: Option<usize>) -> Self {
+        Self { inner, max_message_size }
+    }
+}
+
+
+/// Implementation of the SpanExporter trait for MessageSizeExporter
+impl<E: SpanExporter> SpanExporter for MessageSizeExporter<E> {
+    async fn export(&self, batch: Vec<SpanData>) -> OTelSdkResult {
+        let Some(max_message_size) = self.max_message_size else {
+            return self.inner.export(batch).await;
+        };
+        let limit = max_message_size.saturating_sub(REQUEST_OVERHEAD).max(1);
+        let mut result = Ok(());
+        for chunk in split_batch(batch, limit) {
+            if let Err(err) = self.inner.export(chunk).await {
+                result = Err(err);
+            }
+        }
+        result
+    }
+
+    fn shutdown_with_timeout(&mut self, timeout: Duration) -> OTelSdkResult {
+        self.inner.shutdown_with_timeout(timeout)
+    }
+
+    fn force_flush(&mut self) -> OTelSdkResult {
+        self.inner.force_flush()
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        self.inner.set_resource(resource)
+    }
+}
@@ -1,33 +1,387 @@
+//! # Tracer Module
+//!
+//! This module provides the tracer providers for each supported trace transport.
 pub mod http;
 pub mod stdout;
 pub mod grpc;
-mod reqwest;
+pub mod reqwest;
+pub mod zipkin;
+pub mod datadog;
+pub mod xray;
+pub mod file;
+#[cfg(not(feature = "noop"))]
+pub(crate) mod in_memory;
+#[cfg(not(feature = "noop"))]
+mod binary_encoding;
+mod json;
+#[cfg(not(feature = "noop"))]
+pub(crate) mod summary;
+pub(crate) mod sampling;
+#[cfg(not(feature = "noop"))]
+pub(crate) mod heartbeat;
+mod partial_success;
+mod retry;
+#[cfg(not(feature = "noop"))]
+mod disk_buffer;
+#[cfg(not(feature = "noop"))]
+mod message_size;
+#[cfg(not(feature = "noop"))]
+mod queue;
+#[cfg(not(feature = "noop"))]
+mod tail_sampling;
+#[cfg(not(feature = "noop"))]
+mod error_link;
+pub(crate) mod id_generator;
+#[cfg(not(feature = "noop"))]
+mod scope_routing;
+#[cfg(not(feature = "noop"))]
+pub(crate) mod redaction;
+#[cfg(not(feature = "noop"))]
+pub(crate) mod baggage;
+#[cfg(not(feature = "noop"))]
+pub(crate) mod enrichment;
+#[cfg(not(feature = "noop"))]
+pub(crate) mod span_name;
+#[cfg(not(feature = "noop"))]
+pub(crate) mod boxed;
+pub(crate) mod export_error;
+mod insecure;
+#[cfg(not(feature = "noop"))]
+pub(crate) mod connectivity;
 
-use opentelemetry_sdk::trace::TraceError;
+pub use sampling::RateLimitingSampler;
+pub use id_generator::XRayIdGenerator;
+pub use export_error::{dropped_batches, dropped_spans, estimated_queue_drops, ExportErrorCallbackHandle, ExportErrorEvent};
+pub(crate) use insecure::report_insecure;
+
+use opentelemetry_sdk::trace::{TraceError, TracerProviderBuilder};
+#[cfg(not(feature = "noop"))]
+use opentelemetry_sdk::trace::{SimpleSpanProcessor, SpanExporter, SpanProcessor};
 use opentelemetry_sdk::trace::SdkTracerProvider as SDKTracerProvider;
-use crate::auth::layer;
-use crate::config::TraceConfig;
+use opentelemetry_sdk::Resource;
+use crate::auth::AuthContext;
+#[cfg(not(feature = "noop"))]
+use opentelemetry_otlp::{Compression, Protocol};
+use crate::config::{RedactionConfig, ScopeFilter, SpanEnrichmentConfig, SpanNameRule, TailSamplingConfig, TraceConfig};
+#[cfg(not(feature = "noop"))]
+use crate::config::{ExportCompression, ExportMode, ExportPolicyConfig, HttpEncoding, SpanLimitsConfig};
+#[cfg(not(feature = "noop"))]
+use crate::tracer::binary_encoding::BinaryEncodingExporter;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::disk_buffer::DiskBufferExporter;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::message_size::MessageSizeExporter;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::retry::RetryExporter;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::sampling::build_sampler;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::id_generator::build_id_generator;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::queue::QueueTrackingProcessor;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::tail_sampling::TailSamplingProcessor;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::error_link::ErrorLinkProcessor;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::scope_routing::ScopeRoutingProcessor;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::redaction::RedactionProcessor;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::enrichment::EnrichmentProcessor;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::span_name::SpanNameProcessor;
+use std::sync::{Arc, OnceLock};
+use crate::tracer::export_error::ExportStats;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::export_error::ExportErrorExporter;
+
+
+/// The OTLP span exporter, wrapped with the crate's export-time binary attribute encoding, disk buffer and retries.
+#[cfg(not(feature = "noop"))]
+pub(crate) type OtlpSpanExporter = BinaryEncodingExporter<DiskBufferExporter<RetryExporter<opentelemetry_otlp::SpanExporter>>>;
 
-/// Returns the tracer provider based on the provided configuration.
+
+/// Implementation of OtlpSpanExporter
+#[cfg(not(feature = "noop"))]
+impl OtlpSpanExporter {
+    /// Returns the OTLP span exporter without the crate's export-time binary attribute encoding, disk buffer and retries.
+    pub(crate) fn raw_exporter(&self) -> &opentelemetry_otlp::SpanExporter {
+        self.inner().inner().inner()
+    }
+}
+
+
+/// Returns the tracer provider based on the provided configuration, with the tail sampling of the
+/// `OTEL_TAIL_SAMPLING_*` environment variables, if set.
 ///
 /// # Arguments
 ///
 /// * `trace_config` - The tracing configuration.
-/// * `service_name` - The name of the service.
-pub async fn get_tracer_provider(trace_config: &TraceConfig, service_name: &String) -> Result<SDKTracerProvider, TraceError> {
+/// * `resource` - The resource of the traces, e.g. `resource::get_resource(service_name)`.
+/// * `auth_context` - The shared authentication state used to obtain token providers.
+pub async fn get_tracer_provider(trace_config: &TraceConfig, resource: &Resource, auth_context: &AuthContext) -> Result<SDKTracerProvider, TraceError> {
+    let tail_sampling = tail_sampling_from_env()?;
+    let builder = get_tracer_provider_builder(trace_config, resource, auth_context, tail_sampling.as_ref(), None, None, &[], &ScopeFilter::All, &Arc::default()).await?;
+    report_insecure();
+    Ok(builder.build())
+}
+
+
+/// Returns a tracer provider exporting every span to the exporter of the provided configuration and
+/// to each additional exporter, e.g. batch OTLP export plus simple export to standard output.
+///
+/// The sampler, ID generator and span limits of `trace_config`, and the tail sampling of the
+/// `OTEL_TAIL_SAMPLING_*` environment variables, if set, apply to every exporter.
+///
+/// # Arguments
+///
+/// * `trace_config` - The tracing configuration.
+/// * `additional_exporters` - The configurations of the additional exporters.
+/// * `resource` - The resource of the traces, e.g. `resource::get_resource(service_name)`.
+/// * `auth_context` - The shared authentication state used to obtain token providers.
+#[cfg(not(feature = "noop"))]
+pub async fn get_tracer_provider_with_exporters(trace_config: &TraceConfig, additional_exporters: &[TraceConfig], resource: &Resource, auth_context: &AuthContext) -> Result<SDKTracerProvider, TraceError> {
+    let tail_sampling = tail_sampling_from_env()?;
+    let stats = Arc::default();
+    let mut builder = get_tracer_provider_builder(trace_config, resource, auth_context, tail_sampling.as_ref(), None, None, &[], &ScopeFilter::All, &stats).await?;
+    for additional in additional_exporters {
+        builder = with_exporter(builder, additional, auth_context, tail_sampling.as_ref(), None, None, &[], &ScopeFilter::All, &stats).await?;
+    }
+    report_insecure();
+    Ok(builder.build())
+}
+
+
+/// Stub of `get_tracer_provider_with_exporters` with the `noop` feature, returning a tracer provider without exporter.
+#[cfg(feature = "noop")]
+pub async fn get_tracer_provider_with_exporters(_trace_config: &TraceConfig, _additional_exporters: &[TraceConfig], _resource: &Resource, _auth_context: &AuthContext) -> Result<SDKTracerProvider, TraceError> {
+    Ok(SDKTracerProvider::builder().build())
+}
+
+
+/// Returns the tail sampling configuration of the environment variables, if any. See `TailSamplingConfig::from_env`.
+fn tail_sampling_from_env() -> Result<Option<TailSamplingConfig>, TraceError> {
+    TailSamplingConfig::from_env().map_err(|err| TraceError::from(err.to_string()))
+}
+
+
+/// Applies the span limits of the configuration to a tracer provider builder.
+#[cfg(not(feature = "noop"))]
+pub(crate) fn with_span_limits(builder: TracerProviderBuilder, limits: &SpanLimitsConfig) -> TracerProviderBuilder {
+    builder
+        .with_max_attributes_per_span(limits.max_attributes_per_span)
+        .with_max_events_per_span(limits.max_events_per_span)
+        .with_max_links_per_span(limits.max_links_per_span)
+}
+
+
+/// Returns the OTLP protocol of an HTTP exporter with the given payload encoding.
+#[cfg(not(feature = "noop"))]
+pub(crate) fn http_protocol(encoding: HttpEncoding) -> Protocol {
+    match encoding {
+        HttpEncoding::Protobuf => Protocol::HttpBinary,
+        HttpEncoding::Json => Protocol::HttpJson,
+    }
+}
+
+
+/// Returns the compression of the OTLP exporter builders for the given payload compression, if any.
+#[cfg(not(feature = "noop"))]
+pub(crate) fn otlp_compression(compression: ExportCompression) -> Option<Compression> {
+    match compression {
+        ExportCompression::None => None,
+        ExportCompression::Gzip => Some(Compression::Gzip),
+        ExportCompression::Zstd => Some(Compression::Zstd),
+    }
+}
+
+
+/// Returns the runtime of the exports running outside of a Tokio runtime, created on first use and shared by
+/// every exporter, so the connections it drives are reused across exports.
+fn export_runtime() -> std::io::Result<&'static tokio::runtime::Runtime> {
+    static RUNTIME: OnceLock<tokio::runtime::Runtime> = OnceLock::new();
+    if let Some(runtime) = RUNTIME.get() {
+        return Ok(runtime);
+    }
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .thread_name("otel-export")
+        .enable_all()
+        .build()?;
+    Ok(RUNTIME.get_or_init(|| runtime))
+}
+
+
+/// Runs a future needing the Tokio reactor, e.g. an HTTP request, on the current runtime when there is one,
+/// so concurrent exports overlap, or else on the shared export runtime, blocking the exporting thread.
+pub(crate) async fn on_tokio<T, E: From<std::io::Error>>(future: impl Future<Output = Result<T, E>>) -> Result<T, E> {
+    if tokio::runtime::Handle::try_current().is_ok() {
+        future.await
+    } else {
+        export_runtime()?.block_on(future)
+    }
+}
+
+
+/// Runs blocking file system operations on the blocking thread pool of the Tokio runtime, if any,
+/// so they do not stall the runtime, or on the current thread otherwise.
+pub(crate) async fn run_blocking<T: Send + 'static>(f: impl FnOnce() -> std::io::Result<T> + Send + 'static) -> std::io::Result<T> {
+    match tokio::runtime::Handle::try_current() {
+        Ok(handle) => handle.spawn_blocking(f).await.map_err(std::io::Error::other)?,
+        Err(_) => f(),
+    }
+}
+
+
+/// Adds an exporter to a tracer provider builder with the processor of the given export mode, exporting up to
+/// the maximum number of concurrent exports of the policy in batch mode, and splitting the batches larger than
+/// its maximum message size. Failed exports are recorded as dropped batches, under the name of the exporter.
+#[cfg(not(feature = "noop"))]
+pub(crate) fn with_export_mode<E: SpanExporter + 'static>(builder: TracerProviderBuilder, exporter: E, mode: ExportMode, policy: &ExportPolicyConfig, name: &str) -> TracerProviderBuilder {
+    report_insecure();
+    let stats = Arc::new(ExportStats::default());
+    let exporter = MessageSizeExporter::new(ExportErrorExporter::new(exporter, name, stats.clone()), policy.max_message_size);
+    match mode {
+        ExportMode::Batch => builder.with_span_processor(QueueTrackingProcessor::new(exporter, stats, policy.max_concurrent_exports)),
+        ExportMode::Simple => builder.with_simple_exporter(exporter),
+    }
+}
+
+
+/// Adds an exporter to a tracer provider builder with the crate's span processing and the processor of the given export mode,
+/// exporting up to the maximum number of concurrent exports of the policy in batch mode, and splitting the batches larger
+/// than its maximum message size. Failed exports are recorded as dropped batches, under the name of the exporter, in the
+/// counters of the tracer provider.
+#[cfg(not(feature = "noop"))]
+#[allow(clippy::too_many_arguments)]
+fn with_export_processor<E: SpanExporter + 'static>(builder: TracerProviderBuilder, exporter: E, mode: ExportMode, policy: &ExportPolicyConfig, name: &str, tail_sampling: Option<&TailSamplingConfig>, redaction: Option<&RedactionConfig>, enrichment: Option<&SpanEnrichmentConfig>, span_names: &[SpanNameRule], scopes: &ScopeFilter, stats: &Arc<ExportStats>) -> Result<TracerProviderBuilder, TraceError> {
+    let exporter = MessageSizeExporter::new(ExportErrorExporter::new(exporter, name, stats.clone()), policy.max_message_size);
+    Ok(match mode {
+        ExportMode::Batch => builder.with_span_processor(export_processor(QueueTrackingProcessor::new(exporter, stats.clone(), policy.max_concurrent_exports), tail_sampling, redaction, enrichment, span_names, scopes)?),
+        ExportMode::Simple => builder.with_span_processor(export_processor(SimpleSpanProcessor::new(exporter), tail_sampling, redaction, enrichment, span_names, scopes)?),
+    })
+}
+
+
+/// The processor of an exporter wrapped with the crate's span processing.
+#[cfg(not(feature = "noop"))]
+type ExportProcessor<P> = ScopeRoutingProcessor<ErrorLinkProcessor<TailSamplingProcessor<RedactionProcessor<EnrichmentProcessor<SpanNameProcessor<P>>>>>>;
+
+
+/// Wraps the processor of an exporter with the crate's span processing: scope routing, error links,
+/// tail sampling, attribute redaction and enrichment, and span name rewriting.
+#[cfg(not(feature = "noop"))]
+fn export_processor<P: SpanProcessor>(processor: P, tail_sampling: Option<&TailSamplingConfig>, redaction: Option<&RedactionConfig>, enrichment: Option<&SpanEnrichmentConfig>, span_names: &[SpanNameRule], scopes: &ScopeFilter) -> Result<ExportProcessor<P>, TraceError> {
+    let processor = SpanNameProcessor::new(processor, span_names).map_err(|err| TraceError::from(err.to_string()))?;
+    let processor = RedactionProcessor::new(EnrichmentProcessor::new(processor, enrichment), redaction).map_err(|err| TraceError::from(err.to_string()))?;
+    Ok(ScopeRoutingProcessor::new(ErrorLinkProcessor::new(TailSamplingProcessor::new(processor, tail_sampling.cloned())), scopes.clone()))
+}
+
+
+/// Adds the exporter of the provided configuration to a tracer provider builder.
+///
+/// Several exporters can be added to the same builder, each receiving the spans of its instrumentation scopes.
+///
+/// # Arguments
+///
+/// * `builder` - The tracer provider builder.
+/// * `trace_config` - The tracing configuration of the exporter.
+/// * `auth_context` - The shared authentication state used to obtain token providers.
+/// * `tail_sampling` - The tail sampling configuration applied before export, if any.
+/// * `redaction` - The attribute redaction configuration applied before export, if any.
+/// * `enrichment` - The attributes added before export to the spans without them, if any.
+/// * `span_names` - The rules rewriting the span names before export.
+/// * `scopes` - The instrumentation scopes whose spans are exported.
+/// * `stats` - The counters of the exported and dropped spans of the tracer provider.
+#[cfg(not(feature = "noop"))]
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn with_exporter(builder: TracerProviderBuilder, trace_config: &TraceConfig, auth_context: &AuthContext, tail_sampling: Option<&TailSamplingConfig>, redaction: Option<&RedactionConfig>, enrichment: Option<&SpanEnrichmentConfig>, span_names: &[SpanNameRule], scopes: &ScopeFilter, stats: &Arc<ExportStats>) -> Result<TracerProviderBuilder, TraceError> {
     match trace_config {
         TraceConfig::HTTP(otlp_config) => {
-            let token_provider = layer::new_gen_token(&otlp_config.auth_config);
-            http::get_http_tracer_provider(otlp_config, service_name, token_provider).await
+            let token_provider = auth_context.token_provider(&otlp_config.auth_config);
+            let exporter = http::get_http_span_exporter(otlp_config, token_provider).await?;
+            with_export_processor(builder, exporter, otlp_config.export_mode, &otlp_config.export_policy, "http", tail_sampling, redaction, enrichment, span_names, scopes, stats)
         },
         TraceConfig::GRPC(otlp_config) => {
-            let token_provider = layer::new_gen_token(&otlp_config.auth_config);
-            grpc::init_grpc_otlp_tracer_provider(otlp_config, service_name, token_provider).await
+            let exporter = grpc::get_grpc_span_exporter(otlp_config, auth_context).await?;
+            with_export_processor(builder, exporter, otlp_config.export_mode, &otlp_config.export_policy, "grpc", tail_sampling, redaction, enrichment, span_names, scopes, stats)
         },
         TraceConfig::REQWEST(otlp_config) => {
-            let token_provider = layer::new_gen_token(&otlp_config.auth_config);
-            reqwest::get_reqwest_tracer_provider(otlp_config, service_name, token_provider).await
+            let token_provider = auth_context.token_provider(&otlp_config.auth_config);
+            let exporter = reqwest::get_reqwest_span_exporter(otlp_config, token_provider)?;
+            with_export_processor(builder, exporter, otlp_config.export_mode, &otlp_config.export_policy, "reqwest", tail_sampling, redaction, enrichment, span_names, scopes, stats)
         }
-        TraceConfig::StdOut => stdout::get_stdout_tracer_provider().await,
+        TraceConfig::Zipkin(zipkin_config) => {
+            let token_provider = auth_context.token_provider(&zipkin_config.auth_config);
+            let exporter = zipkin::get_zipkin_span_exporter(zipkin_config, token_provider)?;
+            with_export_processor(builder, exporter, zipkin_config.export_mode, &ExportPolicyConfig::default(), "zipkin", tail_sampling, redaction, enrichment, span_names, scopes, stats)
+        },
+        TraceConfig::DatadogAgent(datadog_config) => {
+            let token_provider = auth_context.token_provider(&datadog_config.auth_config);
+            let exporter = datadog::get_datadog_span_exporter(datadog_config, token_provider);
+            with_export_processor(builder, exporter, datadog_config.export_mode, &ExportPolicyConfig::default(), "datadog", tail_sampling, redaction, enrichment, span_names, scopes, stats)
+        },
+        TraceConfig::XRay(xray_config) => match &xray_config.daemon_address {
+            Some(daemon_address) => {
+                let exporter = xray::get_xray_span_exporter(daemon_address)?;
+                with_export_processor(builder, exporter, xray_config.export_mode, &ExportPolicyConfig::default(), "xray", tail_sampling, redaction, enrichment, span_names, scopes, stats)
+            },
+            None => Ok(builder),
+        },
+        TraceConfig::File(file_config) => {
+            let exporter = file::FileSpanExporter::new(file_config);
+            with_export_processor(builder, exporter, file_config.export_mode, &ExportPolicyConfig::default(), "file", tail_sampling, redaction, enrichment, span_names, scopes, stats)
+        },
+        TraceConfig::InMemory(in_memory_config) => {
+            let processor = SimpleSpanProcessor::new(ExportErrorExporter::new(in_memory::InMemoryExporter::new(in_memory_config.store.clone()), "in_memory", stats.clone()));
+            Ok(builder.with_span_processor(export_processor(processor, tail_sampling, redaction, enrichment, span_names, scopes)?))
+        },
+        // Spans are recorded, but never reach a processor.
+        TraceConfig::Noop(_) => Ok(builder),
+        TraceConfig::StdOut(stdout_config) => {
+            let processor = SimpleSpanProcessor::new(ExportErrorExporter::new(stdout::StdoutSpanExporter::new(stdout_config), "stdout", stats.clone()));
+            Ok(builder.with_span_processor(export_processor(processor, tail_sampling, redaction, enrichment, span_names, scopes)?))
+        },
     }
 }
+
+
+/// Returns a tracer provider builder with the exporter of the provided configuration,
+/// so callers can register additional components before building the provider.
+///
+/// # Arguments
+///
+/// * `trace_config` - The tracing configuration.
+/// * `resource` - The resource of the traces.
+/// * `auth_context` - The shared authentication state used to obtain token providers.
+/// * `tail_sampling` - The tail sampling configuration applied before export, if any.
+/// * `redaction` - The attribute redaction configuration applied before export, if any.
+/// * `enrichment` - The attributes added before export to the spans without them, if any.
+/// * `span_names` - The rules rewriting the span names before export.
+/// * `scopes` - The instrumentation scopes whose spans are exported.
+/// * `stats` - The counters of the exported and dropped spans of the tracer provider.
+#[cfg(not(feature = "noop"))]
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn get_tracer_provider_builder(trace_config: &TraceConfig, resource: &Resource, auth_context: &AuthContext, tail_sampling: Option<&TailSamplingConfig>, redaction: Option<&RedactionConfig>, enrichment: Option<&SpanEnrichmentConfig>, span_names: &[SpanNameRule], scopes: &ScopeFilter, stats: &Arc<ExportStats>) -> Result<TracerProviderBuilder, TraceError> {
+    with_exporter(with_pipeline(trace_config, resource), trace_config, auth_context, tail_sampling, redaction, enrichment, span_names, scopes, stats).await
+}
+
+
+/// Stub of `get_tracer_provider_builder` with the `noop` feature, returning a builder without exporter.
+#[cfg(feature = "noop")]
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn get_tracer_provider_builder(_trace_config: &TraceConfig, _resource: &Resource, _auth_context: &AuthContext, _tail_sampling: Option<&TailSamplingConfig>, _redaction: Option<&RedactionConfig>, _enrichment: Option<&SpanEnrichmentConfig>, _span_names: &[SpanNameRule], _scopes: &ScopeFilter, _stats: &Arc<ExportStats>) -> Result<TracerProviderBuilder, TraceError> {
+    Ok(SDKTracerProvider::builder())
+}
+
+
+/// Returns a tracer provider builder with the resource, sampler, ID generator and span limits of the configuration.
+#[cfg(not(feature = "noop"))]
+fn with_pipeline(trace_config: &TraceConfig, resource: &Resource) -> TracerProviderBuilder {
+    let builder = SDKTracerProvider::builder()
+        .with_resource(resource.clone())
+        .with_sampler(build_sampler(trace_config.sampler()))
+        .with_id_generator(build_id_generator(trace_config.id_generator()));
+    with_span_limits(builder, trace_config.span_limits())
+}
@@ -0,0 +1,46 @@
+use opentelemetry_http::{Bytes, Response};
+use opentelemetry_proto::tonic::collector::logs::v1::ExportLogsServiceResponse;
+use opentelemetry_proto::tonic::collector::trace::v1::ExportTraceServiceResponse;
+use prost::Message;
+use crate::self_observability::record_partial_success;
+
+
+/// Inspects the response of an OTLP/HTTP export and records partial successes.
+///
+/// OTLP collectors report rejected items in the body of otherwise successful responses.
+/// Only protobuf-encoded trace and log responses are inspected; other responses are ignored.
+///
+/// # Arguments
+///
+/// * `path` - The path of the export request, used to determine the signal.
+/// * `response` - The response of the export request.
+pub fn handle_export_response(path: &str, response: &Response<Bytes>) {
+    if !response.status().is_success() || response.body().is_empty() {
+        return;
+    }
+    let is_protobuf = response.headers()
+        .get("content-type")
+        .and_then(|v| v.to_str().ok())
+        .is_none_or(|v| v.starts_with("application/x-protobuf"));
+    if !is_protobuf {
+        return;
+    }
+
+    let body = response.body().as_ref();
+    let partial = if path.ends_with("/v1/traces") {
+        ExportTraceServiceResponse::decode(body).ok()
+            .and_then(|r| r.partial_success)
+            .map(|p| ("traces", p.rejected_spans, p.error_message))
+    } else if path.ends_with("/v1/logs") {
+        ExportLogsServiceResponse::decode(body).ok()
+            .and_then(|r| r.partial_success)
+            .map(|p| ("logs", p.rejected_log_records, p.error_message))
+    } else {
+        None
+    };
+
+    if let Some((signal, rejected, message)) = partial
+        && (rejected > 0 || !message.is_empty()) {
+        record_partial_success(signal, rejected, &message);
+    }
+}
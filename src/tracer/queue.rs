@@ -0,0 +1,192 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use opentelemetry::Context;
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::error::OTelSdkResult;
+#[cfg(feature = "concurrent-exports")]
+use opentelemetry_sdk::runtime::Tokio;
+use opentelemetry_sdk::trace::{BatchSpanProcessor, Span, SpanData, SpanExporter, SpanProcessor};
+#[cfg(feature = "concurrent-exports")]
+use opentelemetry_sdk::trace::BatchConfigBuilder;
+#[cfg(feature = "concurrent-exports")]
+use opentelemetry_sdk::trace::span_processor_with_async_runtime::BatchSpanProcessor as TokioBatchSpanProcessor;
+use crate::self_observability::QueueMetrics;
+use crate::config::read_env_var;
+use crate::tracer::export_error::{record_queue_drop, ExportStats};
+
+
+/// Default maximum queue size of the batch span processor.
+const DEFAULT_MAX_QUEUE_SIZE: usize = 2048;
+
+
+/// Estimated state of the batch span processor queue.
+///
+/// The depth grows by one for each ended span, up to the maximum queue size since the processor
+/// drops spans when full, and shrinks by the size of each batch handed to the exporter.
+#[derive(Debug)]
+struct QueueState {
+    capacity: usize,
+    depth: usize,
+    high_water_mark: usize,
+    full_since: Option<Instant>,
+    time_at_capacity: Duration,
+}
+
+
+impl QueueState {
+    /// Accounts for a span entering the queue, returning whether it is dropped because the queue is full.
+    fn push(&mut self) -> bool {
+        let dropped = self.depth == self.capacity;
+        self.depth = (self.depth + 1).min(self.capacity);
+        self.high_water_mark = self.high_water_mark.max(self.depth);
+        if self.depth == self.capacity && self.full_since.is_none() {
+            self.full_since = Some(Instant::now());
+        }
+        dropped
+    }
+
+    /// Accounts for a batch of spans leaving the queue.
+    fn pop(&mut self, count: usize) {
+        self.depth = self.depth.saturating_sub(count);
+        if self.depth < self.capacity && let Some(since) = self.full_since.take() {
+            self.time_at_capacity += since.elapsed();
+        }
+    }
+
+    /// Returns the time spent at capacity since the last call.
+    fn take_time_at_capacity(&mut self) -> Duration {
+        if let Some(since) = self.full_since.as_mut() {
+            self.time_at_capacity += since.elapsed();
+            *since = Instant::now();
+        }
+        std::mem::take(&mut self.time_at_capacity)
+    }
+}
+
+
+/// Shared queue state of a `QueueTrackingProcessor` and its exporter.
+type SharedQueueState = Arc<Mutex<QueueState>>;
+
+
+/// A span exporter that reports the batches it receives to the queue state, then delegates to the wrapped exporter.
+#[derive(Debug)]
+struct QueueTrackingExporter<E: SpanExporter> {
+    inner: E,
+    state: SharedQueueState,
+    metrics: QueueMetrics,
+}
+
+
+/// Implementation of the SpanExporter trait for QueueTrackingExporter
+impl<E: SpanExporter> SpanExporter for QueueTrackingExporter<E> {
+    async fn export(&self, batch: Vec<SpanData>) -> OTelSdkResult {
+        let (high_water_mark, time_at_capacity) = {
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            state.pop(batch.len());
+            (state.high_water_mark, state.take_time_at_capacity())
+        };
+        self.metrics.record(high_water_mark as u64, time_at_capacity);
+        self.inner.export(batch).await
+    }
+
+    fn shutdown_with_timeout(&mut self, timeout: Duration) -> OTelSdkResult {
+        self.inner.shutdown_with_timeout(timeout)
+    }
+
+    fn force_flush(&mut self) -> OTelSdkResult {
+        self.inner.force_flush()
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        self.inner.set_resource(resource)
+    }
+}
+
+
+/// A batch span processor that tracks the high-water mark of its queue and the time spent at capacity.
+///
+/// Both are reported through the `otel.exporter.queue.high_water_mark` gauge and the
+/// `otel.exporter.queue.time_at_capacity` counter of the global meter provider on each export, so
+/// `OTEL_BSP_MAX_QUEUE_SIZE` can be tuned from data. The instruments are created with the processor.
+#[derive(Debug)]
+pub struct QueueTrackingProcessor {
+    inner: Box<dyn SpanProcessor>,
+    state: SharedQueueState,
+    stats: Arc<ExportStats>,
+}
+
+
+impl QueueTrackingProcessor {
+    /// Creates a new instance of `QueueTrackingProcessor`.
+    ///
+    /// The batch span processor is configured from the `OTEL_BSP_*` environment variables. With the
+    /// `concurrent-exports` feature, when `max_concurrent_exports` is above 1 and a Tokio runtime is running,
+    /// batches are exported by a task of that runtime, with up to that many exports in flight; otherwise
+    /// a background thread exports one batch at a time.
+    /// # Arguments
+    /// * `exporter` - The exporter of the batch span processor.
+    /// * `stats` - The counters of the tracer provider, recording the spans dropped by a full queue.
+    /// * `max_concurrent_exports` - The maximum number of batches exported at once.
+    /// # Returns
+    /// A new `QueueTrackingProcessor` instance.
+    pub(crate) fn new<E: SpanExporter + 'static>(exporter: E, stats: Arc<ExportStats>, max_concurrent_exports: usize) -> Self {
+        let capacity = read_env_var("OTEL_BSP_MAX_QUEUE_SIZE").ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_MAX_QUEUE_SIZE);
+        let state = Arc::new(Mutex::new(QueueState {
+            capacity,
+            depth: 0,
+            high_water_mark: 0,
+            full_since: None,
+            time_at_capacity: Duration::ZERO,
+        }));
+        let exporter = QueueTrackingExporter { inner: exporter, state: state.clone(), metrics: QueueMetrics::new() };
+        Self { inner: batch_processor(exporter, max_concurrent_exports), state, stats }
+    }
+}
+
+
+/// Returns the batch span processor of an exporter, exporting up to `max_concurrent_exports` batches at once
+/// on the current Tokio runtime, if any.
+#[cfg(feature = "concurrent-exports")]
+fn batch_processor<E: SpanExporter + 'static>(exporter: E, max_concurrent_exports: usize) -> Box<dyn SpanProcessor> {
+    if max_concurrent_exports > 1 && tokio::runtime::Handle::try_current().is_ok() {
+        let config = BatchConfigBuilder::default().with_max_concurrent_exports(max_concurrent_exports).build();
+        return Box::new(TokioBatchSpanProcessor::builder(exporter, Tokio).with_batch_config(config).build());
+    }
+    Box::new(BatchSpanProcessor::builder(exporter).build())
+}
+
+
+/// Returns the batch span processor of an exporter, exporting one batch at a time on a background thread.
+#[cfg(not(feature = "concurrent-exports"))]
+fn batch_processor<E: SpanExporter + 'static>(exporter: E, _max_concurrent_exports: usize) -> Box<dyn SpanProcessor> {
+    Box::new(BatchSpanProcessor::builder(exporter).build())
+}
+
+
+/// Implementation of the SpanProcessor trait for QueueTrackingProcessor
+impl SpanProcessor for QueueTrackingProcessor {
+    fn on_start(&self, span: &mut Span, cx: &Context) {
+        self.inner.on_start(span, cx)
+    }
+
+    fn on_end(&self, span: SpanData) {
+        if self.state.lock().unwrap_or_else(|e| e.into_inner()).push() {
+            record_queue_drop(&self.stats, 1);
+        }
+        self.inner.on_end(span)
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        self.inner.force_flush()
+    }
+
+    fn shutdown_with_timeout(&self, timeout: Duration) -> OTelSdkResult {
+        self.inner.shutdown_with_timeout(timeout)
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        self.inner.set_resource(resource)
+    }
+}
@@ -0,0 +1,208 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+use opentelemetry::{Array, Context, KeyValue, StringValue, Value};
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::error::OTelSdkResult;
+use opentelemetry_sdk::trace::{Span, SpanData, SpanProcessor};
+use hmac::{Hmac, KeyInit, Mac};
+use regex::Regex;
+use sha2::Sha256;
+use crate::config::{RedactionAction, RedactionConfig, CARD_NUMBER_PATTERN};
+
+
+/// Replacement of the redacted parts of attribute values with `RedactionAction::Drop`.
+const REDACTED: &str = "[REDACTED]";
+
+
+/// A compiled value pattern of a `RedactionConfig`.
+#[derive(Debug)]
+struct ValuePattern {
+    regex: Regex,
+    /// Whether only the matches passing the Luhn checksum are redacted, i.e. credit card numbers.
+    luhn: bool,
+}
+
+
+/// The compiled rules of a `RedactionConfig`.
+#[derive(Debug)]
+pub(crate) struct Redactor {
+    keys: Vec<String>,
+    patterns: Vec<ValuePattern>,
+    action: RedactionAction,
+    hash_key: Vec<u8>,
+}
+
+
+/// Returns a random key for the hashes of a configuration without `hash_key`.
+fn random_hash_key() -> Vec<u8> {
+    (0..4).flat_map(|_| RandomState::new().build_hasher().finish().to_le_bytes()).collect()
+}
+
+
+/// Returns whether the digits of a text pass the Luhn checksum of credit card numbers.
+fn luhn_valid(text: &str) -> bool {
+    let digits: Vec<u32> = text.chars().filter_map(|c| c.to_digit(10)).collect();
+    let sum: u32 = digits.iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &digit)| match (i % 2, digit * 2) {
+            (0, _) => digit,
+            (_, doubled) if doubled > 9 => doubled - 9,
+            (_, doubled) => doubled,
+        })
+        .sum();
+    !digits.is_empty() && sum.is_multiple_of(10)
+}
+
+
+impl Redactor {
+    /// Compiles the rules of a `RedactionConfig`.
+    /// # Arguments
+    /// * `config` - The redaction configuration.
+    /// # Returns
+    /// The compiled rules, or an error if a value pattern is not a valid regular expression.
+    pub(crate) fn new(config: &RedactionConfig) -> Result<Self, regex::Error> {
+        Ok(Redactor {
+            keys: config.keys.iter().map(|key| key.to_ascii_lowercase()).collect(),
+            patterns: config.value_patterns.iter()
+                .map(|pattern| Ok(ValuePattern { regex: Regex::new(pattern)?, luhn: pattern == CARD_NUMBER_PATTERN }))
+                .collect::<Result<_, regex::Error>>()?,
+            action: config.action,
+            hash_key: config.hash_key.as_ref().map(|key| key.as_bytes().to_vec()).unwrap_or_else(random_hash_key),
+        })
+    }
+
+    /// Returns the action applied to redacted values.
+    pub(crate) fn action(&self) -> RedactionAction {
+        self.action
+    }
+
+    /// Returns whether the attribute with the given key is redacted, ignoring the ASCII case.
+    pub(crate) fn matches_key(&self, key: &str) -> bool {
+        let key = key.to_ascii_lowercase();
+        self.keys.iter().any(|pattern| match (pattern.strip_prefix('*'), pattern.strip_suffix('*')) {
+            (Some(infix), Some(_)) => key.contains(infix.trim_end_matches('*')),
+            (None, Some(prefix)) => key.starts_with(prefix),
+            _ => key == *pattern,
+        })
+    }
+
+    /// Returns the hex-encoded HMAC-SHA256 of a value, keyed with the hash key of the configuration.
+    pub(crate) fn hash(&self, value: &str) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.hash_key).expect("HMAC accepts keys of any size");
+        mac.update(value.as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    /// Redacts the parts of a text matching the value patterns, returning `None` if none matches.
+    pub(crate) fn redact_text(&self, text: &str) -> Option<String> {
+        let mut redacted: Option<String> = None;
+        for pattern in &self.patterns {
+            let value = redacted.as_deref().unwrap_or(text);
+            if !pattern.regex.is_match(value) {
+                continue;
+            }
+            let replaced = pattern.regex.replace_all(value, |caps: &regex::Captures| match self.action {
+                _ if pattern.luhn && !luhn_valid(&caps[0]) => caps[0].to_string(),
+                RedactionAction::Drop => REDACTED.to_string(),
+                RedactionAction::Hash => self.hash(&caps[0]),
+            });
+            if replaced != value {
+                redacted = Some(replaced.into_owned());
+            }
+        }
+        redacted
+    }
+
+    /// Redacts the parts of a string matching the value patterns.
+    fn redact_str(&self, s: &StringValue) -> StringValue {
+        match self.redact_text(s.as_str()) {
+            Some(value) => StringValue::from(value),
+            None => s.clone(),
+        }
+    }
+
+    /// Redacts the string parts of a value matching the value patterns.
+    fn redact_value(&self, value: &Value) -> Value {
+        match value {
+            Value::String(s) => Value::String(self.redact_str(s)),
+            Value::Array(Array::String(l)) => Value::Array(Array::String(l.iter().map(|s| self.redact_str(s)).collect())),
+            _ => value.clone(),
+        }
+    }
+
+    /// Applies the rules to a list of attributes.
+    fn redact_attributes(&self, attributes: &mut Vec<KeyValue>) {
+        *attributes = attributes
+            .iter()
+            .filter_map(|kv| {
+                if !self.matches_key(kv.key.as_str()) {
+                    return Some(KeyValue::new(kv.key.clone(), self.redact_value(&kv.value)));
+                }
+                match self.action {
+                    RedactionAction::Drop => None,
+                    RedactionAction::Hash => Some(KeyValue::new(kv.key.clone(), self.hash(&kv.value.as_str()))),
+                }
+            })
+            .collect();
+    }
+}
+
+
+/// A span processor that drops or hashes the span, event and link attributes matching a `RedactionConfig`
+/// before the span is passed to the wrapped processor.
+///
+/// Without a configuration, spans are passed through to the wrapped processor.
+#[derive(Debug)]
+pub struct RedactionProcessor<P: SpanProcessor> {
+    inner: P,
+    redactor: Option<Redactor>,
+}
+
+
+impl<P: SpanProcessor> RedactionProcessor<P> {
+    /// Creates a new instance of `RedactionProcessor`.
+    /// # Arguments
+    /// * `inner` - The processor receiving the redacted spans.
+    /// * `config` - The redaction configuration, or `None` to pass every span through.
+    /// # Returns
+    /// A new `RedactionProcessor` instance, or an error if a value pattern is not a valid regular expression.
+    pub fn new(inner: P, config: Option<&RedactionConfig>) -> Result<Self, regex::Error> {
+        let redactor = config.map(Redactor::new).transpose()?;
+        Ok(Self { inner, redactor })
+    }
+}
+
+
+/// Implementation of the SpanProcessor trait for RedactionProcessor
+impl<P: SpanProcessor> SpanProcessor for RedactionProcessor<P> {
+    fn on_start(&self, span: &mut Span, cx: &Context) {
+        self.inner.on_start(span, cx)
+    }
+
+    fn on_end(&self, mut span: SpanData) {
+        if let Some(redactor) = &self.redactor {
+            redactor.redact_attributes(&mut span.attributes);
+            for event in span.events.events.iter_mut() {
+                redactor.redact_attributes(&mut event.attributes);
+            }
+            for link in span.links.links.iter_mut() {
+                redactor.redact_attributes(&mut link.attributes);
+            }
+        }
+        self.inner.on_end(span)
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        self.inner.force_flush()
+    }
+
+    fn shutdown_with_timeout(&self, timeout: Duration) -> OTelSdkResult {
+        self.inner.shutdown_with_timeout(timeout)
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        self.inner.set_resource(resource)
+    }
+}
@@ -2,15 +2,35 @@ use std::str::FromStr;
 use std::sync::Arc;
 use async_trait::async_trait;
 use opentelemetry_http::{Bytes, HttpClient, HttpError, Request, Response};
+#[cfg(not(feature = "noop"))]
 use opentelemetry_otlp::{SpanExporter, WithHttpConfig, WithExportConfig};
+use opentelemetry_sdk::Resource;
 use opentelemetry_sdk::trace::TraceError;
 use opentelemetry_sdk::trace::SdkTracerProvider as SDKTracerProvider;
-use reqwest;
 use reqwest::header::HeaderName;
-use tokio::runtime::Runtime;
 use crate::auth::GetToken;
 use crate::config::OTLPTraceConfig;
-use crate::resource::get_resource;
+#[cfg(not(feature = "noop"))]
+use crate::config::HttpVersion;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::OtlpSpanExporter;
+use crate::tracer::partial_success::handle_export_response;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::binary_encoding::BinaryEncodingExporter;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::disk_buffer::DiskBufferExporter;
+use crate::tracer::retry::transport_error;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::retry::RetryExporter;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::insecure::warn_insecure;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::sampling::build_sampler;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::id_generator::build_id_generator;
+use crate::tracer::on_tokio;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::{http_protocol, otlp_compression, with_export_mode, with_span_limits};
 
 /// A Reqwest-based HTTP client that adds authentication tokens to requests.
 #[derive(Debug, Clone)]
@@ -37,9 +57,8 @@ impl ReqwestTracerClient {
     /// # Returns
     /// The modified HTTP request with the authorization header if a token is available.
     async fn get_token(&self, request: Request<Bytes>) -> anyhow::Result<Request<Bytes>> {
-        let rt = Runtime::new()?;
-        let headers = rt.block_on(async { self.token_provider.get_auth_headers().await})?;
         let (mut parts, bts) = request.into_parts();
+        let headers = on_tokio(self.token_provider.get_request_auth_headers(parts.method.as_str(), parts.uri.path(), &bts)).await?;
         for (key, value) in headers {
             let hn = HeaderName::from_str(key.as_str())?;
             parts.headers.insert(hn, value.parse()?);
@@ -54,47 +73,125 @@ impl ReqwestTracerClient {
 #[async_trait]
 impl HttpClient for ReqwestTracerClient {
     async fn send_bytes(&self, request: Request<Bytes>) -> anyhow::Result<Response<Bytes>, HttpError> {
-        let rt = Runtime::new()?;
         let request = self.get_token(request).await?;
-        rt.block_on(async { self.client.send_bytes(request).await})
+        let path = request.uri().path().to_string();
+        let response = on_tokio(self.client.send_bytes(request)).await.map_err(transport_error)?;
+        handle_export_response(&path, &response);
+        if matches!(response.status().as_u16(), 401 | 403) {
+            self.token_provider.record_rejection(&format!("export rejected with status {}", response.status()));
+        }
+        Ok(response)
     }
 }
 
 
 
-/// Initializes the OTLP HTTP tracer provider with authentication.
+/// Builds the reqwest client of the exporter, which uses the configured HTTP version and does not verify
+/// the certificate of the collector with `insecure_skip_verify`.
+#[cfg(not(feature = "noop"))]
+fn default_client(otlp_config: &OTLPTraceConfig) -> anyhow::Result<reqwest::Client, TraceError> {
+    let mut builder = reqwest::Client::builder();
+    if otlp_config.insecure_skip_verify {
+        warn_insecure("reqwest");
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+    builder = match otlp_config.http_version {
+        HttpVersion::Auto => builder,
+        HttpVersion::Http1 => builder.http1_only(),
+        HttpVersion::Http2 => builder.http2_prior_knowledge(),
+    };
+    builder.build().map_err(|err| TraceError::from(err.to_string()))
+}
+
+
+/// Builds the OTLP HTTP span exporter using reqwest, with authentication.
 /// # Arguments
 /// * `otlp_config` - The OTLP trace configuration.
-/// * `service_name` - The name of the service.
 /// * `token_provider` - An `Arc<dyn GetToken>` to provide access tokens.
 /// # Returns
-/// A `Result` containing the initialized `SDKTracerProvider` or a `TraceError`.
-pub async fn get_reqwest_tracer_provider(otlp_config: &OTLPTraceConfig, service_name: &String, token_provider: Arc<dyn GetToken>) -> anyhow::Result<SDKTracerProvider, TraceError> {
-    let http_client = Arc::new(
-        reqwest::Client::builder()
-            .build()
-            .unwrap_or_default(),
-    ) as Arc<dyn HttpClient>;
+/// A `Result` containing the span exporter or a `TraceError`.
+#[cfg(not(feature = "noop"))]
+pub(crate) fn get_reqwest_span_exporter(otlp_config: &OTLPTraceConfig, token_provider: Arc<dyn GetToken>) -> anyhow::Result<OtlpSpanExporter, TraceError> {
+    let client = default_client(otlp_config)?;
+    get_reqwest_span_exporter_with_client(otlp_config, token_provider, client)
+}
 
+
+/// Builds the OTLP HTTP span exporter using the given reqwest client, with authentication.
+/// # Arguments
+/// * `otlp_config` - The OTLP trace configuration.
+/// * `token_provider` - An `Arc<dyn GetToken>` to provide access tokens.
+/// * `client` - The reqwest client sending the requests, e.g. with the proxy, TLS and pool settings of the application.
+/// # Returns
+/// A `Result` containing the span exporter or a `TraceError`.
+#[cfg(not(feature = "noop"))]
+fn get_reqwest_span_exporter_with_client(otlp_config: &OTLPTraceConfig, token_provider: Arc<dyn GetToken>, client: reqwest::Client) -> anyhow::Result<OtlpSpanExporter, TraceError> {
     let reqwest_tracer_client = ReqwestTracerClient::new(
-        // HyperClient::with_default_connector(Duration::from_secs(5), None),
-        http_client,
+        Arc::new(client) as Arc<dyn HttpClient>,
         token_provider,
     );
 
-    let span_exporter = SpanExporter::builder()
+    let mut builder = SpanExporter::builder()
         .with_http()
         .with_endpoint(otlp_config.endpoint.clone())
-        .with_http_client(reqwest_tracer_client)
+        .with_protocol(http_protocol(otlp_config.encoding))
+        .with_http_client(reqwest_tracer_client);
+    if let Some(compression) = otlp_compression(otlp_config.compression) {
+        builder = builder.with_compression(compression);
+    }
+    let span_exporter = builder
         .build()
         .map_err(|err| TraceError::from(err.to_string()))?;
 
-    Ok(
-        SDKTracerProvider::builder()
-            .with_resource(get_resource(service_name))
-            .with_batch_exporter(span_exporter)
-            .build()
-    )
+    let exporter = DiskBufferExporter::new(RetryExporter::new(span_exporter, &otlp_config.export_policy, otlp_config.export_mode), &otlp_config.export_policy);
+    Ok(BinaryEncodingExporter::new(exporter, otlp_config.binary_policy))
+}
+
+
+/// Initializes the OTLP HTTP tracer provider with authentication.
+/// # Arguments
+/// * `otlp_config` - The OTLP trace configuration.
+/// * `resource` - The resource of the traces.
+/// * `token_provider` - An `Arc<dyn GetToken>` to provide access tokens.
+/// # Returns
+/// A `Result` containing the initialized `SDKTracerProvider` or a `TraceError`.
+#[cfg(not(feature = "noop"))]
+pub async fn get_reqwest_tracer_provider(otlp_config: &OTLPTraceConfig, resource: &Resource, token_provider: Arc<dyn GetToken>) -> anyhow::Result<SDKTracerProvider, TraceError> {
+    let client = default_client(otlp_config)?;
+    get_reqwest_tracer_provider_with_client(otlp_config, resource, token_provider, client).await
+}
+
+
+/// Stub of `get_reqwest_tracer_provider` with the `noop` feature, returning a tracer provider without exporter.
+#[cfg(feature = "noop")]
+pub async fn get_reqwest_tracer_provider(_otlp_config: &OTLPTraceConfig, _resource: &Resource, _token_provider: Arc<dyn GetToken>) -> anyhow::Result<SDKTracerProvider, TraceError> {
+    Ok(SDKTracerProvider::builder().build())
+}
+
+
+/// Initializes the OTLP HTTP tracer provider with authentication, sending requests through an existing
+/// reqwest client, so telemetry traffic shares the proxy, TLS and pool settings of the application.
+/// # Arguments
+/// * `otlp_config` - The OTLP trace configuration.
+/// * `resource` - The resource of the traces.
+/// * `token_provider` - An `Arc<dyn GetToken>` to provide access tokens.
+/// * `client` - The reqwest client sending the requests.
+/// # Returns
+/// A `Result` containing the initialized `SDKTracerProvider` or a `TraceError`.
+#[cfg(not(feature = "noop"))]
+pub async fn get_reqwest_tracer_provider_with_client(otlp_config: &OTLPTraceConfig, resource: &Resource, token_provider: Arc<dyn GetToken>, client: reqwest::Client) -> anyhow::Result<SDKTracerProvider, TraceError> {
+    let builder = with_span_limits(SDKTracerProvider::builder(), &otlp_config.span_limits)
+        .with_resource(resource.clone())
+        .with_sampler(build_sampler(&otlp_config.sampler))
+        .with_id_generator(build_id_generator(&otlp_config.id_generator));
+    Ok(with_export_mode(builder, get_reqwest_span_exporter_with_client(otlp_config, token_provider, client)?, otlp_config.export_mode, &otlp_config.export_policy, "reqwest").build())
+}
+
+
+/// Stub of `get_reqwest_tracer_provider_with_client` with the `noop` feature, returning a tracer provider without exporter.
+#[cfg(feature = "noop")]
+pub async fn get_reqwest_tracer_provider_with_client(_otlp_config: &OTLPTraceConfig, _resource: &Resource, _token_provider: Arc<dyn GetToken>, _client: reqwest::Client) -> anyhow::Result<SDKTracerProvider, TraceError> {
+    Ok(SDKTracerProvider::builder().build())
 }
 
 
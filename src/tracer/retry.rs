@@ -0,0 +1,263 @@
+#[cfg(not(feature = "noop"))]
+use std::collections::hash_map::RandomState;
+#[cfg(not(feature = "noop"))]
+use std::hash::{BuildHasher, Hasher};
+#[cfg(not(feature = "noop"))]
+use std::sync::Arc;
+#[cfg(not(feature = "noop"))]
+use std::time::{Duration, Instant};
+use opentelemetry_http::HttpError;
+#[cfg(not(feature = "noop"))]
+use opentelemetry_sdk::Resource;
+#[cfg(not(feature = "noop"))]
+use opentelemetry_sdk::error::{OTelSdkError, OTelSdkResult};
+#[cfg(not(feature = "noop"))]
+use opentelemetry_sdk::trace::{SpanData, SpanExporter};
+#[cfg(not(feature = "noop"))]
+use tonic::Code;
+#[cfg(not(feature = "noop"))]
+use tower::retry::budget::{Budget, TpsBudget};
+#[cfg(not(feature = "noop"))]
+use crate::auth::GetToken;
+#[cfg(not(feature = "noop"))]
+use crate::config::{ExportMode, ExportPolicyConfig};
+#[cfg(not(feature = "noop"))]
+use crate::tracer::on_tokio;
+
+
+/// Delay before the first retry, doubled on each subsequent retry.
+#[cfg(not(feature = "noop"))]
+const INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Maximum delay between two retries.
+#[cfg(not(feature = "noop"))]
+const MAX_BACKOFF: Duration = Duration::from_secs(5);
+
+/// HTTP status codes for which an export is retried, as per the OTLP specification.
+#[cfg(not(feature = "noop"))]
+const RETRYABLE_HTTP_STATUSES: &[u16] = &[429, 502, 503, 504];
+
+/// gRPC status codes for which an export is retried, as per the OTLP specification.
+#[cfg(not(feature = "noop"))]
+const RETRYABLE_GRPC_CODES: &[Code] = &[Code::Cancelled, Code::DeadlineExceeded, Code::Aborted, Code::OutOfRange, Code::Unavailable, Code::DataLoss];
+
+/// Prefix of the HTTP status code in the errors of the OTLP HTTP exporter.
+#[cfg(not(feature = "noop"))]
+const HTTP_STATUS_PREFIX: &str = "Status Code: ";
+
+/// Prefix of the errors of the HTTP clients of the exporters for requests which did not reach the collector.
+const TRANSPORT_ERROR_PREFIX: &str = "transport error: ";
+
+/// Period over which exports are counted towards the retry budget.
+#[cfg(not(feature = "noop"))]
+const BUDGET_TTL: Duration = Duration::from_secs(10);
+
+
+/// A span exporter that retries the failed exports of the wrapped exporter which may succeed later,
+/// e.g. while the collector restarts.
+///
+/// Exports rejected with HTTP 429, 502, 503 or 504, or with a transient gRPC status such as UNAVAILABLE,
+/// and exports failing before reaching the collector are retried with a jittered exponential backoff.
+/// Other failures, e.g. HTTP 400 or INVALID_ARGUMENT, and failures without a status are not retried.
+/// Retries are limited per export by `max_retries` and `max_elapsed`, and across exports by a tower
+/// `TpsBudget`, so a struggling collector is not overwhelmed by retries.
+///
+/// With `ExportMode::Simple`, exports are not retried, since each export blocks the thread ending the span.
+///
+/// With a token provider, the exports rejected with the gRPC UNAUTHENTICATED or PERMISSION_DENIED status are
+/// recorded as authentication failures of the provider.
+#[cfg(not(feature = "noop"))]
+#[derive(Debug)]
+pub struct RetryExporter<E: SpanExporter> {
+    inner: E,
+    max_retries: u32,
+    max_elapsed: Duration,
+    budget: TpsBudget,
+    token_provider: Option<Arc<dyn GetToken>>,
+}
+
+
+/// Cause of a failed export, recovered from the error of the exporter.
+#[cfg(not(feature = "noop"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ExportFailure {
+    /// The collector answered with an HTTP status code.
+    Http(u16),
+    /// The collector, or the gRPC transport, answered with a gRPC status code.
+    Grpc(Code),
+    /// The request did not reach the collector, e.g. because the connection was refused.
+    Transport,
+    /// The export timed out.
+    Timeout,
+    /// The exporter is shut down, or the error carries no status.
+    Other,
+}
+
+
+#[cfg(not(feature = "noop"))]
+impl ExportFailure {
+    /// Classifies a failed export by the HTTP status or gRPC code of its error.
+    ///
+    /// The OTLP exporters only report failures as messages, so the status is read from the formats they
+    /// render: `Status Code: 503` for HTTP, and the `Display` or `Debug` rendering of the `tonic::Status` for gRPC.
+    pub(crate) fn classify(error: &OTelSdkError) -> Self {
+        let message = match error {
+            OTelSdkError::AlreadyShutdown => return ExportFailure::Other,
+            OTelSdkError::Timeout(_) => return ExportFailure::Timeout,
+            OTelSdkError::InternalFailure(message) => message,
+        };
+        if let Some(index) = message.find(HTTP_STATUS_PREFIX) {
+            let status = &message[index + HTTP_STATUS_PREFIX.len()..];
+            let digits = status.find(|c: char| !c.is_ascii_digit()).unwrap_or(status.len());
+            if let Ok(status) = status[..digits].parse() {
+                return ExportFailure::Http(status);
+            }
+        }
+        let code = (0..=16).map(Code::from_i32).find(|code| {
+            message.contains(&format!("status: '{}'", code.description())) || message.contains(&format!("code: {:?},", code))
+        });
+        if let Some(code) = code {
+            return ExportFailure::Grpc(code);
+        }
+        if message.contains(TRANSPORT_ERROR_PREFIX) {
+            return ExportFailure::Transport;
+        }
+        ExportFailure::Other
+    }
+
+    /// Returns whether the export may succeed if retried.
+    pub(crate) fn is_retryable(self) -> bool {
+        match self {
+            ExportFailure::Http(status) => RETRYABLE_HTTP_STATUSES.contains(&status),
+            ExportFailure::Grpc(code) => RETRYABLE_GRPC_CODES.contains(&code),
+            ExportFailure::Transport | ExportFailure::Timeout => true,
+            ExportFailure::Other => false,
+        }
+    }
+}
+
+
+/// Returns whether a failed export may succeed if retried.
+#[cfg(not(feature = "noop"))]
+pub(crate) fn is_retryable(error: &OTelSdkError) -> bool {
+    ExportFailure::classify(error).is_retryable()
+}
+
+
+/// Wraps the error of an HTTP request which did not reach the collector, so the failed export is retried.
+pub(crate) fn transport_error(error: HttpError) -> HttpError {
+    format!("{TRANSPORT_ERROR_PREFIX}{error}").into()
+}
+
+
+/// Returns the delay before a retry: the exponential backoff of the attempt, capped at `MAX_BACKOFF`,
+/// reduced by a random jitter of up to half of it, so exporters do not retry in lockstep.
+#[cfg(not(feature = "noop"))]
+fn backoff_delay(attempt: u32) -> Duration {
+    let delay = INITIAL_BACKOFF.saturating_mul(2u32.saturating_pow(attempt)).min(MAX_BACKOFF);
+    let jitter = RandomState::new().build_hasher().finish() as f64 / u64::MAX as f64;
+    delay.mul_f64(1.0 - jitter / 2.0)
+}
+
+
+/// Waits before a retry on the Tokio timer, without blocking the runtime the export runs on.
+#[cfg(not(feature = "noop"))]
+async fn backoff(delay: Duration) {
+    let _ = on_tokio(async {
+        tokio::time::sleep(delay).await;
+        Ok::<_, std::io::Error>(())
+    }).await;
+}
+
+
+#[cfg(not(feature = "noop"))]
+impl<E: SpanExporter> RetryExporter<E> {
+    /// Creates a new instance of `RetryExporter`.
+    /// # Arguments
+    /// * `inner` - The exporter to delegate to.
+    /// * `policy` - The export policy, setting the maximum retries, the maximum elapsed time and the retry budget.
+    /// * `mode` - The export mode of the exporter. Exports are not retried with `ExportMode::Simple`.
+    /// # Returns
+    /// A new `RetryExporter` instance.
+    pub fn new(inner: E, policy: &ExportPolicyConfig, mode: ExportMode) -> Self {
+        let retry_ratio = (policy.retry_budget_percent as f32 / 100.0).min(1000.0);
+        Self {
+            inner,
+            max_retries: match mode {
+                ExportMode::Batch => policy.max_retries,
+                ExportMode::Simple => 0,
+            },
+            max_elapsed: policy.max_elapsed,
+            budget: TpsBudget::new(BUDGET_TTL, 1, retry_ratio),
+            token_provider: None,
+        }
+    }
+
+    /// Records the exports rejected with the gRPC UNAUTHENTICATED or PERMISSION_DENIED status as authentication
+    /// failures of the given provider, for the gRPC exporter, whose transport does not expose the statuses.
+    /// # Arguments
+    /// * `token_provider` - The token provider of the exports.
+    /// # Returns
+    /// The `RetryExporter` instance recording the authentication failures.
+    pub(crate) fn with_token_provider(mut self, token_provider: Arc<dyn GetToken>) -> Self {
+        self.token_provider = Some(token_provider);
+        self
+    }
+
+    /// Exports a batch, retrying the failures which may succeed later.
+    async fn export_with_retries(&self, batch: Vec<SpanData>) -> OTelSdkResult {
+        self.budget.deposit();
+        let start = Instant::now();
+        let mut attempt = 0;
+        loop {
+            // The batch is only copied while a retry may follow, so the last attempt takes it.
+            let result = if attempt < self.max_retries {
+                self.inner.export(batch.clone()).await
+            } else {
+                return self.inner.export(batch).await;
+            };
+            let Err(error) = &result else {
+                return result;
+            };
+            let delay = backoff_delay(attempt);
+            if !is_retryable(error)
+                || start.elapsed() + delay > self.max_elapsed
+                || !self.budget.withdraw() {
+                return result;
+            }
+            backoff(delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// Returns the wrapped exporter.
+    pub(crate) fn inner(&self) -> &E {
+        &self.inner
+    }
+}
+
+
+/// Implementation of the SpanExporter trait for RetryExporter
+#[cfg(not(feature = "noop"))]
+impl<E: SpanExporter> SpanExporter for RetryExporter<E> {
+    async fn export(&self, batch: Vec<SpanData>) -> OTelSdkResult {
+        let result = self.export_with_retries(batch).await;
+        if let (Err(error), Some(token_provider)) = (&result, &self.token_provider)
+            && let ExportFailure::Grpc(code @ (Code::Unauthenticated | Code::PermissionDenied)) = ExportFailure::classify(error) {
+            token_provider.record_rejection(&format!("export rejected with gRPC status {:?}", code));
+        }
+        result
+    }
+
+    fn shutdown_with_timeout(&mut self, timeout: Duration) -> OTelSdkResult {
+        self.inner.shutdown_with_timeout(timeout)
+    }
+
+    fn force_flush(&mut self) -> OTelSdkResult {
+        self.inner.force_flush()
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        self.inner.set_resource(resource)
+    }
+}
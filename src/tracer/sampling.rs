@@ -0,0 +1,203 @@
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+#[cfg(not(feature = "noop"))]
+use std::time::Duration;
+use opentelemetry::{Context, KeyValue};
+use opentelemetry::trace::{Link, SamplingDecision, SamplingResult, SpanKind, TraceContextExt, TraceId};
+use opentelemetry_sdk::trace::ShouldSample;
+#[cfg(not(feature = "noop"))]
+use opentelemetry_sdk::trace::Sampler;
+use crate::config::CustomSampler;
+#[cfg(not(feature = "noop"))]
+use crate::config::SamplerConfig;
+
+
+/// Maximum number of sampling decisions logged per second.
+#[cfg(not(feature = "noop"))]
+const MAX_LOGGED_DECISIONS_PER_SEC: u32 = 10;
+
+
+/// Fixed-window rate limiter shared by the clones of a `LoggingSampler`.
+#[cfg(not(feature = "noop"))]
+#[derive(Debug)]
+struct RateLimiter {
+    window_start: Instant,
+    count: u32,
+}
+
+
+#[cfg(not(feature = "noop"))]
+impl RateLimiter {
+    /// Returns whether another event is allowed in the current one-second window.
+    fn allow(&mut self) -> bool {
+        if self.window_start.elapsed() >= Duration::from_secs(1) {
+            self.window_start = Instant::now();
+            self.count = 0;
+        }
+        self.count += 1;
+        self.count <= MAX_LOGGED_DECISIONS_PER_SEC
+    }
+}
+
+
+/// A sampler that logs the decisions of the sampler of a configuration for root spans, with the
+/// rule of the configuration deciding them, e.g. `trace_id_ratio`.
+///
+/// Decisions are logged at TRACE level with the `rust_otel_setup::sampling` target, and rate
+/// limited so that high-throughput services are not flooded.
+#[cfg(not(feature = "noop"))]
+#[derive(Debug, Clone)]
+pub struct LoggingSampler {
+    inner: ConfiguredSampler,
+    rule: &'static str,
+    limiter: Arc<Mutex<RateLimiter>>,
+}
+
+
+#[cfg(not(feature = "noop"))]
+impl LoggingSampler {
+    /// Creates a new instance of `LoggingSampler`.
+    /// # Arguments
+    /// * `config` - The configuration of the sampler whose decisions are logged.
+    /// # Returns
+    /// A new `LoggingSampler` instance.
+    pub fn new(config: &SamplerConfig) -> Self {
+        let limiter = RateLimiter { window_start: Instant::now(), count: 0 };
+        Self { inner: build_sampler(config), rule: root_rule(config), limiter: Arc::new(Mutex::new(limiter)) }
+    }
+}
+
+
+/// Returns the rule of a sampler configuration deciding the sampling of root spans.
+#[cfg(not(feature = "noop"))]
+fn root_rule(config: &SamplerConfig) -> &'static str {
+    match config {
+        SamplerConfig::AlwaysOn => "always_on",
+        SamplerConfig::AlwaysOff => "always_off",
+        SamplerConfig::TraceIdRatio(_) => "trace_id_ratio",
+        SamplerConfig::RateLimiting { .. } => "rate_limiting",
+        // Root spans have no parent, so the sampler of the root spans decides.
+        SamplerConfig::ParentBased(root) => root_rule(root),
+        SamplerConfig::Custom(_) => "custom",
+    }
+}
+
+
+/// Token bucket shared by the clones of a `RateLimitingSampler`.
+#[derive(Debug)]
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+
+/// A sampler that samples at most a fixed number of spans per second.
+///
+/// Tokens are replenished continuously, up to one second worth of spans, so short bursts are
+/// sampled while sustained traffic is capped. Wrap it in a parent-based sampler to keep traces complete.
+#[derive(Debug, Clone)]
+pub struct RateLimitingSampler {
+    spans_per_second: f64,
+    bucket: Arc<Mutex<TokenBucket>>,
+}
+
+
+impl RateLimitingSampler {
+    /// Creates a new instance of `RateLimitingSampler`.
+    /// # Arguments
+    /// * `spans_per_second` - The maximum sustained number of sampled spans per second.
+    /// # Returns
+    /// A new `RateLimitingSampler` instance.
+    pub fn new(spans_per_second: u32) -> Self {
+        let spans_per_second = spans_per_second as f64;
+        let bucket = TokenBucket { tokens: spans_per_second, last_refill: Instant::now() };
+        Self { spans_per_second, bucket: Arc::new(Mutex::new(bucket)) }
+    }
+
+    /// Takes a token from the bucket, returning whether one was available.
+    fn try_acquire(&self) -> bool {
+        let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        let refill = now.duration_since(bucket.last_refill).as_secs_f64() * self.spans_per_second;
+        bucket.tokens = (bucket.tokens + refill).min(self.spans_per_second);
+        bucket.last_refill = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+
+/// Implementation of the ShouldSample trait for RateLimitingSampler
+impl ShouldSample for RateLimitingSampler {
+    fn should_sample(&self, parent_context: Option<&Context>, _trace_id: TraceId, _name: &str, _span_kind: &SpanKind, _attributes: &[KeyValue], _links: &[Link]) -> SamplingResult {
+        let decision = if self.try_acquire() { SamplingDecision::RecordAndSample } else { SamplingDecision::Drop };
+        let trace_state = parent_context
+            .map(|cx| cx.span().span_context().trace_state().clone())
+            .unwrap_or_default();
+        SamplingResult { decision, attributes: Vec::new(), trace_state }
+    }
+}
+
+
+/// The sampler of a `SamplerConfig`.
+#[cfg(not(feature = "noop"))]
+#[derive(Debug, Clone)]
+pub struct ConfiguredSampler(Box<dyn ShouldSample>);
+
+
+/// Returns the sampler of the given configuration.
+#[cfg(not(feature = "noop"))]
+pub fn build_sampler(config: &SamplerConfig) -> ConfiguredSampler {
+    ConfiguredSampler(match config {
+        SamplerConfig::AlwaysOn => Box::new(Sampler::AlwaysOn),
+        SamplerConfig::AlwaysOff => Box::new(Sampler::AlwaysOff),
+        SamplerConfig::TraceIdRatio(ratio) => Box::new(Sampler::TraceIdRatioBased(*ratio)),
+        SamplerConfig::RateLimiting { spans_per_second } => Box::new(RateLimitingSampler::new(*spans_per_second)),
+        SamplerConfig::ParentBased(root) => Box::new(Sampler::ParentBased(build_sampler(root).0)),
+        SamplerConfig::Custom(custom) => Box::new(custom.clone()),
+    })
+}
+
+
+/// Implementation of the ShouldSample trait for ConfiguredSampler
+#[cfg(not(feature = "noop"))]
+impl ShouldSample for ConfiguredSampler {
+    fn should_sample(&self, parent_context: Option<&Context>, trace_id: TraceId, name: &str, span_kind: &SpanKind, attributes: &[KeyValue], links: &[Link]) -> SamplingResult {
+        self.0.should_sample(parent_context, trace_id, name, span_kind, attributes, links)
+    }
+}
+
+
+/// Implementation of the ShouldSample trait for CustomSampler, delegating to the user sampler.
+impl ShouldSample for CustomSampler {
+    fn should_sample(&self, parent_context: Option<&Context>, trace_id: TraceId, name: &str, span_kind: &SpanKind, attributes: &[KeyValue], links: &[Link]) -> SamplingResult {
+        self.0.should_sample(parent_context, trace_id, name, span_kind, attributes, links)
+    }
+}
+
+
+/// Implementation of the ShouldSample trait for LoggingSampler
+#[cfg(not(feature = "noop"))]
+impl ShouldSample for LoggingSampler {
+    fn should_sample(&self, parent_context: Option<&Context>, trace_id: TraceId, name: &str, span_kind: &SpanKind, attributes: &[KeyValue], links: &[Link]) -> SamplingResult {
+        let result = self.inner.should_sample(parent_context, trace_id, name, span_kind, attributes, links);
+
+        let is_root = parent_context.is_none_or(|cx| !cx.has_active_span());
+        if is_root && self.limiter.lock().unwrap_or_else(|e| e.into_inner()).allow() {
+            tracing::trace!(
+                target: "rust_otel_setup::sampling",
+                sampler = ?self.inner,
+                rule = self.rule,
+                span_name = name,
+                %trace_id,
+                decision = ?result.decision,
+                "Root span sampling decision",
+            );
+        }
+        result
+    }
+}
@@ -0,0 +1,57 @@
+use std::time::Duration;
+use opentelemetry::Context;
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::error::OTelSdkResult;
+use opentelemetry_sdk::trace::{Span, SpanData, SpanProcessor};
+use crate::config::ScopeFilter;
+
+
+/// A span processor that passes to the wrapped processor only the spans of the instrumentation scopes
+/// accepted by its filter, so each exporter of a provider receives its own subset of the spans.
+///
+/// Spans created through `tracing` share the scope of the tracer installed in the subscriber, named
+/// after the service; other scopes come from instrumentation libraries using their own tracers.
+#[derive(Debug)]
+pub struct ScopeRoutingProcessor<P: SpanProcessor> {
+    inner: P,
+    scopes: ScopeFilter,
+}
+
+
+impl<P: SpanProcessor> ScopeRoutingProcessor<P> {
+    /// Creates a new instance of `ScopeRoutingProcessor`.
+    /// # Arguments
+    /// * `inner` - The processor receiving the spans of the accepted scopes.
+    /// * `scopes` - The instrumentation scopes accepted by the processor.
+    /// # Returns
+    /// A new `ScopeRoutingProcessor` instance.
+    pub fn new(inner: P, scopes: ScopeFilter) -> Self {
+        Self { inner, scopes }
+    }
+}
+
+
+/// Implementation of the SpanProcessor trait for ScopeRoutingProcessor
+impl<P: SpanProcessor> SpanProcessor for ScopeRoutingProcessor<P> {
+    fn on_start(&self, span: &mut Span, cx: &Context) {
+        self.inner.on_start(span, cx)
+    }
+
+    fn on_end(&self, span: SpanData) {
+        if self.scopes.matches(span.instrumentation_scope.name()) {
+            self.inner.on_end(span)
+        }
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        self.inner.force_flush()
+    }
+
+    fn shutdown_with_timeout(&self, timeout: Duration) -> OTelSdkResult {
+        self.inner.shutdown_with_timeout(timeout)
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        self.inner.set_resource(resource)
+    }
+}
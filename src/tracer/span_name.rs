@@ -0,0 +1,77 @@
+use std::time::Duration;
+use opentelemetry::Context;
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::error::OTelSdkResult;
+use opentelemetry_sdk::trace::{Span, SpanData, SpanProcessor};
+use regex::Regex;
+use crate::config::SpanNameRule;
+
+
+/// A span processor that rewrites the name of each ended span with regex rules, e.g. `GET /users/12345`
+/// to `GET /users/{id}`, before it is passed to the wrapped processor, so high-cardinality names do not
+/// reach the backends.
+///
+/// Rules are applied in order, each one to the output of the previous one. The names are rewritten at export
+/// time, in the span data the wrapped processor owns, so names updated after the span started are rewritten too
+/// and no span data is copied. Without rules, spans are passed through to the wrapped processor.
+#[derive(Debug)]
+pub struct SpanNameProcessor<P: SpanProcessor> {
+    inner: P,
+    rules: Vec<(Regex, String)>,
+}
+
+
+impl<P: SpanProcessor> SpanNameProcessor<P> {
+    /// Creates a new instance of `SpanNameProcessor`.
+    /// # Arguments
+    /// * `inner` - The processor receiving the renamed spans.
+    /// * `rules` - The rewriting rules.
+    /// # Returns
+    /// A new `SpanNameProcessor` instance, or an error if a pattern is not a valid regular expression.
+    pub fn new(inner: P, rules: &[SpanNameRule]) -> Result<Self, regex::Error> {
+        let rules = rules
+            .iter()
+            .map(|rule| Ok((Regex::new(&rule.pattern)?, rule.replacement.clone())))
+            .collect::<Result<_, regex::Error>>()?;
+        Ok(Self { inner, rules })
+    }
+
+    /// Applies the rules to a span name.
+    fn rewrite(&self, name: &str) -> Option<String> {
+        let mut rewritten = None;
+        for (pattern, replacement) in &self.rules {
+            let current = rewritten.as_deref().unwrap_or(name);
+            if pattern.is_match(current) {
+                rewritten = Some(pattern.replace_all(current, replacement.as_str()).into_owned());
+            }
+        }
+        rewritten
+    }
+}
+
+
+/// Implementation of the SpanProcessor trait for SpanNameProcessor
+impl<P: SpanProcessor> SpanProcessor for SpanNameProcessor<P> {
+    fn on_start(&self, span: &mut Span, cx: &Context) {
+        self.inner.on_start(span, cx)
+    }
+
+    fn on_end(&self, mut span: SpanData) {
+        if let Some(rewritten) = self.rewrite(&span.name) {
+            span.name = rewritten.into();
+        }
+        self.inner.on_end(span)
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        self.inner.force_flush()
+    }
+
+    fn shutdown_with_timeout(&self, timeout: Duration) -> OTelSdkResult {
+        self.inner.shutdown_with_timeout(timeout)
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        self.inner.set_resource(resource)
+    }
+}
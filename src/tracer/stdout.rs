@@ -1,12 +1,338 @@
-use opentelemetry_sdk::trace::TraceError;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::io::IsTerminal;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use chrono::{DateTime, Local, Utc};
+use opentelemetry::SpanId;
+use opentelemetry::trace::{Status, TraceId};
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::error::{OTelSdkError, OTelSdkResult};
+use opentelemetry_sdk::trace::{SpanData, SpanExporter, TraceError};
 use opentelemetry_sdk::trace::SdkTracerProvider as SDKTracerProvider;
-use opentelemetry_stdout as stdout;
+use serde_json::json;
+use crate::config::{StdoutFormat, StdoutTraceConfig, TimestampFormat, Timezone};
+use crate::tracer::json::{json_attributes, pairs};
+#[cfg(not(feature = "noop"))]
+use crate::tracer::sampling::build_sampler;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::id_generator::build_id_generator;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::with_span_limits;
+
+
+/// Maximum number of traces buffered by `StdoutSpanExporter` in the pretty format.
+const MAX_BUFFERED_TRACES: usize = 1000;
+
+
+/// ANSI escape codes used by the pretty format.
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const RED: &str = "\x1b[31m";
+const RESET: &str = "\x1b[0m";
+
+
+/// A span exporter that writes spans to standard output, with configurable timestamps.
+///
+/// In the pretty format, the spans of a trace are buffered until its local root span ends, then the
+/// trace is printed as a tree. Traces still buffered are printed when the exporter is flushed or shut down.
+#[derive(Debug)]
+pub struct StdoutSpanExporter {
+    config: StdoutTraceConfig,
+    resource: Resource,
+    is_shutdown: AtomicBool,
+    resource_emitted: AtomicBool,
+    colored: bool,
+    traces: Mutex<HashMap<TraceId, Vec<SpanData>>>,
+}
+
+
+/// Formats a timestamp following the given format and timezone.
+pub(crate) fn format_timestamp(time: SystemTime, format: TimestampFormat, timezone: Timezone) -> String {
+    match format {
+        TimestampFormat::EpochMillis => time
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis().to_string())
+            .unwrap_or_default(),
+        TimestampFormat::Rfc3339 => match timezone {
+            Timezone::Utc => DateTime::<Utc>::from(time).to_rfc3339(),
+            Timezone::Local => DateTime::<Local>::from(time).to_rfc3339(),
+        },
+        TimestampFormat::Default => match timezone {
+            Timezone::Utc => DateTime::<Utc>::from(time).format("%Y-%m-%d %H:%M:%S%.6f").to_string(),
+            Timezone::Local => DateTime::<Local>::from(time).format("%Y-%m-%d %H:%M:%S%.6f").to_string(),
+        },
+    }
+}
+
+
+/// Returns whether a span is the local root of its trace.
+fn is_local_root(span: &SpanData) -> bool {
+    span.parent_span_id == SpanId::INVALID || span.parent_span_is_remote
+}
+
+
+impl StdoutSpanExporter {
+    /// Creates a new instance of `StdoutSpanExporter`.
+    /// # Arguments
+    /// * `config` - The standard output trace configuration.
+    /// # Returns
+    /// A new `StdoutSpanExporter` instance.
+    pub fn new(config: &StdoutTraceConfig) -> Self {
+        Self {
+            config: config.clone(),
+            resource: Resource::builder().build(),
+            is_shutdown: AtomicBool::new(false),
+            resource_emitted: AtomicBool::new(false),
+            colored: std::io::stdout().is_terminal(),
+            traces: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Wraps text in an ANSI style if standard output is a terminal.
+    fn style(&self, style: &str, text: &str) -> String {
+        if self.colored {
+            format!("{}{}{}", style, text, RESET)
+        } else {
+            text.to_string()
+        }
+    }
+
+    /// Formats a timestamp following the configured format and timezone.
+    fn format_time(&self, time: SystemTime) -> String {
+        format_timestamp(time, self.config.timestamp_format, self.config.timezone)
+    }
+
+    /// Renders a batch of spans.
+    fn render(&self, batch: &[SpanData]) -> Result<String, std::fmt::Error> {
+        let mut out = String::new();
+        writeln!(out, "Spans")?;
+        if !self.resource_emitted.swap(true, Ordering::SeqCst) {
+            writeln!(out, "Resource")?;
+            if let Some(schema_url) = self.resource.schema_url() {
+                writeln!(out, "\tResource SchemaUrl: {:?}", schema_url)?;
+            }
+            for (k, v) in self.resource.iter() {
+                writeln!(out, "\t ->  {}={:?}", k, v)?;
+            }
+        }
+
+        for (i, span) in batch.iter().enumerate() {
+            writeln!(out, "Span #{}", i)?;
+            writeln!(out, "\tInstrumentation Scope")?;
+            writeln!(out, "\t\tName         : {:?}", span.instrumentation_scope.name())?;
+            if let Some(version) = span.instrumentation_scope.version() {
+                writeln!(out, "\t\tVersion  : {:?}", version)?;
+            }
+            writeln!(out)?;
+            writeln!(out, "\tName         : {}", span.name)?;
+            writeln!(out, "\tTraceId      : {}", span.span_context.trace_id())?;
+            writeln!(out, "\tSpanId       : {}", span.span_context.span_id())?;
+            if span.parent_span_id == SpanId::INVALID {
+                writeln!(out, "\tParentSpanId : None (root span)")?;
+            } else {
+                writeln!(out, "\tParentSpanId : {}", span.parent_span_id)?;
+            }
+            writeln!(out, "\tKind         : {:?}", span.span_kind)?;
+            writeln!(out, "\tStart time   : {}", self.format_time(span.start_time))?;
+            writeln!(out, "\tEnd time     : {}", self.format_time(span.end_time))?;
+            writeln!(out, "\tStatus       : {:?}", span.status)?;
+            for (index, kv) in span.attributes.iter().enumerate() {
+                if index == 0 {
+                    writeln!(out, "\tAttributes:")?;
+                }
+                writeln!(out, "\t\t ->  {}: {:?}", kv.key, kv.value)?;
+            }
+            for (index, event) in span.events.iter().enumerate() {
+                if index == 0 {
+                    writeln!(out, "\tEvents:")?;
+                }
+                writeln!(out, "\tEvent #{}", index)?;
+                writeln!(out, "\tName      : {}", event.name)?;
+                writeln!(out, "\tTimestamp : {}", self.format_time(event.timestamp))?;
+                for kv in event.attributes.iter() {
+                    writeln!(out, "\t\t ->  {}: {:?}", kv.key, kv.value)?;
+                }
+            }
+            for (index, link) in span.links.iter().enumerate() {
+                if index == 0 {
+                    writeln!(out, "\tLinks:")?;
+                }
+                writeln!(out, "\tLink #{}", index)?;
+                writeln!(out, "\tTraceId: {}", link.span_context.trace_id())?;
+                writeln!(out, "\tSpanId : {}", link.span_context.span_id())?;
+            }
+        }
+        Ok(out)
+    }
+
+    /// Renders a batch of spans as compact JSON lines.
+    fn render_json(&self, batch: &[SpanData]) -> String {
+        batch.iter().map(|span| {
+            let parent_span_id = (span.parent_span_id != SpanId::INVALID).then(|| span.parent_span_id.to_string());
+            let line = json!({
+                "trace_id": span.span_context.trace_id().to_string(),
+                "span_id": span.span_context.span_id().to_string(),
+                "parent_span_id": parent_span_id,
+                "name": span.name,
+                "kind": format!("{:?}", span.span_kind),
+                "start_time": self.format_time(span.start_time),
+                "end_time": self.format_time(span.end_time),
+                "status": format!("{:?}", span.status),
+                "attributes": json_attributes(pairs(&span.attributes)),
+                "events": span.events.iter().map(|event| json!({
+                    "name": event.name,
+                    "time": self.format_time(event.timestamp),
+                    "attributes": json_attributes(pairs(&event.attributes)),
+                })).collect::<Vec<_>>(),
+            });
+            format!("{}\n", line)
+        }).collect()
+    }
+
+    /// Renders a span and its children as an indented tree.
+    fn render_tree(&self, out: &mut String, span: &SpanData, children: &HashMap<SpanId, Vec<&SpanData>>, prefix: &str, last: bool, root: bool) -> std::fmt::Result {
+        let (branch, indent) = match (root, last) {
+            (true, _) => ("", ""),
+            (false, true) => ("└─ ", "   "),
+            (false, false) => ("├─ ", "│  "),
+        };
+        let duration = span.end_time.duration_since(span.start_time).unwrap_or_default();
+        let status = match &span.status {
+            Status::Error { description } => format!(" {}", self.style(RED, &format!("error: {}", description))),
+            _ => String::new(),
+        };
+        writeln!(out, "{}{}{} {}{}", prefix, branch, self.style(BOLD, &span.name), self.style(DIM, &format!("{:?}", duration)), status)?;
+
+        let child_prefix = format!("{}{}", prefix, indent);
+        let detail_prefix = match children.get(&span.span_context.span_id()) {
+            Some(_) => format!("{}│  ", child_prefix),
+            None => format!("{}   ", child_prefix),
+        };
+        if root {
+            writeln!(out, "{}{}", detail_prefix, self.style(DIM, &format!("trace {} at {}", span.span_context.trace_id(), self.format_time(span.start_time))))?;
+        }
+        for kv in span.attributes.iter() {
+            writeln!(out, "{}{}", detail_prefix, self.style(DIM, &format!("{} = {}", kv.key, kv.value)))?;
+        }
+        for event in span.events.iter() {
+            writeln!(out, "{}{} {}", detail_prefix, self.style(DIM, &format!("event at {}:", self.format_time(event.timestamp))), event.name)?;
+        }
+
+        if let Some(spans) = children.get(&span.span_context.span_id()) {
+            for (i, child) in spans.iter().enumerate() {
+                self.render_tree(out, child, children, &child_prefix, i + 1 == spans.len(), false)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Renders the spans of a trace as trees, one per span whose parent is not among the spans.
+    fn render_pretty(&self, mut spans: Vec<SpanData>) -> Result<String, std::fmt::Error> {
+        spans.sort_by_key(|span| span.start_time);
+        let ids: Vec<SpanId> = spans.iter().map(|span| span.span_context.span_id()).collect();
+        let mut children: HashMap<SpanId, Vec<&SpanData>> = HashMap::new();
+        let mut roots = Vec::new();
+        for span in &spans {
+            if ids.contains(&span.parent_span_id) {
+                children.entry(span.parent_span_id).or_default().push(span);
+            } else {
+                roots.push(span);
+            }
+        }
+        let mut out = String::new();
+        for root in roots {
+            self.render_tree(&mut out, root, &children, "", true, true)?;
+        }
+        Ok(out)
+    }
+
+    /// Buffers a batch of spans, returning the traces ready to be printed: the traces whose local root
+    /// ended, or every trace if the buffer is full.
+    fn take_ready(&self, batch: Vec<SpanData>) -> Vec<Vec<SpanData>> {
+        let mut traces = self.traces.lock().unwrap_or_else(|e| e.into_inner());
+        let mut completed = Vec::new();
+        for span in batch {
+            let trace_id = span.span_context.trace_id();
+            if is_local_root(&span) {
+                completed.push(trace_id);
+            }
+            traces.entry(trace_id).or_default().push(span);
+        }
+        if traces.len() > MAX_BUFFERED_TRACES {
+            return traces.drain().map(|(_, spans)| spans).collect();
+        }
+        completed.iter().filter_map(|trace_id| traces.remove(trace_id)).collect()
+    }
+
+    /// Prints every buffered trace.
+    fn flush_traces(&self) -> OTelSdkResult {
+        let traces: Vec<_> = self.traces.lock().unwrap_or_else(|e| e.into_inner()).drain().map(|(_, spans)| spans).collect();
+        for spans in traces {
+            let out = self.render_pretty(spans).map_err(|e| OTelSdkError::InternalFailure(e.to_string()))?;
+            print!("{}", out);
+        }
+        Ok(())
+    }
+}
+
+
+/// Implementation of the SpanExporter trait for StdoutSpanExporter
+impl SpanExporter for StdoutSpanExporter {
+    async fn export(&self, batch: Vec<SpanData>) -> OTelSdkResult {
+        if self.is_shutdown.load(Ordering::SeqCst) {
+            return Err(OTelSdkError::AlreadyShutdown);
+        }
+        let out = match self.config.format {
+            StdoutFormat::Default => self.render(&batch).map_err(|e| OTelSdkError::InternalFailure(e.to_string()))?,
+            StdoutFormat::Json => self.render_json(&batch),
+            StdoutFormat::Pretty => {
+                let mut out = String::new();
+                for spans in self.take_ready(batch) {
+                    out.push_str(&self.render_pretty(spans).map_err(|e| OTelSdkError::InternalFailure(e.to_string()))?);
+                }
+                out
+            },
+        };
+        print!("{}", out);
+        Ok(())
+    }
+
+    fn force_flush(&mut self) -> OTelSdkResult {
+        self.flush_traces()
+    }
+
+    // The processors shut the exporters down through `shutdown_with_timeout`, and the simple processor
+    // does not forward `force_flush`, so the traces whose local root did not end are printed here.
+    fn shutdown_with_timeout(&mut self, _timeout: Duration) -> OTelSdkResult {
+        self.is_shutdown.store(true, Ordering::SeqCst);
+        self.flush_traces()
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        self.resource = resource.clone();
+    }
+}
+
 
 /// Returns a tracer provider that exports spans to standard output.
-pub async fn get_stdout_tracer_provider() -> Result<SDKTracerProvider, TraceError> {
+/// # Arguments
+/// * `config` - The standard output trace configuration.
+#[cfg(not(feature = "noop"))]
+pub async fn get_stdout_tracer_provider(config: &StdoutTraceConfig) -> Result<SDKTracerProvider, TraceError> {
     Ok(
-        SDKTracerProvider::builder()
-            .with_simple_exporter(stdout::SpanExporter::default())
+        with_span_limits(SDKTracerProvider::builder(), &config.span_limits)
+            .with_sampler(build_sampler(&config.sampler))
+            .with_id_generator(build_id_generator(&config.id_generator))
+            .with_simple_exporter(StdoutSpanExporter::new(config))
             .build()
     )
-}
\ No newline at end of file
+}
+
+
+/// Stub of `get_stdout_tracer_provider` with the `noop` feature, returning a tracer provider without exporter.
+#[cfg(feature = "noop")]
+pub async fn get_stdout_tracer_provider(_config: &StdoutTraceConfig) -> Result<SDKTracerProvider, TraceError> {
+    Ok(SDKTracerProvider::builder().build())
+}
@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use opentelemetry::Context;
+use opentelemetry::trace::{SpanId, Status, TraceId};
+use opentelemetry_sdk::error::OTelSdkResult;
+use opentelemetry_sdk::trace::{Span, SpanData, SpanProcessor};
+
+
+/// Maximum number of traces tracked by `TraceSummaryProcessor`.
+const MAX_TRACKED_TRACES: usize = 10_000;
+
+/// Number of traces listed in each section of the summary.
+const TOP_TRACES: usize = 5;
+
+
+/// Information about a trace captured by `TraceSummaryProcessor`.
+#[derive(Debug, Clone)]
+struct TraceEntry {
+    root_name: Option<String>,
+    start: SystemTime,
+    end: SystemTime,
+    span_count: usize,
+    has_error: bool,
+}
+
+
+impl TraceEntry {
+    /// Returns the duration between the earliest span start and the latest span end.
+    fn duration(&self) -> Duration {
+        self.end.duration_since(self.start).unwrap_or_default()
+    }
+
+    /// Returns a one-line description of the trace.
+    fn describe(&self, trace_id: &TraceId) -> String {
+        format!(
+            "  {} {} {:.2?} ({} spans)",
+            trace_id,
+            self.root_name.as_deref().unwrap_or("<unknown root>"),
+            self.duration(),
+            self.span_count,
+        )
+    }
+}
+
+
+/// A span processor that prints a human-readable summary of the captured traces on shutdown.
+///
+/// The summary includes the number of traces and spans, the slowest traces and the traces
+/// containing errors. Meant for development, CLI tools and integration tests.
+#[derive(Debug, Default)]
+pub struct TraceSummaryProcessor {
+    traces: Mutex<HashMap<TraceId, TraceEntry>>,
+}
+
+
+impl TraceSummaryProcessor {
+    /// Creates a new instance of `TraceSummaryProcessor`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Renders the summary of the captured traces.
+    fn render(&self) -> String {
+        let traces = self.traces.lock().unwrap_or_else(|e| e.into_inner());
+        let span_count: usize = traces.values().map(|t| t.span_count).sum();
+        let mut lines = vec![format!("Trace summary: {} traces, {} spans", traces.len(), span_count)];
+
+        let mut slowest: Vec<_> = traces.iter().collect();
+        slowest.sort_by_key(|(_, t)| std::cmp::Reverse(t.duration()));
+        lines.push("Slowest traces:".to_string());
+        lines.extend(slowest.iter().take(TOP_TRACES).map(|(id, t)| t.describe(id)));
+
+        let errors: Vec<_> = traces.iter().filter(|(_, t)| t.has_error).collect();
+        lines.push(format!("Error traces: {}", errors.len()));
+        lines.extend(errors.iter().take(TOP_TRACES).map(|(id, t)| t.describe(id)));
+        lines.join("\n")
+    }
+}
+
+
+/// Implementation of the SpanProcessor trait for TraceSummaryProcessor
+impl SpanProcessor for TraceSummaryProcessor {
+    fn on_start(&self, _span: &mut Span, _cx: &Context) {}
+
+    fn on_end(&self, span: SpanData) {
+        let mut traces = self.traces.lock().unwrap_or_else(|e| e.into_inner());
+        let trace_id = span.span_context.trace_id();
+        if !traces.contains_key(&trace_id) && traces.len() >= MAX_TRACKED_TRACES {
+            return;
+        }
+        let entry = traces.entry(trace_id).or_insert_with(|| TraceEntry {
+            root_name: None,
+            start: span.start_time,
+            end: span.end_time,
+            span_count: 0,
+            has_error: false,
+        });
+        entry.start = entry.start.min(span.start_time);
+        entry.end = entry.end.max(span.end_time);
+        entry.span_count += 1;
+        entry.has_error |= matches!(span.status, Status::Error { .. });
+        if span.parent_span_id == SpanId::INVALID {
+            entry.root_name = Some(span.name.to_string());
+        }
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        Ok(())
+    }
+
+    fn shutdown_with_timeout(&self, _timeout: Duration) -> OTelSdkResult {
+        println!("{}", self.render());
+        Ok(())
+    }
+}
@@ -0,0 +1,171 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use opentelemetry::Context;
+use opentelemetry::trace::{SpanId, Status, TraceId};
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::error::OTelSdkResult;
+use opentelemetry_sdk::trace::{Span, SpanData, SpanProcessor};
+use crate::config::TailSamplingConfig;
+
+
+/// Maximum number of traces buffered by `TailSamplingProcessor`.
+const MAX_BUFFERED_TRACES: usize = 10_000;
+
+
+/// Spans of a trace waiting for a sampling decision.
+#[derive(Debug)]
+struct BufferedTrace {
+    first_seen: Instant,
+    spans: Vec<SpanData>,
+}
+
+
+/// Traces waiting for a sampling decision, with their arrival order.
+#[derive(Debug, Default)]
+struct TraceBuffer {
+    traces: HashMap<TraceId, BufferedTrace>,
+    arrival: VecDeque<TraceId>,
+}
+
+
+/// A span processor that buffers the spans of each trace and decides whether to export the whole trace.
+///
+/// A trace is decided when its local root span ends, or when it has been buffered for the configured
+/// decision wait. Traces containing an error span or a span slower than the latency threshold are
+/// always exported; other traces are exported with the configured ratio, based on their trace ID.
+/// Spans ending after their trace was decided are buffered as a new trace.
+///
+/// Without a configuration, spans are passed through to the wrapped processor.
+#[derive(Debug)]
+pub struct TailSamplingProcessor<P: SpanProcessor> {
+    inner: P,
+    config: Option<TailSamplingConfig>,
+    buffer: Mutex<TraceBuffer>,
+}
+
+
+/// Returns whether the trace is kept by the ratio, using the same trace ID bits as the SDK ratio sampler.
+fn ratio_keeps(trace_id: TraceId, ratio: f64) -> bool {
+    let bytes = trace_id.to_bytes();
+    let mut low = [0u8; 8];
+    low.copy_from_slice(&bytes[8..16]);
+    let threshold = (ratio.clamp(0.0, 1.0) * (1u64 << 63) as f64) as u64;
+    (u64::from_be_bytes(low) >> 1) < threshold
+}
+
+
+impl<P: SpanProcessor> TailSamplingProcessor<P> {
+    /// Creates a new instance of `TailSamplingProcessor`.
+    /// # Arguments
+    /// * `inner` - The processor receiving the spans of the exported traces.
+    /// * `config` - The tail sampling configuration, or `None` to pass every span through.
+    /// # Returns
+    /// A new `TailSamplingProcessor` instance.
+    pub fn new(inner: P, config: Option<TailSamplingConfig>) -> Self {
+        Self { inner, config, buffer: Mutex::new(TraceBuffer::default()) }
+    }
+
+    /// Returns whether the spans of a trace are exported.
+    fn keep(config: &TailSamplingConfig, trace_id: TraceId, spans: &[SpanData]) -> bool {
+        spans.iter().any(|span| {
+            matches!(span.status, Status::Error { .. })
+                || span.end_time.duration_since(span.start_time).unwrap_or_default() >= config.latency_threshold
+        }) || ratio_keeps(trace_id, config.ratio)
+    }
+
+    /// Decides a buffered trace, forwarding its spans to the wrapped processor if it is kept.
+    fn decide(&self, config: &TailSamplingConfig, trace_id: TraceId, trace: BufferedTrace) {
+        if Self::keep(config, trace_id, &trace.spans) {
+            for span in trace.spans {
+                self.inner.on_end(span);
+            }
+        }
+    }
+
+    /// Removes the traces that must be decided: the given completed trace, the traces buffered for
+    /// longer than the decision wait, and the oldest traces beyond the buffer capacity.
+    fn take_ready(&self, config: &TailSamplingConfig, completed: Option<TraceId>, buffer: &mut TraceBuffer) -> Vec<(TraceId, BufferedTrace)> {
+        let mut ready = Vec::new();
+        if let Some(trace_id) = completed && let Some(trace) = buffer.traces.remove(&trace_id) {
+            ready.push((trace_id, trace));
+        }
+        while let Some(trace_id) = buffer.arrival.front().copied() {
+            let Some(trace) = buffer.traces.get(&trace_id) else {
+                // Already decided.
+                buffer.arrival.pop_front();
+                continue;
+            };
+            if trace.first_seen.elapsed() < config.decision_wait && buffer.traces.len() <= MAX_BUFFERED_TRACES {
+                break;
+            }
+            buffer.arrival.pop_front();
+            if let Some(trace) = buffer.traces.remove(&trace_id) {
+                ready.push((trace_id, trace));
+            }
+        }
+        ready
+    }
+
+    /// Decides every buffered trace.
+    fn flush_buffer(&self, config: &TailSamplingConfig) {
+        let traces: Vec<_> = {
+            let mut buffer = self.buffer.lock().unwrap_or_else(|e| e.into_inner());
+            buffer.arrival.clear();
+            buffer.traces.drain().collect()
+        };
+        for (trace_id, trace) in traces {
+            self.decide(config, trace_id, trace);
+        }
+    }
+}
+
+
+/// Implementation of the SpanProcessor trait for TailSamplingProcessor
+impl<P: SpanProcessor> SpanProcessor for TailSamplingProcessor<P> {
+    fn on_start(&self, span: &mut Span, cx: &Context) {
+        self.inner.on_start(span, cx)
+    }
+
+    fn on_end(&self, span: SpanData) {
+        let Some(config) = &self.config else {
+            return self.inner.on_end(span);
+        };
+        let trace_id = span.span_context.trace_id();
+        let is_local_root = span.parent_span_id == SpanId::INVALID || span.parent_span_is_remote;
+
+        let ready = {
+            let mut buffer = self.buffer.lock().unwrap_or_else(|e| e.into_inner());
+            if !buffer.traces.contains_key(&trace_id) {
+                buffer.arrival.push_back(trace_id);
+            }
+            buffer.traces
+                .entry(trace_id)
+                .or_insert_with(|| BufferedTrace { first_seen: Instant::now(), spans: Vec::new() })
+                .spans
+                .push(span);
+            self.take_ready(config, is_local_root.then_some(trace_id), &mut buffer)
+        };
+        for (trace_id, trace) in ready {
+            self.decide(config, trace_id, trace);
+        }
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        if let Some(config) = &self.config {
+            self.flush_buffer(config);
+        }
+        self.inner.force_flush()
+    }
+
+    fn shutdown_with_timeout(&self, timeout: Duration) -> OTelSdkResult {
+        if let Some(config) = &self.config {
+            self.flush_buffer(config);
+        }
+        self.inner.shutdown_with_timeout(timeout)
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        self.inner.set_resource(resource)
+    }
+}
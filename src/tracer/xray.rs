@@ -0,0 +1,194 @@
+use std::net::UdpSocket;
+use opentelemetry::trace::{SpanId, SpanKind, Status};
+use opentelemetry::{Key, Value};
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::error::{OTelSdkError, OTelSdkResult};
+use opentelemetry_sdk::trace::{SpanData, SpanExporter, TraceError};
+use opentelemetry_sdk::trace::SdkTracerProvider as SDKTracerProvider;
+use serde_json::{json, Map};
+use crate::config::XRayTraceConfig;
+#[cfg(not(feature = "noop"))]
+use crate::config::ExportPolicyConfig;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::id_generator::build_id_generator;
+use crate::tracer::json::{json_attributes, pairs, unix_nanos};
+#[cfg(not(feature = "noop"))]
+use crate::tracer::sampling::build_sampler;
+use crate::tracer::on_tokio;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::{with_export_mode, with_span_limits};
+
+
+/// Header preceding each segment document sent to the X-Ray daemon.
+const SEGMENT_HEADER: &str = "{\"format\": \"json\", \"version\": 1}\n";
+
+
+/// Maximum size of the datagrams sent to the X-Ray daemon, header included: the largest UDP payload over IPv4,
+/// within the 64KB segment documents accepted by the daemon.
+const MAX_DATAGRAM_SIZE: usize = 65_507;
+
+
+/// A span exporter that sends each span as a segment document to the UDP endpoint of the AWS X-Ray daemon.
+///
+/// Spans without a local parent are sent as segments named after the service, and the other spans as
+/// subsegments of their parent. Span attributes are sent as segment metadata, and the HTTP semantic
+/// convention attributes as the HTTP fields of the segment.
+///
+/// The segments are sent through the Tokio reactor, see `on_tokio`. Segments larger than a datagram are sent
+/// without their metadata or, if still too large, dropped.
+#[derive(Debug)]
+pub struct XRayDaemonExporter {
+    socket: UdpSocket,
+    daemon_address: String,
+    resource: Resource,
+}
+
+
+/// Returns the X-Ray trace ID of a span, e.g. `1-5759e988-bd862e3fe1be46a994272793`.
+fn xray_trace_id(span: &SpanData) -> String {
+    let id = span.span_context.trace_id().to_string();
+    format!("1-{}-{}", &id[..8], &id[8..])
+}
+
+
+/// Returns the value of a span attribute.
+fn attribute<'a>(span: &'a SpanData, key: &str) -> Option<&'a Value> {
+    span.attributes.iter().find(|kv| kv.key.as_str() == key).map(|kv| &kv.value)
+}
+
+
+impl XRayDaemonExporter {
+    /// Creates a new instance of `XRayDaemonExporter`.
+    /// # Arguments
+    /// * `daemon_address` - The UDP address of the X-Ray daemon, e.g. `127.0.0.1:2000`.
+    /// # Returns
+    /// A new `XRayDaemonExporter` instance, or an error if the UDP socket cannot be bound.
+    pub fn new(daemon_address: &str) -> std::io::Result<Self> {
+        let socket = UdpSocket::bind("0.0.0.0:0")?;
+        socket.set_nonblocking(true)?;
+        Ok(Self {
+            socket,
+            daemon_address: daemon_address.to_string(),
+            resource: Resource::builder().build(),
+        })
+    }
+
+    /// Renders a span as a segment document, header included, with the span attributes as metadata if enabled.
+    fn render(&self, span: &SpanData, with_metadata: bool) -> String {
+        let is_segment = span.parent_span_id == SpanId::INVALID || span.parent_span_is_remote;
+        let service = self.resource.get(&Key::from_static_str("service.name"))
+            .map(|value| value.as_str().into_owned())
+            .unwrap_or_else(|| "unknown_service".to_string());
+        let mut segment = Map::new();
+        segment.insert("name".to_string(), json!(if is_segment { service } else { span.name.to_string() }));
+        segment.insert("id".to_string(), json!(span.span_context.span_id().to_string()));
+        segment.insert("trace_id".to_string(), json!(xray_trace_id(span)));
+        segment.insert("start_time".to_string(), json!(unix_nanos(span.start_time) as f64 / 1e9));
+        segment.insert("end_time".to_string(), json!(unix_nanos(span.end_time) as f64 / 1e9));
+        if span.parent_span_id != SpanId::INVALID {
+            segment.insert("parent_id".to_string(), json!(span.parent_span_id.to_string()));
+        }
+        if !is_segment {
+            segment.insert("type".to_string(), json!("subsegment"));
+            if span.span_kind == SpanKind::Client {
+                segment.insert("namespace".to_string(), json!("remote"));
+            }
+        }
+        if let Status::Error { description } = &span.status {
+            segment.insert("fault".to_string(), json!(true));
+            segment.insert("cause".to_string(), json!({ "exceptions": [{ "message": description }] }));
+        }
+
+        let method = attribute(span, "http.request.method");
+        let url = attribute(span, "url.full").or_else(|| attribute(span, "url.path"));
+        let status = attribute(span, "http.response.status_code");
+        if method.is_some() || url.is_some() || status.is_some() {
+            segment.insert("http".to_string(), json!({
+                "request": {
+                    "method": method.map(|v| v.as_str()),
+                    "url": url.map(|v| v.as_str()),
+                },
+                "response": {
+                    "status": status.and_then(|v| v.as_str().parse::<u16>().ok()),
+                },
+            }));
+        }
+        if with_metadata {
+            segment.insert("metadata".to_string(), json!({
+                "default": json_attributes(pairs(&span.attributes)),
+                "span": { "name": span.name, "kind": format!("{:?}", span.span_kind) },
+            }));
+        }
+        format!("{}{}", SEGMENT_HEADER, serde_json::Value::Object(segment))
+    }
+
+    /// Renders a span as a segment document fitting in a datagram, without its metadata if needed,
+    /// or `None` if it is still too large.
+    fn render_datagram(&self, span: &SpanData) -> Option<String> {
+        Some(self.render(span, true))
+            .filter(|segment| segment.len() <= MAX_DATAGRAM_SIZE)
+            .or_else(|| Some(self.render(span, false)).filter(|segment| segment.len() <= MAX_DATAGRAM_SIZE))
+    }
+}
+
+
+/// Implementation of the SpanExporter trait for XRayDaemonExporter
+impl SpanExporter for XRayDaemonExporter {
+    async fn export(&self, batch: Vec<SpanData>) -> OTelSdkResult {
+        let segments = batch.iter().filter_map(|span| self.render_datagram(span)).collect::<Vec<_>>();
+        on_tokio(async {
+            let socket = tokio::net::UdpSocket::from_std(self.socket.try_clone()?)?;
+            for segment in &segments {
+                socket.send_to(segment.as_bytes(), &self.daemon_address).await?;
+            }
+            Ok::<_, std::io::Error>(())
+        }).await.map_err(|e| OTelSdkError::InternalFailure(e.to_string()))?;
+        match batch.len() - segments.len() {
+            0 => Ok(()),
+            dropped => Err(OTelSdkError::InternalFailure(format!(
+                "{} spans larger than the {} bytes datagram limit of the X-Ray daemon were dropped", dropped, MAX_DATAGRAM_SIZE))),
+        }
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        self.resource = resource.clone();
+    }
+}
+
+
+/// Builds the X-Ray daemon span exporter.
+/// # Arguments
+/// * `daemon_address` - The UDP address of the X-Ray daemon.
+/// # Returns
+/// A `Result` containing the span exporter or a `TraceError`.
+#[cfg(not(feature = "noop"))]
+pub(crate) fn get_xray_span_exporter(daemon_address: &str) -> Result<XRayDaemonExporter, TraceError> {
+    XRayDaemonExporter::new(daemon_address).map_err(|err| TraceError::from(err.to_string()))
+}
+
+
+/// Initializes the X-Ray tracer provider, generating X-Ray compatible IDs and sending the spans to the
+/// X-Ray daemon, if enabled.
+/// # Arguments
+/// * `config` - The X-Ray trace configuration.
+/// * `resource` - The resource of the traces.
+/// # Returns
+/// A `Result` containing the initialized `SDKTracerProvider` or a `TraceError`.
+#[cfg(not(feature = "noop"))]
+pub async fn get_xray_tracer_provider(config: &XRayTraceConfig, resource: &Resource) -> Result<SDKTracerProvider, TraceError> {
+    let builder = with_span_limits(SDKTracerProvider::builder(), &config.span_limits)
+        .with_resource(resource.clone())
+        .with_sampler(build_sampler(&config.sampler))
+        .with_id_generator(build_id_generator(&config.id_generator));
+    Ok(match &config.daemon_address {
+        Some(daemon_address) => with_export_mode(builder, get_xray_span_exporter(daemon_address)?, config.export_mode, &ExportPolicyConfig::default(), "xray").build(),
+        None => builder.build(),
+    })
+}
+
+
+/// Stub of `get_xray_tracer_provider` with the `noop` feature, returning a tracer provider without exporter.
+#[cfg(feature = "noop")]
+pub async fn get_xray_tracer_provider(_config: &XRayTraceConfig, _resource: &Resource) -> Result<SDKTracerProvider, TraceError> {
+    Ok(SDKTracerProvider::builder().build())
+}
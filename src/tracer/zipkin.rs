@@ -0,0 +1,61 @@
+use std::sync::Arc;
+#[cfg(not(feature = "noop"))]
+use opentelemetry_http::HttpClient;
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::trace::TraceError;
+use opentelemetry_sdk::trace::SdkTracerProvider as SDKTracerProvider;
+#[cfg(not(feature = "noop"))]
+use opentelemetry_zipkin::ZipkinExporter;
+use crate::auth::GetToken;
+use crate::config::ZipkinConfig;
+#[cfg(not(feature = "noop"))]
+use crate::config::ExportPolicyConfig;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::id_generator::build_id_generator;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::reqwest::ReqwestTracerClient;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::sampling::build_sampler;
+#[cfg(not(feature = "noop"))]
+use crate::tracer::{with_export_mode, with_span_limits};
+
+
+/// Builds the Zipkin span exporter, sending spans in Zipkin v2 JSON with authentication.
+/// # Arguments
+/// * `config` - The Zipkin trace configuration.
+/// * `token_provider` - An `Arc<dyn GetToken>` to provide access tokens.
+/// # Returns
+/// A `Result` containing the span exporter or a `TraceError`.
+#[cfg(not(feature = "noop"))]
+pub(crate) fn get_zipkin_span_exporter(config: &ZipkinConfig, token_provider: Arc<dyn GetToken>) -> Result<ZipkinExporter, TraceError> {
+    let http_client = Arc::new(reqwest::Client::new()) as Arc<dyn HttpClient>;
+    ZipkinExporter::builder()
+        .with_collector_endpoint(config.endpoint.clone())
+        .with_http_client(ReqwestTracerClient::new(http_client, token_provider))
+        .build()
+        .map_err(|err| TraceError::from(err.to_string()))
+}
+
+
+/// Initializes the Zipkin tracer provider.
+/// # Arguments
+/// * `config` - The Zipkin trace configuration.
+/// * `resource` - The resource of the traces.
+/// * `token_provider` - An `Arc<dyn GetToken>` to provide access tokens.
+/// # Returns
+/// A `Result` containing the initialized `SDKTracerProvider` or a `TraceError`.
+#[cfg(not(feature = "noop"))]
+pub async fn get_zipkin_tracer_provider(config: &ZipkinConfig, resource: &Resource, token_provider: Arc<dyn GetToken>) -> Result<SDKTracerProvider, TraceError> {
+    let builder = with_span_limits(SDKTracerProvider::builder(), &config.span_limits)
+        .with_resource(resource.clone())
+        .with_sampler(build_sampler(&config.sampler))
+        .with_id_generator(build_id_generator(&config.id_generator));
+    Ok(with_export_mode(builder, get_zipkin_span_exporter(config, token_provider)?, config.export_mode, &ExportPolicyConfig::default(), "zipkin").build())
+}
+
+
+/// Stub of `get_zipkin_tracer_provider` with the `noop` feature, returning a tracer provider without exporter.
+#[cfg(feature = "noop")]
+pub async fn get_zipkin_tracer_provider(_config: &ZipkinConfig, _resource: &Resource, _token_provider: Arc<dyn GetToken>) -> Result<SDKTracerProvider, TraceError> {
+    Ok(SDKTracerProvider::builder().build())
+}